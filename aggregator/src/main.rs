@@ -0,0 +1,40 @@
+//! A program that verifies many compressed fibonacci proofs and commits a digest of all of their
+//! public values, so that one proof of it, e.g. a Groth16 one, covers every run at the cost of
+//! verifying a single proof on-chain.
+
+// These two lines are necessary for the program to properly compile.
+//
+// Under the hood, we wrap your main function with some extra code so that it behaves properly
+// inside the zkVM.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolType;
+use fibonacci_lib::{
+    aggregation_digest, child_vkey, public_values_digest, AggregatedPublicValuesStruct,
+    AGGREGATION_VERSION,
+};
+
+pub fn main() {
+    // Read the verification key hash of the fibonacci program, as u32 words, and the public values
+    // of every proof to aggregate. The proofs themselves are written to stdin by the host as
+    // deferred proofs, which the zkVM checks when the proof of this program is generated.
+    let vkey: [u32; 8] = sp1_zkvm::io::read();
+    let public_values: Vec<Vec<u8>> = sp1_zkvm::io::read();
+    assert!(!public_values.is_empty(), "no proofs to aggregate");
+
+    // Verify that each of them is the public values of a proof of the fibonacci program.
+    for public_values in &public_values {
+        sp1_zkvm::lib::verify::verify_sp1_proof(&vkey, &public_values_digest(public_values));
+    }
+
+    // Commit to the fibonacci program and to the public values of every proof, in order, so a
+    // verifier given them can check they're the ones the aggregated proofs committed.
+    let aggregated = AggregatedPublicValuesStruct {
+        version: AGGREGATION_VERSION,
+        vkey: child_vkey(&vkey),
+        count: public_values.len() as u32,
+        public_values_digest: aggregation_digest(public_values.iter().map(Vec::as_slice)),
+    };
+    sp1_zkvm::io::commit_slice(&AggregatedPublicValuesStruct::abi_encode(&aggregated));
+}
@@ -2,12 +2,8 @@
 pragma solidity ^0.8.20;
 
 import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
-
-struct PublicValuesStruct {
-    uint32 n;
-    uint32 a;
-    uint32 b;
-}
+import {InclusionProofStruct, PublicValues, PublicValuesStruct} from "./PublicValues.sol";
+import {MonotreeProof} from "./MonotreeProof.sol";
 
 /// @title Fibonacci.
 /// @author Succinct Labs
@@ -35,10 +31,30 @@ contract Fibonacci {
     function verifyFibonacciProof(bytes calldata _publicValues, bytes calldata _proofBytes)
         public
         view
-        returns (uint32, uint32, uint32)
+        returns (uint32, uint64, uint64)
     {
         ISP1Verifier(verifier).verifyProof(fibonacciProgramVKey, _publicValues, _proofBytes);
-        PublicValuesStruct memory publicValues = abi.decode(_publicValues, (PublicValuesStruct));
+        PublicValuesStruct memory publicValues = PublicValues.decode(_publicValues);
         return (publicValues.n, publicValues.a, publicValues.b);
     }
+
+    /// @notice The entrypoint for acting on the leaf a proof inserts into the tree, rather than
+    ///         only on the root. Checks the committed inclusion proof of the leaf against the root.
+    /// @param _proofBytes The encoded proof.
+    /// @param _publicValues The encoded public values, followed by the inclusion proof.
+    function verifyFibonacciLeaf(bytes calldata _publicValues, bytes calldata _proofBytes)
+        public
+        view
+        returns (bytes32, bytes32, bytes32)
+    {
+        ISP1Verifier(verifier).verifyProof(fibonacciProgramVKey, _publicValues, _proofBytes);
+        PublicValuesStruct memory publicValues = PublicValues.decode(_publicValues);
+        InclusionProofStruct memory inclusionProof = PublicValues.decodeInclusionProof(_publicValues);
+        bytes32 root = MonotreeProof.toBytes32(publicValues.root);
+        require(
+            MonotreeProof.verify(publicValues.hasher, root, publicValues.leaf, inclusionProof),
+            "the leaf isn't in the tree"
+        );
+        return (root, publicValues.key, publicValues.leaf);
+    }
 }
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {InclusionProofStruct} from "./PublicValues.sol";
+
+/// @title MonotreeProof.
+/// @notice Checking monotree Merkle proofs against a root committed by the fibonacci program, so
+///         contracts can act on individual leaves of the tree rather than only on its root.
+/// @dev Hashes the proof the way monotree's `verify_proof` and `proof_path` in `lib/` do. Only
+///      trees built with SHA-256 can be checked, as it's the only one of the program's hashers
+///      the EVM has a precompile for.
+library MonotreeProof {
+    /// @notice The id the program commits as `hasher` for trees built with SHA-256.
+    uint8 internal constant SHA2 = 2;
+
+    /// @notice Whether a proof shows that `_leaf` is in the tree with `_root`.
+    /// @param _hasher The hasher the tree is built with, as committed by the program.
+    /// @param _root The root of the tree.
+    /// @param _leaf The leaf to check the inclusion of.
+    /// @param _proof The Merkle proof of the leaf, from the root down.
+    function verify(uint8 _hasher, bytes32 _root, bytes32 _leaf, InclusionProofStruct memory _proof)
+        internal
+        pure
+        returns (bool)
+    {
+        require(_hasher == SHA2, "unsupported hasher");
+        require(_proof.rights.length == _proof.cuts.length, "malformed inclusion proof");
+
+        bytes32 hash = _leaf;
+        for (uint256 i = _proof.cuts.length; i > 0; i--) {
+            bytes memory cut = _proof.cuts[i - 1];
+            bool right = _proof.rights[i - 1];
+            // A step going right splits its cut around the hash, which an empty cut can't be.
+            if (right && cut.length == 0) {
+                return false;
+            }
+            hash = sha256(node(hash, cut, right ? cut.length - 1 : 0));
+        }
+        return hash == _root;
+    }
+
+    /// @notice The root committed by the program as a `uint8[32]`, as a `bytes32`.
+    function toBytes32(uint8[32] memory _root) internal pure returns (bytes32 root) {
+        for (uint256 i = 0; i < 32; i++) {
+            root |= bytes32(uint256(_root[i]) << (8 * (31 - i)));
+        }
+    }
+
+    /// @notice The node a step hashes: the cut with `_hash` inserted at `_at`.
+    function node(bytes32 _hash, bytes memory _cut, uint256 _at) private pure returns (bytes memory) {
+        bytes memory result = new bytes(_cut.length + 32);
+        for (uint256 j = 0; j < _at; j++) {
+            result[j] = _cut[j];
+        }
+        for (uint256 j = 0; j < 32; j++) {
+            result[_at + j] = _hash[j];
+        }
+        for (uint256 j = _at; j < _cut.length; j++) {
+            result[j + 32] = _cut[j];
+        }
+        return result;
+    }
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+// Generated by `cargo run --release -- gen-sol` from `PublicValuesStruct` in `lib/`. Do not edit.
+pragma solidity ^0.8.20;
+
+/// @notice The public values committed by the fibonacci program.
+struct PublicValuesStruct {
+    uint32 version;
+    uint32 n;
+    uint32 offset;
+    bytes32 offset_hash;
+    uint64 a;
+    uint64 b;
+    bytes32 key;
+    bytes32 leaf;
+    uint32 insert_count;
+    uint8[32] root;
+    uint64 nonce;
+    uint64 timestamp;
+    bytes32 inputs_digest;
+    bytes32 child_vkey;
+    bool overflowed;
+    bytes32 roots_digest;
+    bytes32 prior_root;
+    uint32 entry_count;
+    bool inclusion_verified;
+    bytes32 absent_key;
+    bytes32 updates_digest;
+    uint8 hasher;
+    uint32 removal_count;
+    bytes32 update_key;
+    bytes32 old_leaf;
+    bytes32 new_leaf;
+    uint32 namespace_count;
+    bytes32 namespaces_digest;
+    uint32 blob_count;
+    bytes32 blobs_digest;
+    bool big;
+    uint64 modulus;
+    uint64 a_mod;
+    uint64 b_mod;
+    uint64 recurrence_a0;
+    uint64 recurrence_a1;
+    int64 recurrence_p;
+    int64 recurrence_q;
+    uint8 error_code;
+    bool wide;
+    uint64 a_hi;
+    uint64 b_hi;
+    uint64 pisano_modulus;
+    uint64 pisano_period;
+    bool signed;
+    int64 signed_n;
+    int64 a_signed;
+    int64 b_signed;
+    uint32 table_stride;
+    uint32 table_len;
+    bytes32 table_root;
+    bool membership;
+    uint64 membership_x;
+    bool membership_found;
+    uint32 membership_index;
+    bool golden_ratio;
+    uint128 ratio;
+    bytes32 previous_digest;
+}
+
+/// @notice The monotree Merkle proof of the committed leaf, committed right after the public
+///         values. Each step is a cut of a sibling node and whether the path goes right at it,
+///         from the root down. See `MonotreeProof` for checking it.
+struct InclusionProofStruct {
+    bool[] rights;
+    bytes[] cuts;
+}
+
+/// @title PublicValues.
+/// @notice Decoding the public values committed by the fibonacci program.
+library PublicValues {
+    /// @notice The layout version of the public values this library decodes.
+    uint32 internal constant VERSION = 30;
+
+    /// @notice The size of the ABI-encoded public values, i.e. where the inclusion proof starts.
+    uint256 internal constant STRUCT_SIZE = 2848;
+
+    /// @notice Decode the ABI-encoded public values of a proof, rejecting other layout versions.
+    /// @param _publicValues The encoded public values.
+    function decode(bytes calldata _publicValues) internal pure returns (PublicValuesStruct memory) {
+        PublicValuesStruct memory publicValues = abi.decode(_publicValues, (PublicValuesStruct));
+        require(publicValues.version == VERSION, "unsupported public values version");
+        return publicValues;
+    }
+
+    /// @notice Decode the inclusion proof committed after the ABI-encoded public values.
+    /// @param _publicValues The encoded public values.
+    function decodeInclusionProof(bytes calldata _publicValues) internal pure returns (InclusionProofStruct memory) {
+        return abi.decode(_publicValues[STRUCT_SIZE:], (InclusionProofStruct));
+    }
+}
@@ -7,9 +7,11 @@ import {Fibonacci} from "../src/Fibonacci.sol";
 import {SP1VerifierGateway} from "@sp1-contracts/SP1VerifierGateway.sol";
 
 struct SP1ProofFixtureJson {
-    uint32 a;
-    uint32 b;
+    uint64 a;
+    uint64 b;
     uint32 n;
+    uint32 offset;
+    bytes32 offsetHash;
     bytes proof;
     bytes publicValues;
     bytes32 vkey;
@@ -41,7 +43,7 @@ contract FibonacciTest is Test {
 
         vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
 
-        (uint32 n, uint32 a, uint32 b) = fibonacci.verifyFibonacciProof(fixture.publicValues, fixture.proof);
+        (uint32 n, uint64 a, uint64 b) = fibonacci.verifyFibonacciProof(fixture.publicValues, fixture.proof);
         assert(n == fixture.n);
         assert(a == fixture.a);
         assert(b == fixture.b);
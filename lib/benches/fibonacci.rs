@@ -0,0 +1,42 @@
+//! Native timings of the fibonacci implementations, to cross-reference with the cycles they take
+//! inside the zkVM from the `bench` subcommand when picking one for the program.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fibonacci_lib::{fibonacci, fibonacci_big, fibonacci_fast, u256};
+use std::hint::black_box;
+
+/// The default n of the `bench` subcommand, and larger ones where the methods diverge.
+const N: [u32; 6] = [10, 20, 40, 1_000, 100_000, 1_000_000];
+
+fn wrapping(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wrapping");
+    for n in N {
+        group.bench_with_input(BenchmarkId::new("iterative", n), &n, |b, &n| {
+            b.iter(|| fibonacci(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("fast_doubling", n), &n, |b, &n| {
+            b.iter(|| fibonacci_fast(black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+fn exact(c: &mut Criterion) {
+    let mut group = c.benchmark_group("exact");
+    for n in N.into_iter().filter(|&n| n <= 100_000) {
+        group.bench_with_input(BenchmarkId::new("big", n), &n, |b, &n| {
+            b.iter(|| fibonacci_big(black_box(n)))
+        });
+    }
+    // Outside the zkVM it multiplies without the bigint precompile, so it only compares the
+    // 256-bit doubling with `BigUint` natively.
+    for n in [10, 20, 40, u256::FIBONACCI_U256_MAX_N] {
+        group.bench_with_input(BenchmarkId::new("u256", n), &n, |b, &n| {
+            b.iter(|| u256::fibonacci(black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, wrapping, exact);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+//! Deserializes arbitrary bytes as the inputs and the tree witness the program reads from stdin,
+//! run natively, which must reject malformed ones with an error rather than panic, and read back
+//! whatever they accept unchanged.
+#![no_main]
+
+use fibonacci_lib::{FibonacciInputs, TreeWitness};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(inputs) = bincode::deserialize::<FibonacciInputs>(data) {
+        let bytes = bincode::serialize(&inputs).unwrap();
+        assert_eq!(bincode::deserialize::<FibonacciInputs>(&bytes).unwrap(), inputs);
+    }
+    if let Ok(witness) = bincode::deserialize::<TreeWitness>(data) {
+        let bytes = bincode::serialize(&witness).unwrap();
+        assert_eq!(bincode::deserialize::<TreeWitness>(&bytes).unwrap(), witness);
+    }
+});
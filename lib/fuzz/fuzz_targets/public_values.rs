@@ -0,0 +1,26 @@
+//! Decodes arbitrary bytes as public values in every encoding, the way verifiers read untrusted
+//! proofs, which must reject malformed ones with an error rather than panic.
+#![no_main]
+
+use fibonacci_lib::{
+    decode_big_results, split_public_values, PublicValuesEncoding, PublicValuesStruct,
+    VersionedPublicValues,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = VersionedPublicValues::abi_decode(data);
+    let _ = PublicValuesStruct::try_from(data);
+    let _ = PublicValuesStruct::borsh_decode(data);
+    let _ = PublicValuesStruct::ssz_decode(data);
+    for encoding in [
+        PublicValuesEncoding::Abi,
+        PublicValuesEncoding::Borsh,
+        PublicValuesEncoding::Ssz,
+    ] {
+        if let Ok((_, proof, big_results)) = split_public_values(data, encoding) {
+            let _ = proof.steps();
+            let _ = decode_big_results(true, big_results);
+        }
+    }
+});
@@ -0,0 +1,66 @@
+//! Q64.64 fixed-point numbers, the 128-bit layout DeFi contracts commonly take non-integer values
+//! in, so the program can commit them as a plain `uint128` a contract uses as is.
+
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A non-negative number with 64 integer bits and 64 fractional bits, i.e. `bits / 2^64`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Q64x64(pub u128);
+
+impl Q64x64 {
+    /// The number of fractional bits.
+    pub const FRACTIONAL_BITS: u32 = 64;
+
+    /// 1.
+    pub const ONE: Self = Self(1 << Self::FRACTIONAL_BITS);
+
+    /// The golden ratio `(1 + sqrt(5)) / 2`, rounded down.
+    pub const GOLDEN_RATIO: Self = Self(0x1_9e37_79b9_7f4a_7c15);
+
+    /// `numerator / denominator` rounded down, or `None` for a zero denominator. It always fits,
+    /// as the numerator is less than 2^64.
+    pub fn from_ratio(numerator: u64, denominator: u64) -> Option<Self> {
+        (u128::from(numerator) << Self::FRACTIONAL_BITS)
+            .checked_div(u128::from(denominator))
+            .map(Self)
+    }
+
+    /// The integer part.
+    pub fn integer(self) -> u64 {
+        (self.0 >> Self::FRACTIONAL_BITS) as u64
+    }
+
+    /// The fractional part, in units of 2^-64.
+    pub fn fraction(self) -> u64 {
+        self.0 as u64
+    }
+
+    /// The closest `f64`, which keeps only 53 of the 128 bits.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::ONE.0 as f64
+    }
+}
+
+impl From<u64> for Q64x64 {
+    fn from(integer: u64) -> Self {
+        Self(u128::from(integer) << Self::FRACTIONAL_BITS)
+    }
+}
+
+/// The decimal digits, with 20 fractional digits rounded down, enough to tell any two apart.
+impl fmt::Display for Q64x64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.", self.integer())?;
+        let mut fraction = u128::from(self.fraction());
+        for _ in 0..20 {
+            fraction *= 10;
+            write!(f, "{}", fraction >> Self::FRACTIONAL_BITS)?;
+            fraction &= u128::from(u64::MAX);
+        }
+        Ok(())
+    }
+}
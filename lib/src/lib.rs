@@ -1,19 +1,2083 @@
-use alloy_sol_types::sol;
+//! The fibonacci functions and the public values types the program and the script share.
+//!
+//! Without the default `std` feature the crate is `no_std`, only needing `alloc`, so it can be
+//! linked into other guest programs and embedded verifiers too. Serde support for the inputs and
+//! the public values types, and the helpers only hosts need, like [`verify_public_values`], come
+//! with the `serde` and `std` features.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use alloy_primitives::{keccak256, B256};
+use alloy_sol_types::{sol, SolCall, SolType};
+use num_bigint::BigUint;
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub mod fixed;
+pub mod u256;
+
+use fixed::Q64x64;
 
 sol! {
     /// The public values encoded as a struct that can be easily deserialized inside Solidity.
+    ///
+    /// With the `serde` feature it also (de)serializes with serde, e.g. to JSON, with the
+    /// `bytes32` fields as hex strings.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     struct PublicValuesStruct {
+        uint32 version;
+        uint32 n;
+        uint32 offset;
+        bytes32 offset_hash;
+        uint64 a;
+        uint64 b;
+        bytes32 key;
+        bytes32 leaf;
+        uint32 insert_count;
+        uint8[32] root;
+        uint64 nonce;
+        uint64 timestamp;
+        bytes32 inputs_digest;
+        bytes32 child_vkey;
+        bool overflowed;
+        bytes32 roots_digest;
+        bytes32 prior_root;
+        uint32 entry_count;
+        bool inclusion_verified;
+        bytes32 absent_key;
+        bytes32 updates_digest;
+        uint8 hasher;
+        uint32 removal_count;
+        bytes32 update_key;
+        bytes32 old_leaf;
+        bytes32 new_leaf;
+        uint32 namespace_count;
+        bytes32 namespaces_digest;
+        uint32 blob_count;
+        bytes32 blobs_digest;
+        bool big;
+        uint64 modulus;
+        uint64 a_mod;
+        uint64 b_mod;
+        uint64 recurrence_a0;
+        uint64 recurrence_a1;
+        int64 recurrence_p;
+        int64 recurrence_q;
+        uint8 error_code;
+        bool wide;
+        uint64 a_hi;
+        uint64 b_hi;
+        uint64 pisano_modulus;
+        uint64 pisano_period;
+        bool signed;
+        int64 signed_n;
+        int64 a_signed;
+        int64 b_signed;
+        uint32 table_stride;
+        uint32 table_len;
+        bytes32 table_root;
+        bool membership;
+        uint64 membership_x;
+        bool membership_found;
+        uint32 membership_index;
+        bool golden_ratio;
+        uint128 ratio;
+        bytes32 previous_digest;
+    }
+
+    /// The public values committed by the first version of the program, which didn't commit the
+    /// offset, the nonce or the timestamp, and computed `a` and `b` as 32-bit numbers.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct LegacyPublicValuesStruct {
         uint32 n;
         uint32 a;
         uint32 b;
         uint8[32] root;
     }
+
+    /// A monotree Merkle proof that the committed `leaf` is at the committed `key` in the tree
+    /// with the committed `root`, committed right after the public values.
+    ///
+    /// Each step is a cut of a sibling node and whether the path goes right at it, from the root
+    /// down, as returned by monotree's `get_merkle_proof`.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct InclusionProofStruct {
+        bool[] rights;
+        bytes[] cuts;
+    }
+
+    /// The public values committed by the aggregator program, which verifies many fibonacci proofs
+    /// at once so that verifying its proof on-chain stands in for verifying each of them.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct AggregatedPublicValuesStruct {
+        uint32 version;
+        bytes32 vkey;
+        uint32 count;
+        bytes32 public_values_digest;
+    }
+
+    /// The entrypoint of `contracts/src/Fibonacci.sol` verifying a proof, see
+    /// [`PublicValuesStruct::solidity_calldata`].
+    function verifyFibonacciProof(bytes calldata _publicValues, bytes calldata _proofBytes)
+        external
+        view
+        returns (uint32, uint64, uint64);
+}
+
+/// The layout version committed as the first field of [`PublicValuesStruct`].
+///
+/// Bump it whenever the fields of `PublicValuesStruct` change, so that decoders can tell which
+/// layout a proof was made with instead of silently mis-decoding it.
+pub const PUBLIC_VALUES_VERSION: u32 = 30;
+
+/// The `error_code` the program commits when the results overflow with `checked` inputs, see
+/// [`FibonacciInputs::checked_results`]. It commits 0 when it doesn't abort.
+pub const ERROR_OVERFLOW: u8 = 1;
+
+/// The inputs the program reads from stdin.
+///
+/// The host writes this whole struct to stdin and the program reads it back in one go, so the two
+/// sides can't disagree on the order the individual values are written in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct FibonacciInputs {
+    /// Which fibonacci number to compute.
+    pub n: u32,
+    /// The offset added to the computed fibonacci numbers.
+    pub offset: u32,
+    /// A private salt to hide the offset with. When given, the program commits a hash of the
+    /// offset and the salt instead of the offset itself.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub salt: Option<[u8; 32]>,
+    /// A number chosen by the caller, committed so consumers can tell proofs requested for
+    /// different purposes apart and reject replayed ones.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nonce: u64,
+    /// When the proof was requested, in seconds since the Unix epoch, committed so consumers can
+    /// reject stale proofs.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timestamp: u64,
+    /// How to encode the public values the program commits.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub encoding: PublicValuesEncoding,
+    /// The key to insert into the tree, instead of the default one, see [`tree_entry`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub key: Option<[u8; 32]>,
+    /// The leaf to insert into the tree, instead of the one derived from the result, see
+    /// [`tree_entry`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub leaf: Option<[u8; 32]>,
+    /// More `(key, leaf)` entries to insert into the tree in one batch, before the key and the
+    /// leaf above.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub entries: Vec<([u8; 32], [u8; 32])>,
+    /// A key to prove absent from the tree at the prior root, before any inserts, e.g. a nullifier
+    /// that must not have been spent yet.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub absent_key: Option<[u8; 32]>,
+    /// Which hasher to build the tree with.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hasher: TreeHasher,
+    /// Keys to remove from the tree, after the batch of entries and before the key and the leaf.
+    /// Each of them must be in the tree.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub removals: Vec<[u8; 32]>,
+    /// A change of the leaf at a key already in the tree, applied first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub leaf_update: Option<LeafUpdate>,
+    /// Index the tree by `n`, inserting the entry from [`indexed_tree_entry`] rather than from
+    /// [`tree_entry`] by default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub index_by_n: bool,
+    /// Entries to insert into other trees than the main one, each with its own root, e.g. a
+    /// "balances" tree and a "nonces" tree. Applied after the updates of the main tree.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub namespaces: Vec<Namespace>,
+    /// A seed to derive more entries from, see [`seeded_entries`], inserted in the same batch
+    /// after `entries`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub seed: Option<u64>,
+    /// How many entries to derive from `seed`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub seeded_entries: u32,
+    /// `(key, blob)` entries with leaf data of any length, inserted in the same batch after the
+    /// others with the hash of the blob as the leaf, see [`blob_leaf`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub blobs: Vec<([u8; 32], Vec<u8>)>,
+    /// How to compute the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub method: FibonacciMethod,
+    /// Also compute the fibonacci numbers exactly, see [`fibonacci_big`], and commit them after the
+    /// inclusion proof, see [`encode_big_results`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub big: bool,
+    /// A modulus to also compute the fibonacci numbers modulo, e.g. a prime field's, see
+    /// [`fibonacci_mod_offset`]. Must be non-zero.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub modulus: Option<u64>,
+    /// Which sequence to compute the n'th element of, in place of the fibonacci numbers. `big` and
+    /// `modulus` are only supported for the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sequence: Sequence,
+    /// Abort with [`ERROR_OVERFLOW`] committed as `error_code` if the fibonacci numbers overflow,
+    /// instead of committing them wrapped around, see [`FibonacciInputs::checked_results`]. Only
+    /// supported for the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checked: bool,
+    /// Also compute the fibonacci numbers with 128 bits, see [`fibonacci_u128`], and commit their
+    /// high halves, `a` and `b` being their low halves. Only supported for the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wide: bool,
+    /// A modulus to also compute the Pisano period of, i.e. the period of the fibonacci numbers
+    /// modulo it, see [`pisano_period`]. Must be non-zero.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pisano: Option<u64>,
+    /// A signed index to also compute the fibonacci number at, negative ones giving the
+    /// negafibonacci numbers, see [`fibonacci_signed`]. Only supported for the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub signed_index: Option<i64>,
+    /// Also commit every k'th fibonacci number up to the n'th, for this k, as the root of a Merkle
+    /// tree over them, see [`fibonacci_table`] and [`table_root`]. Must be non-zero, and only
+    /// supported for the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub table_stride: Option<u32>,
+    /// A number to also check for being a fibonacci number, committing its index if it is one, see
+    /// [`is_fibonacci`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub membership: Option<u64>,
+    /// Also compute `F(n+1) / F(n)`, which tends to the golden ratio, as a Q64.64 fixed-point
+    /// number, see [`fibonacci_ratio`]. Only supported for the fibonacci numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub golden_ratio: bool,
+    /// The proof of the previous run to verify inside the program, chaining this run onto it, see
+    /// [`PreviousProof`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub previous: Option<PreviousProof>,
+}
+
+/// The previous run of a chain of runs, each picking up the tree where the one before left it.
+///
+/// The program verifies the previous run's proof with the zkVM's deferred proof verification and
+/// checks that its `root` is the prior root this run starts from, then commits the verification
+/// key as `child_vkey` and the digest of the public values as `previous_digest`. Verifying the
+/// latest proof of the chain thus stands in for verifying every one before it. The host writes the
+/// compressed proof itself to stdin along with the inputs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct PreviousProof {
+    /// The u32 words of the verification key hash of the previous proof.
+    pub vkey: [u32; 8],
+    /// The public values the previous proof committed, in the encoding of this run.
+    pub public_values: Vec<u8>,
+}
+
+/// A tree independent of the main one, named so the host can tell it apart, and the entries to
+/// insert into it in one batch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct Namespace {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub entries: Vec<([u8; 32], [u8; 32])>,
+}
+
+/// A change of the leaf at `key` from `old_leaf`, which must be the leaf there at the prior root,
+/// to `new_leaf`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct LeafUpdate {
+    pub key: [u8; 32],
+    pub old_leaf: [u8; 32],
+    pub new_leaf: [u8; 32],
+}
+
+impl FibonacciInputs {
+    /// The `inputs_digest` the program commits when reading these inputs, as written to stdin by
+    /// the host, i.e. bincode-encoded.
+    #[cfg(feature = "std")]
+    pub fn digest(&self) -> B256 {
+        let bytes = bincode::serialize(self).expect("failed to serialize the inputs");
+        inputs_digest([bytes.as_slice()])
+    }
+
+    /// The key and the leaf the program inserts into the tree for the offset fibonacci number `b`:
+    /// the given ones, falling back to [`tree_entry`], or [`indexed_tree_entry`] when indexing by
+    /// `n`, for either that isn't given.
+    pub fn tree_entry(&self, b: u64) -> ([u8; 32], [u8; 32]) {
+        let (key, leaf) = if self.index_by_n {
+            indexed_tree_entry(self.n, b)
+        } else {
+            tree_entry(b)
+        };
+        (self.key.unwrap_or(key), self.leaf.unwrap_or(leaf))
+    }
+
+    /// The batch of entries the program inserts in one go: `entries`, then the ones derived from
+    /// `seed`, if any, then the ones of `blobs`.
+    pub fn batch(&self) -> Vec<([u8; 32], [u8; 32])> {
+        let seeded = self
+            .seed
+            .map(|seed| seeded_entries(seed, self.seeded_entries))
+            .unwrap_or_default();
+        let blobs: Vec<_> = self
+            .blobs
+            .iter()
+            .map(|(key, blob)| (*key, blob_leaf(blob)))
+            .collect();
+        [self.entries.as_slice(), &seeded, &blobs].concat()
+    }
+
+    /// The number of entries in the [batch](FibonacciInputs::batch), without deriving them.
+    pub fn batch_len(&self) -> usize {
+        let seeded = if self.seed.is_some() {
+            self.seeded_entries as usize
+        } else {
+            0
+        };
+        self.entries.len() + seeded + self.blobs.len()
+    }
+
+    /// The n'th element of the sequence and the next one, before adding the offset.
+    pub fn results(&self) -> (u64, u64) {
+        self.sequence.compute(self.n, self.method)
+    }
+
+    /// The n'th fibonacci number and the next one with the offset added, or an error if either
+    /// overflows, in which case the program aborts given `checked` inputs.
+    pub fn checked_results(&self) -> Result<(u64, u64), OverflowError> {
+        let (a, b) = fibonacci_checked(self.n)?;
+        let offset = u64::from(self.offset);
+        let overflow = OverflowError { n: self.n };
+        Ok((
+            a.checked_add(offset).ok_or(overflow)?,
+            b.checked_add(offset).ok_or(overflow)?,
+        ))
+    }
+
+    /// The `child_vkey` and the `previous_digest` the program commits when chaining onto the
+    /// [previous proof](FibonacciInputs::previous), both zero otherwise.
+    pub fn chained(&self) -> (B256, B256) {
+        self.previous
+            .as_ref()
+            .map(|previous| {
+                (
+                    child_vkey(&previous.vkey),
+                    public_values_digest(&previous.public_values).into(),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// The n'th fibonacci number and the next one with 128 bits, with the offset added, wrapping
+    /// around on overflows. Their low halves are the offset results of [`fibonacci`].
+    pub fn wide_results(&self) -> (u128, u128) {
+        let (a, b) = fibonacci_u128(self.n);
+        let offset = u128::from(self.offset);
+        (a.wrapping_add(offset), b.wrapping_add(offset))
+    }
+
+    /// The fibonacci number at `signed_index` and the next one with the offset added, wrapping
+    /// around on overflows, or `None` without a signed index.
+    pub fn signed_results(&self) -> Option<(i64, i64)> {
+        self.signed_index.map(|n| {
+            let (a, b) = fibonacci_signed(n);
+            let offset = i64::from(self.offset);
+            (a.wrapping_add(offset), b.wrapping_add(offset))
+        })
+    }
+
+    /// The `(key, leaf)` updates the program applies to the tree, in order, for the offset
+    /// fibonacci number `b`: the leaf update, then the batch of entries, then the removals, each as
+    /// an update to a zero leaf, then the key and the leaf, once, unless the offset is zero.
+    pub fn updates(&self, b: u64) -> Vec<([u8; 32], [u8; 32])> {
+        let entry = (self.offset > 0).then(|| self.tree_entry(b));
+        let leaf_update = self.leaf_update.map(|update| (update.key, update.new_leaf));
+        let removals = self.removals.iter().map(|key| (*key, [0; 32]));
+        leaf_update
+            .into_iter()
+            .chain(self.batch())
+            .chain(removals)
+            .chain(entry)
+            .collect()
+    }
+}
+
+/// The nodes of the tree the program reads when inserting into it, so it can pick up the tree at a
+/// prior root instead of building it from scratch.
+///
+/// The host writes this to stdin after [`FibonacciInputs`]. Monotree looks nodes up by their hash,
+/// and the program checks every node against its hash, so the nodes reachable from `root` can't be
+/// forged. The program commits `root` as `prior_root`, which binds the proof to the prior state
+/// rather than to the (possibly large) witness itself, so the witness isn't part of
+/// `inputs_digest`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TreeWitness {
+    /// The root of the tree before the program inserts into it, or `None` for an empty tree.
+    pub root: Option<[u8; 32]>,
+    /// The nodes the program reads, as `(hash, node)` pairs.
+    pub nodes: Vec<([u8; 32], Vec<u8>)>,
+    /// The Merkle proof of the key of [`FibonacciInputs::leaf_update`] at `root`, as returned by
+    /// monotree's `get_merkle_proof`. Given it, the program derives the nodes on the path to the
+    /// key from it, see [`proof_path`], so the host leaves them out of `nodes`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub leaf_update_proof: Option<Vec<(bool, Vec<u8>)>>,
+    /// The roots of the trees of [`FibonacciInputs::namespaces`] before the program inserts into
+    /// them, in the same order, with `None` for empty ones. Missing roots are taken to be `None`.
+    ///
+    /// Monotree addresses nodes by their hash, so the nodes of every tree are in `nodes` together.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub namespace_roots: Vec<Option<[u8; 32]>>,
+}
+
+/// The root a monotree Merkle proof of `leaf` leads to, along with the nodes on the way up to it as
+/// `(hash, node)` pairs, hashing them with `digest`.
+///
+/// This is how monotree's `verify_proof` hashes the proof, so the nodes are the ones the tree
+/// stores: given the proof of a key, they're the nodes reading the key goes through, or, with
+/// another leaf, the nodes changing the leaf at the key leaves behind.
+///
+/// `None` if the proof is malformed: a step going right splits its cut around the hash, which an
+/// empty cut can't be.
+pub fn proof_path(
+    digest: impl Fn(&[u8]) -> [u8; 32],
+    leaf: &[u8; 32],
+    proof: &[(bool, Vec<u8>)],
+) -> Option<([u8; 32], Vec<([u8; 32], Vec<u8>)>)> {
+    let mut hash = *leaf;
+    let mut nodes = Vec::with_capacity(proof.len());
+    for (right, cut) in proof.iter().rev() {
+        let node = if *right {
+            let (last, head) = cut.split_last()?;
+            [head, &hash, &[*last]].concat()
+        } else {
+            [&hash[..], cut].concat()
+        };
+        hash = digest(&node);
+        nodes.push((hash, node));
+    }
+    Some((hash, nodes))
+}
+
+/// Whether a monotree Merkle proof, as `(right, cut)` steps from the root down, shows that `leaf`
+/// is in the tree with `root`, hashing with `digest`, the hasher the tree is built with.
+///
+/// The proof is checked the way monotree's `verify_proof` checks it, see [`proof_path`], so
+/// exported proofs can be checked without linking monotree.
+pub fn verify_inclusion_proof(
+    digest: impl Fn(&[u8]) -> [u8; 32],
+    root: &[u8; 32],
+    leaf: &[u8; 32],
+    proof: &[(bool, Vec<u8>)],
+) -> bool {
+    proof_path(digest, leaf, proof).is_some_and(|(hash, _)| &hash == root)
+}
+
+/// Which hasher the program builds its tree with, committed as `hasher`, so roots can be made
+/// compatible with different downstream systems by the same program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TreeHasher {
+    #[default]
+    Blake3,
+    Blake2b,
+    /// SHA-256, accelerated by the zkVM's precompile.
+    Sha2,
+    /// Poseidon over the BN254 scalar field, see [`poseidon_digest`]. Needs the `poseidon`
+    /// feature.
+    #[cfg(feature = "poseidon")]
+    Poseidon,
+}
+
+impl TreeHasher {
+    /// The id the hasher is committed as.
+    pub fn id(self) -> u8 {
+        match self {
+            Self::Blake3 => 0,
+            Self::Blake2b => 1,
+            Self::Sha2 => 2,
+            #[cfg(feature = "poseidon")]
+            Self::Poseidon => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for TreeHasher {
+    type Error = String;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::Blake3),
+            1 => Ok(Self::Blake2b),
+            2 => Ok(Self::Sha2),
+            #[cfg(feature = "poseidon")]
+            3 => Ok(Self::Poseidon),
+            _ => Err(format!("unknown hasher id {}", id)),
+        }
+    }
+}
+
+impl core::str::FromStr for TreeHasher {
+    type Err = String;
+
+    fn from_str(hasher: &str) -> Result<Self, Self::Err> {
+        match hasher {
+            "blake3" => Ok(Self::Blake3),
+            "blake2b" => Ok(Self::Blake2b),
+            "sha2" => Ok(Self::Sha2),
+            #[cfg(feature = "poseidon")]
+            "poseidon" => Ok(Self::Poseidon),
+            _ => Err(format!(
+                "unknown hasher {:?}, expected \"blake3\", \"blake2b\", \"sha2\" or, with the \
+                 poseidon feature, \"poseidon\"",
+                hasher
+            )),
+        }
+    }
+}
+
+impl core::fmt::Display for TreeHasher {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Blake3 => "blake3",
+            Self::Blake2b => "blake2b",
+            Self::Sha2 => "sha2",
+            #[cfg(feature = "poseidon")]
+            Self::Poseidon => "poseidon",
+        })
+    }
+}
+
+/// The Poseidon hash of `bytes`, with circom's parameters over the BN254 scalar field, as a
+/// big-endian field element, so circuits on BN254 can recompute the roots of trees built with it.
+///
+/// The bytes are split into 31-byte chunks, which are always below the modulus, and absorbed 11
+/// at a time after the length, as circom's parameters take at most 12 inputs.
+#[cfg(feature = "poseidon")]
+pub fn poseidon_digest(bytes: &[u8]) -> [u8; 32] {
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
+    use light_poseidon::{Poseidon, PoseidonHasher};
+
+    let chunks: Vec<Fr> = bytes.chunks(31).map(Fr::from_be_bytes_mod_order).collect();
+    // The length goes first, so bytes differing only in trailing zeros hash differently. Empty
+    // bytes still take a hash of the length alone.
+    let mut hash = Fr::from(bytes.len() as u64);
+    let empty = chunks.is_empty().then_some(&[][..]);
+    for group in chunks.chunks(11).chain(empty) {
+        let inputs = [&[hash][..], group].concat();
+        hash = Poseidon::<Fr>::new_circom(inputs.len())
+            .and_then(|mut poseidon| poseidon.hash(&inputs))
+            .expect("circom's parameters take up to 12 inputs");
+    }
+    hash.into_bigint()
+        .to_bytes_be()
+        .try_into()
+        .expect("BN254 field elements are 32 bytes")
+}
+
+/// How the program computes the fibonacci numbers. Both methods wrap around on overflows alike and
+/// give the same results, so the choice only changes how many cycles the program takes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum FibonacciMethod {
+    /// Iterating n times, see [`fibonacci`].
+    #[default]
+    Iterative,
+    /// Fast doubling, in about log2(n) steps, see [`fibonacci_fast`].
+    FastDoubling,
+}
+
+impl FibonacciMethod {
+    /// Compute the n'th fibonacci number and the next one with this method.
+    pub fn compute(self, n: u32) -> (u64, u64) {
+        match self {
+            Self::Iterative => fibonacci(n),
+            Self::FastDoubling => fibonacci_fast(n),
+        }
+    }
+}
+
+impl core::str::FromStr for FibonacciMethod {
+    type Err = String;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method {
+            "iterative" => Ok(Self::Iterative),
+            "fast-doubling" => Ok(Self::FastDoubling),
+            _ => Err(format!(
+                "unknown method {:?}, expected \"iterative\" or \"fast-doubling\"",
+                method
+            )),
+        }
+    }
+}
+
+impl core::fmt::Display for FibonacciMethod {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Iterative => "iterative",
+            Self::FastDoubling => "fast-doubling",
+        })
+    }
+}
+
+/// Which sequence the program computes the n'th element of. Each is a linear recurrence
+/// `x(k+2) = p * x(k+1) - q * x(k)` from `x(0) = a0` and `x(1) = a1`, see [`linear_recurrence`],
+/// committed as `recurrence_a0`, `recurrence_a1`, `recurrence_p` and `recurrence_q`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Sequence {
+    /// The fibonacci numbers, computed with [`FibonacciInputs::method`].
+    #[default]
+    Fibonacci,
+    /// The Lucas numbers, see [`lucas`].
+    Lucas,
+    /// Any other linear recurrence of order two.
+    Recurrence { a0: u64, a1: u64, p: i64, q: i64 },
+}
+
+impl Sequence {
+    /// The `(a0, a1, p, q)` parameters of the recurrence.
+    pub fn params(self) -> (u64, u64, i64, i64) {
+        match self {
+            Self::Fibonacci => (0, 1, 1, -1),
+            Self::Lucas => (2, 1, 1, -1),
+            Self::Recurrence { a0, a1, p, q } => (a0, a1, p, q),
+        }
+    }
+
+    /// The sequence with the committed parameters, by name if it has one.
+    pub fn from_params(a0: u64, a1: u64, p: i64, q: i64) -> Self {
+        [Self::Fibonacci, Self::Lucas]
+            .into_iter()
+            .find(|sequence| sequence.params() == (a0, a1, p, q))
+            .unwrap_or(Self::Recurrence { a0, a1, p, q })
+    }
+
+    /// Compute the n'th element of the sequence and the next one, computing the fibonacci numbers
+    /// with `method`.
+    pub fn compute(self, n: u32, method: FibonacciMethod) -> (u64, u64) {
+        match self {
+            Self::Fibonacci => method.compute(n),
+            Self::Lucas => lucas(n),
+            Self::Recurrence { a0, a1, p, q } => linear_recurrence(a0, a1, p, q, n),
+        }
+    }
+}
+
+impl core::str::FromStr for Sequence {
+    type Err = String;
+
+    fn from_str(sequence: &str) -> Result<Self, Self::Err> {
+        match sequence.split(':').collect::<Vec<_>>().as_slice() {
+            ["fibonacci"] => Ok(Self::Fibonacci),
+            ["lucas"] => Ok(Self::Lucas),
+            ["recurrence", a0, a1, p, q] => {
+                let parse_error = |err: core::num::ParseIntError| err.to_string();
+                Ok(Self::Recurrence {
+                    a0: a0.parse().map_err(parse_error)?,
+                    a1: a1.parse().map_err(parse_error)?,
+                    p: p.parse().map_err(parse_error)?,
+                    q: q.parse().map_err(parse_error)?,
+                })
+            }
+            _ => Err(format!(
+                "unknown sequence {:?}, expected \"fibonacci\", \"lucas\" or \
+                 \"recurrence:A0:A1:P:Q\"",
+                sequence
+            )),
+        }
+    }
+}
+
+impl core::fmt::Display for Sequence {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Fibonacci => f.write_str("fibonacci"),
+            Self::Lucas => f.write_str("lucas"),
+            Self::Recurrence { a0, a1, p, q } => write!(f, "recurrence:{}:{}:{}:{}", a0, a1, p, q),
+        }
+    }
+}
+
+/// How the program encodes the public values it commits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum PublicValuesEncoding {
+    /// ABI-encoded [`PublicValuesStruct`], for decoding inside Solidity.
+    #[default]
+    Abi,
+    /// Borsh-encoded [`PackedPublicValues`], for Solana or NEAR programs. Needs the `borsh` feature
+    /// to encode or decode.
+    Borsh,
+    /// SSZ-encoded [`PackedPublicValues`], for beacon chain and consensus tooling. Needs the `ssz`
+    /// feature to encode or decode.
+    Ssz,
+}
+
+impl core::str::FromStr for PublicValuesEncoding {
+    type Err = String;
+
+    fn from_str(encoding: &str) -> Result<Self, Self::Err> {
+        match encoding {
+            "abi" => Ok(Self::Abi),
+            "borsh" => Ok(Self::Borsh),
+            "ssz" => Ok(Self::Ssz),
+            _ => Err(format!(
+                "unknown encoding {:?}, expected \"abi\", \"borsh\" or \"ssz\"",
+                encoding
+            )),
+        }
+    }
+}
+
+impl core::fmt::Display for PublicValuesEncoding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Abi => "abi",
+            Self::Borsh => "borsh",
+            Self::Ssz => "ssz",
+        })
+    }
+}
+
+impl PublicValuesEncoding {
+    /// The size of [`PublicValuesStruct`] in this encoding, i.e. where the [`InclusionProofStruct`]
+    /// committed after it starts.
+    pub fn struct_size(self) -> usize {
+        match self {
+            Self::Abi => <PublicValuesStruct as SolType>::ENCODED_SIZE
+                .expect("the public values struct has a static encoding"),
+            Self::Borsh | Self::Ssz => PACKED_SIZE,
+        }
+    }
+}
+
+/// Declare [`PackedPublicValues`] along with `PACKED_SIZE`, the size of its Borsh or SSZ encoding,
+/// summed from the sizes of its fields, so one can't be changed without the other.
+///
+/// Both encodings write fixed-size numbers, flags and byte arrays in as many bytes as they take in
+/// memory, so the fields must all be of such types.
+macro_rules! packed_public_values {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident {
+            $(pub $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            $(pub $field: $ty,)*
+        }
+
+        /// The size of the Borsh- or SSZ-encoded [`PublicValuesStruct`]. Neither pads the fields,
+        /// so it's much smaller than the ABI encoding.
+        const PACKED_SIZE: usize = 0 $(+ core::mem::size_of::<$ty>())*;
+    };
+}
+
+packed_public_values! {
+    /// [`PublicValuesStruct`] with plain Rust field types, in the same order, for the Borsh and SSZ
+    /// encodings.
+    ///
+    /// Both encode these fields back to back, with little-endian numbers and without padding, so
+    /// they produce the same bytes.
+    #[cfg(any(feature = "borsh", feature = "ssz"))]
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "borsh",
+        derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+    )]
+    #[cfg_attr(feature = "ssz", derive(ssz_derive::Encode, ssz_derive::Decode))]
+    pub struct PackedPublicValues {
+        pub version: u32,
+        pub n: u32,
+        pub offset: u32,
+        pub offset_hash: [u8; 32],
+        pub a: u64,
+        pub b: u64,
+        pub key: [u8; 32],
+        pub leaf: [u8; 32],
+        pub insert_count: u32,
+        pub root: [u8; 32],
+        pub nonce: u64,
+        pub timestamp: u64,
+        pub inputs_digest: [u8; 32],
+        pub child_vkey: [u8; 32],
+        pub overflowed: bool,
+        pub roots_digest: [u8; 32],
+        pub prior_root: [u8; 32],
+        pub entry_count: u32,
+        pub inclusion_verified: bool,
+        pub absent_key: [u8; 32],
+        pub updates_digest: [u8; 32],
+        pub hasher: u8,
+        pub removal_count: u32,
+        pub update_key: [u8; 32],
+        pub old_leaf: [u8; 32],
+        pub new_leaf: [u8; 32],
+        pub namespace_count: u32,
+        pub namespaces_digest: [u8; 32],
+        pub blob_count: u32,
+        pub blobs_digest: [u8; 32],
+        pub big: bool,
+        pub modulus: u64,
+        pub a_mod: u64,
+        pub b_mod: u64,
+        pub recurrence_a0: u64,
+        pub recurrence_a1: u64,
+        pub recurrence_p: i64,
+        pub recurrence_q: i64,
+        pub error_code: u8,
+        pub wide: bool,
+        pub a_hi: u64,
+        pub b_hi: u64,
+        pub pisano_modulus: u64,
+        pub pisano_period: u64,
+        pub signed: bool,
+        pub signed_n: i64,
+        pub a_signed: i64,
+        pub b_signed: i64,
+        pub table_stride: u32,
+        pub table_len: u32,
+        pub table_root: [u8; 32],
+        pub membership: bool,
+        pub membership_x: u64,
+        pub membership_found: bool,
+        pub membership_index: u32,
+        pub golden_ratio: bool,
+        pub ratio: u128,
+        pub previous_digest: [u8; 32],
+    }
+}
+
+#[cfg(any(feature = "borsh", feature = "ssz"))]
+impl PackedPublicValues {
+    /// The size of the Borsh- or SSZ-encoded public values struct.
+    pub const ENCODED_SIZE: usize = PACKED_SIZE;
+}
+
+#[cfg(any(feature = "borsh", feature = "ssz"))]
+impl From<&PublicValuesStruct> for PackedPublicValues {
+    fn from(values: &PublicValuesStruct) -> Self {
+        Self {
+            version: values.version,
+            n: values.n,
+            offset: values.offset,
+            offset_hash: values.offset_hash.0,
+            a: values.a,
+            b: values.b,
+            key: values.key.0,
+            leaf: values.leaf.0,
+            insert_count: values.insert_count,
+            root: values.root,
+            nonce: values.nonce,
+            timestamp: values.timestamp,
+            inputs_digest: values.inputs_digest.0,
+            child_vkey: values.child_vkey.0,
+            overflowed: values.overflowed,
+            roots_digest: values.roots_digest.0,
+            prior_root: values.prior_root.0,
+            entry_count: values.entry_count,
+            inclusion_verified: values.inclusion_verified,
+            absent_key: values.absent_key.0,
+            updates_digest: values.updates_digest.0,
+            hasher: values.hasher,
+            removal_count: values.removal_count,
+            update_key: values.update_key.0,
+            old_leaf: values.old_leaf.0,
+            new_leaf: values.new_leaf.0,
+            namespace_count: values.namespace_count,
+            namespaces_digest: values.namespaces_digest.0,
+            blob_count: values.blob_count,
+            blobs_digest: values.blobs_digest.0,
+            big: values.big,
+            modulus: values.modulus,
+            a_mod: values.a_mod,
+            b_mod: values.b_mod,
+            recurrence_a0: values.recurrence_a0,
+            recurrence_a1: values.recurrence_a1,
+            recurrence_p: values.recurrence_p,
+            recurrence_q: values.recurrence_q,
+            error_code: values.error_code,
+            wide: values.wide,
+            a_hi: values.a_hi,
+            b_hi: values.b_hi,
+            pisano_modulus: values.pisano_modulus,
+            pisano_period: values.pisano_period,
+            signed: values.signed,
+            signed_n: values.signed_n,
+            a_signed: values.a_signed,
+            b_signed: values.b_signed,
+            table_stride: values.table_stride,
+            table_len: values.table_len,
+            table_root: values.table_root.0,
+            membership: values.membership,
+            membership_x: values.membership_x,
+            membership_found: values.membership_found,
+            membership_index: values.membership_index,
+            golden_ratio: values.golden_ratio,
+            ratio: values.ratio,
+            previous_digest: values.previous_digest.0,
+        }
+    }
+}
+
+#[cfg(any(feature = "borsh", feature = "ssz"))]
+impl From<PackedPublicValues> for PublicValuesStruct {
+    fn from(values: PackedPublicValues) -> Self {
+        Self {
+            version: values.version,
+            n: values.n,
+            offset: values.offset,
+            offset_hash: values.offset_hash.into(),
+            a: values.a,
+            b: values.b,
+            key: values.key.into(),
+            leaf: values.leaf.into(),
+            insert_count: values.insert_count,
+            root: values.root,
+            nonce: values.nonce,
+            timestamp: values.timestamp,
+            inputs_digest: values.inputs_digest.into(),
+            child_vkey: values.child_vkey.into(),
+            overflowed: values.overflowed,
+            roots_digest: values.roots_digest.into(),
+            prior_root: values.prior_root.into(),
+            entry_count: values.entry_count,
+            inclusion_verified: values.inclusion_verified,
+            absent_key: values.absent_key.into(),
+            updates_digest: values.updates_digest.into(),
+            hasher: values.hasher,
+            removal_count: values.removal_count,
+            update_key: values.update_key.into(),
+            old_leaf: values.old_leaf.into(),
+            new_leaf: values.new_leaf.into(),
+            namespace_count: values.namespace_count,
+            namespaces_digest: values.namespaces_digest.into(),
+            blob_count: values.blob_count,
+            blobs_digest: values.blobs_digest.into(),
+            big: values.big,
+            modulus: values.modulus,
+            a_mod: values.a_mod,
+            b_mod: values.b_mod,
+            recurrence_a0: values.recurrence_a0,
+            recurrence_a1: values.recurrence_a1,
+            recurrence_p: values.recurrence_p,
+            recurrence_q: values.recurrence_q,
+            error_code: values.error_code,
+            wide: values.wide,
+            a_hi: values.a_hi,
+            b_hi: values.b_hi,
+            pisano_modulus: values.pisano_modulus,
+            pisano_period: values.pisano_period,
+            signed: values.signed,
+            signed_n: values.signed_n,
+            a_signed: values.a_signed,
+            b_signed: values.b_signed,
+            table_stride: values.table_stride,
+            table_len: values.table_len,
+            table_root: values.table_root.into(),
+            membership: values.membership,
+            membership_x: values.membership_x,
+            membership_found: values.membership_found,
+            membership_index: values.membership_index,
+            golden_ratio: values.golden_ratio,
+            ratio: values.ratio,
+            previous_digest: values.previous_digest.into(),
+        }
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl PublicValuesStruct {
+    /// Encode the public values with Borsh, see [`PackedPublicValues`].
+    pub fn borsh_encode(&self) -> Vec<u8> {
+        borsh::to_vec(&PackedPublicValues::from(self)).expect("writing to a Vec can't fail")
+    }
+
+    /// Decode public values encoded with [`PublicValuesStruct::borsh_encode`].
+    pub fn borsh_decode(bytes: &[u8]) -> borsh::io::Result<Self> {
+        <PackedPublicValues as borsh::BorshDeserialize>::try_from_slice(bytes).map(Self::from)
+    }
+}
+
+#[cfg(feature = "ssz")]
+impl PublicValuesStruct {
+    /// Encode the public values with SSZ, see [`PackedPublicValues`].
+    pub fn ssz_encode(&self) -> Vec<u8> {
+        ssz::Encode::as_ssz_bytes(&PackedPublicValues::from(self))
+    }
+
+    /// Decode public values encoded with [`PublicValuesStruct::ssz_encode`].
+    pub fn ssz_decode(bytes: &[u8]) -> Result<Self, ssz::DecodeError> {
+        <PackedPublicValues as ssz::Decode>::from_ssz_bytes(bytes).map(Self::from)
+    }
+}
+
+/// The hash committed in place of a hidden offset, `keccak256(offset || salt)` with the offset as a
+/// big-endian u32, i.e. `keccak256(abi.encodePacked(offset, salt))` in Solidity.
+pub fn offset_commitment(offset: u32, salt: &[u8; 32]) -> B256 {
+    let mut preimage = [0u8; 36];
+    preimage[..4].copy_from_slice(&offset.to_be_bytes());
+    preimage[4..].copy_from_slice(salt);
+    keccak256(preimage)
+}
+
+/// The digest committed as `inputs_digest`, over every buffer the program read from stdin, in the
+/// order it read them.
+///
+/// Each buffer is prefixed with its length as a big-endian u64, so the same bytes split differently
+/// across buffers hash differently too.
+pub fn inputs_digest<'a>(buffers: impl IntoIterator<Item = &'a [u8]>) -> B256 {
+    let mut preimage = Vec::new();
+    for buffer in buffers {
+        preimage.extend_from_slice(&(buffer.len() as u64).to_be_bytes());
+        preimage.extend_from_slice(buffer);
+    }
+    keccak256(preimage)
+}
+
+impl PublicValuesStruct {
+    /// Whether the program ran with `offset`.
+    ///
+    /// If the offset was hidden, pass the `salt` it was hidden with to check `offset` against the
+    /// committed hash instead.
+    pub fn offset_matches(&self, offset: u32, salt: Option<&[u8; 32]>) -> bool {
+        match salt {
+            Some(salt) => self.offset == 0 && self.offset_hash == offset_commitment(offset, salt),
+            None => self.offset == offset && self.offset_hash == B256::ZERO,
+        }
+    }
+
+    /// Whether the proof is a leaf proof, i.e. one that doesn't aggregate a child proof.
+    pub fn is_leaf(&self) -> bool {
+        self.child_vkey == B256::ZERO
+    }
+
+    /// The verification key hash of the aggregated child proof, as the u32 words the zkVM verifies
+    /// proofs with, or `None` for leaf proofs.
+    pub fn child_vkey_words(&self) -> Option<[u32; 8]> {
+        if self.is_leaf() {
+            return None;
+        }
+        let mut words = [0u32; 8];
+        for (word, bytes) in words.iter_mut().zip(self.child_vkey.0.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        Some(words)
+    }
+}
+
+/// A one-line summary of the results, e.g. for logs.
+impl core::fmt::Display for PublicValuesStruct {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "n = {}, ", self.n)?;
+        match self.offset_hash == B256::ZERO {
+            true => write!(f, "offset = {}, ", self.offset)?,
+            false => write!(f, "offset hidden behind {}, ", self.offset_hash)?,
+        }
+        if self.error_code != 0 {
+            return write!(f, "aborted with error code {}", self.error_code);
+        }
+        write!(
+            f,
+            "a = {}, b = {}, root = {}",
+            self.a,
+            self.b,
+            B256::from(self.root)
+        )
+    }
+}
+
+/// Decode ABI-encoded public values committed by the current version of the program, rejecting
+/// other versions, see [`VersionedPublicValues::abi_decode`] to accept the first version too.
+impl TryFrom<&[u8]> for PublicValuesStruct {
+    type Error = alloy_sol_types::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        // The version is the first field, so it's the big-endian number in the first word. The
+        // first version didn't commit one, so its `n` is read instead, which fails the check.
+        let version = bytes
+            .get(..32)
+            .filter(|word| word[..28].iter().all(|&byte| byte == 0))
+            .map(|word| u32::from_be_bytes(word[28..].try_into().unwrap()));
+        check_version(version)?;
+        let mut reader = PublicValuesReader::new(bytes);
+        let values = reader.read::<PublicValuesStruct>()?;
+        let (_, _, big_results) = split_public_values(bytes, PublicValuesEncoding::Abi)?;
+        decode_big_results(values.big, big_results)?;
+        Ok(values)
+    }
+}
+
+impl PublicValuesStruct {
+    /// The calldata of a call to `verifyFibonacciProof` in `contracts/src/Fibonacci.sol`, given
+    /// the ABI-encoded public values a proof committed and its proof bytes, e.g. to send with
+    /// `cast`.
+    ///
+    /// It takes the committed bytes rather than decoded public values, as the contract checks the
+    /// proof against them as they are, with the inclusion proof and the exact results committed
+    /// after the struct.
+    pub fn solidity_calldata(public_values: &[u8], proof_bytes: &[u8]) -> Vec<u8> {
+        verifyFibonacciProofCall {
+            _publicValues: public_values.to_vec().into(),
+            _proofBytes: proof_bytes.to_vec().into(),
+        }
+        .abi_encode()
+    }
+}
+
+/// The `child_vkey` a proof aggregating a proof with the verification key `vkey_words` commits,
+/// i.e. the u32 words of the verification key hash as big-endian bytes.
+pub fn child_vkey(vkey_words: &[u32; 8]) -> B256 {
+    let mut bytes = [0u8; 32];
+    for (bytes, word) in bytes.chunks_exact_mut(4).zip(vkey_words) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    B256::from(bytes)
+}
+
+/// The digest of a proof's public values the zkVM verifies child proofs against, i.e. the SHA-256
+/// hash of the public values as committed.
+pub fn public_values_digest(public_values: &[u8]) -> [u8; 32] {
+    Sha256::digest(public_values).into()
+}
+
+/// The layout version committed as the first field of [`AggregatedPublicValuesStruct`].
+pub const AGGREGATION_VERSION: u32 = 1;
+
+/// The `public_values_digest` the aggregator commits for the public values of the proofs it
+/// aggregates, in order: the keccak256 hash of their [`public_values_digest`]s one after another,
+/// i.e. `keccak256(abi.encodePacked(sha256(publicValues1), ..., sha256(publicValuesN)))` in
+/// Solidity.
+pub fn aggregation_digest<'a>(public_values: impl IntoIterator<Item = &'a [u8]>) -> B256 {
+    let digests: Vec<u8> = public_values
+        .into_iter()
+        .flat_map(public_values_digest)
+        .collect();
+    keccak256(digests)
+}
+
+/// Whether the public values the aggregator committed cover exactly `public_values`, in order,
+/// committed by proofs with the verification key `vkey_words`.
+pub fn verify_aggregation<'a>(
+    aggregated: &AggregatedPublicValuesStruct,
+    vkey_words: &[u32; 8],
+    public_values: impl ExactSizeIterator<Item = &'a [u8]>,
+) -> bool {
+    aggregated.version == AGGREGATION_VERSION
+        && aggregated.vkey == child_vkey(vkey_words)
+        && aggregated.count as usize == public_values.len()
+        && aggregated.public_values_digest == aggregation_digest(public_values)
+}
+
+/// Whether a chain of proofs, given by the u32 words of their verification key hashes and their
+/// public values from the leaf up, links together: the first is a leaf proof, and every other one
+/// commits the verification key of the one before it as its `child_vkey`.
+pub fn verify_chain<'a>(
+    proofs: impl IntoIterator<Item = (&'a [u32; 8], &'a PublicValuesStruct)>,
+) -> bool {
+    let mut child: Option<&[u32; 8]> = None;
+    for (vkey_words, public_values) in proofs {
+        if public_values.child_vkey_words().as_ref() != child {
+            return false;
+        }
+        child = Some(vkey_words);
+    }
+    true
+}
+
+/// Public values made of several ABI-encoded structs one after another, instead of one flat struct.
+///
+/// The program pushes each struct and commits the resulting bytes, and the host splits them back up
+/// with a [`PublicValuesReader`] reading the same types in the same order. Every struct must have
+/// only static fields, so that its encoding has a fixed size to split at.
+///
+/// ```ignore
+/// let bytes = PublicValuesWriter::new()
+///     .push::<PublicValuesStruct>(&public_values)
+///     .push::<TreeStateStruct>(&tree_state)
+///     .into_bytes();
+/// sp1_zkvm::io::commit_slice(&bytes);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PublicValuesWriter {
+    bytes: Vec<u8>,
+}
+
+impl PublicValuesWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the ABI encoding of `value`.
+    pub fn push<T: SolType>(mut self, value: &T::RustType) -> Self {
+        assert!(
+            T::ENCODED_SIZE.is_some(),
+            "only types with a static encoding can be pushed"
+        );
+        self.bytes.extend_from_slice(&T::abi_encode(value));
+        self
+    }
+
+    /// Append the ABI encoding of `value`, which may be dynamic, as the last struct.
+    pub fn push_last<T: SolType>(mut self, value: &T::RustType) -> Vec<u8> {
+        self.bytes.extend_from_slice(&T::abi_encode(value));
+        self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Splits public values written with a [`PublicValuesWriter`] back up into their structs.
+///
+/// ```ignore
+/// let mut reader = PublicValuesReader::new(proof.public_values.as_slice());
+/// let public_values = reader.read::<PublicValuesStruct>()?;
+/// let tree_state = reader.read::<TreeStateStruct>()?;
+/// reader.finish()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct PublicValuesReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PublicValuesReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Decode the next struct, which must be of the type pushed at this point.
+    pub fn read<T: SolType>(&mut self) -> Result<T::RustType, alloy_sol_types::Error> {
+        let size = T::ENCODED_SIZE.ok_or_else(|| {
+            alloy_sol_types::Error::custom("only types with a static encoding can be read")
+        })?;
+        if self.bytes.len() < size {
+            return Err(alloy_sol_types::Error::custom(format!(
+                "expected {} more bytes of public values, got {}",
+                size,
+                self.bytes.len()
+            )));
+        }
+        let (next, rest) = self.bytes.split_at(size);
+        let value = T::abi_decode(next, true)?;
+        self.bytes = rest;
+        Ok(value)
+    }
+
+    /// Decode the rest of the public values as the last struct, which may be dynamic.
+    pub fn read_last<T: SolType>(self) -> Result<T::RustType, alloy_sol_types::Error> {
+        T::abi_decode(self.bytes, true)
+    }
+
+    /// Check that every struct was read, so that public values with trailing data are rejected.
+    pub fn finish(self) -> Result<(), alloy_sol_types::Error> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(alloy_sol_types::Error::custom(format!(
+                "{} bytes of public values left unread",
+                self.bytes.len()
+            )))
+        }
+    }
+}
+
+/// Check that public values were committed with the current layout, given the version they start
+/// with, if any.
+pub fn check_version(version: Option<u32>) -> Result<(), alloy_sol_types::Error> {
+    match version {
+        Some(PUBLIC_VALUES_VERSION) => Ok(()),
+        Some(version) => Err(alloy_sol_types::Error::custom(format!(
+            "unsupported public values version {}, expected {}",
+            version, PUBLIC_VALUES_VERSION
+        ))),
+        None => Err(alloy_sol_types::Error::custom(
+            "the public values don't start with a version",
+        )),
+    }
+}
+
+/// ABI-encoded public values committed by the current version of the program, or by its first
+/// version.
+#[derive(Clone)]
+pub enum VersionedPublicValues {
+    Current(PublicValuesStruct),
+    /// Public values of the first version, which archived proofs may still commit.
+    Legacy(LegacyPublicValuesStruct),
+}
+
+impl VersionedPublicValues {
+    /// Decode public values with the current layout, falling back to the layout of the first
+    /// version if they don't decode with it.
+    ///
+    /// Public values of any other version are rejected, rather than decoded with the wrong layout.
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        // Report why the public values don't decode with the current layout, rather than why they
+        // don't with the legacy one.
+        PublicValuesStruct::try_from(bytes)
+            .map(Self::Current)
+            .or_else(|err| {
+                let mut reader = PublicValuesReader::new(bytes);
+                reader
+                    .read::<LegacyPublicValuesStruct>()
+                    .and_then(|values| reader.finish().map(|()| values))
+                    .map(Self::Legacy)
+                    .map_err(|_| err)
+            })
+    }
+}
+
+/// Split public values committed by the program in `encoding` into the encoded
+/// [`PublicValuesStruct`], the [`InclusionProofStruct`] committed after it and the bytes after
+/// that, which are the exact fibonacci numbers if the program committed them, see
+/// [`decode_big_results`].
+pub fn split_public_values(
+    bytes: &[u8],
+    encoding: PublicValuesEncoding,
+) -> Result<(&[u8], InclusionProofStruct, &[u8]), alloy_sol_types::Error> {
+    let size = encoding.struct_size();
+    if bytes.len() < size {
+        return Err(alloy_sol_types::Error::custom(format!(
+            "expected at least {} bytes of public values, got {}",
+            size,
+            bytes.len()
+        )));
+    }
+    let (values, rest) = bytes.split_at(size);
+    // The proof is dynamic, so where it ends is only known once it's decoded. Its encoding is
+    // canonical, so it ends where its re-encoding would, and is decoded strictly up to there.
+    let proof = InclusionProofStruct::abi_decode(rest, false)?;
+    let proof_size = InclusionProofStruct::abi_encoded_size(&proof).min(rest.len());
+    let (proof, rest) = rest.split_at(proof_size);
+    Ok((values, InclusionProofStruct::abi_decode(proof, true)?, rest))
+}
+
+/// Encode the exact fibonacci numbers the program commits after the inclusion proof, as the
+/// big-endian bytes of `a` and `b`, each prefixed with their length as a big-endian `u32`.
+pub fn encode_big_results(a: &BigUint, b: &BigUint) -> Vec<u8> {
+    [a, b]
+        .into_iter()
+        .flat_map(|number| {
+            let bytes = number.to_bytes_be();
+            [(bytes.len() as u32).to_be_bytes().to_vec(), bytes].concat()
+        })
+        .collect()
+}
+
+/// Decode the exact fibonacci numbers committed after the inclusion proof, given whether the
+/// program committed them (`big`), checking nothing else was committed after it.
+pub fn decode_big_results(
+    big: bool,
+    mut bytes: &[u8],
+) -> Result<Option<(BigUint, BigUint)>, alloy_sol_types::Error> {
+    let results = match big {
+        true => Some((read_big(&mut bytes)?, read_big(&mut bytes)?)),
+        false => None,
+    };
+    if !bytes.is_empty() {
+        return Err(alloy_sol_types::Error::custom(format!(
+            "{} bytes of public values left unread",
+            bytes.len()
+        )));
+    }
+    Ok(results)
+}
+
+/// Read a length-prefixed big-endian number off the front of `bytes`, see [`encode_big_results`].
+fn read_big(bytes: &mut &[u8]) -> Result<BigUint, alloy_sol_types::Error> {
+    let truncated = || alloy_sol_types::Error::custom("the exact fibonacci numbers are truncated");
+    let length = bytes.get(..4).ok_or_else(truncated)?;
+    // The length is untrusted, so adding the prefix to it mustn't overflow a 32-bit usize, as in
+    // the zkVM.
+    let end = (u32::from_be_bytes(length.try_into().unwrap()) as usize)
+        .checked_add(4)
+        .ok_or_else(truncated)?;
+    let number = bytes.get(4..end).ok_or_else(truncated)?;
+    *bytes = &bytes[end..];
+    Ok(BigUint::from_bytes_be(number))
+}
+
+impl InclusionProofStruct {
+    /// The proof for the steps of a monotree Merkle proof, as `(right, cut)` pairs.
+    pub fn from_steps<'a>(steps: impl IntoIterator<Item = &'a (bool, Vec<u8>)>) -> Self {
+        let (rights, cuts) = steps
+            .into_iter()
+            .map(|(right, cut)| (*right, cut.clone().into()))
+            .unzip();
+        Self { rights, cuts }
+    }
+
+    /// The steps of the proof as `(right, cut)` pairs, the form monotree verifies proofs in.
+    pub fn steps(&self) -> Vec<(bool, Vec<u8>)> {
+        self.rights
+            .iter()
+            .zip(&self.cuts)
+            .map(|(right, cut)| (*right, cut.to_vec()))
+            .collect()
+    }
+}
+
+/// Why public values don't check out against the inputs they were supposedly committed for.
+#[derive(Debug)]
+pub enum PublicValuesError {
+    /// The public values couldn't be decoded.
+    Decode(alloy_sol_types::Error),
+    /// The named field doesn't have the value the program commits for the inputs.
+    Mismatch(&'static str),
+}
+
+impl core::fmt::Display for PublicValuesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "failed to decode the public values: {}", err),
+            Self::Mismatch(field) => write!(f, "the committed {} doesn't match the inputs", field),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PublicValuesError {}
+
+impl From<alloy_sol_types::Error> for PublicValuesError {
+    fn from(err: alloy_sol_types::Error) -> Self {
+        Self::Decode(err)
+    }
+}
+
+/// Decode public values committed by the current version of the program in `encoding`.
+pub fn decode_public_values(
+    bytes: &[u8],
+    encoding: PublicValuesEncoding,
+) -> Result<PublicValuesStruct, alloy_sol_types::Error> {
+    match encoding {
+        PublicValuesEncoding::Abi => PublicValuesStruct::try_from(bytes),
+        #[cfg(feature = "borsh")]
+        PublicValuesEncoding::Borsh => decode_packed_public_values(bytes, encoding, |packed| {
+            PublicValuesStruct::borsh_decode(packed)
+                .map_err(|err| alloy_sol_types::Error::custom(err.to_string()))
+        }),
+        #[cfg(not(feature = "borsh"))]
+        PublicValuesEncoding::Borsh => Err(alloy_sol_types::Error::custom(
+            "decoding Borsh-encoded public values needs the borsh feature",
+        )),
+        #[cfg(feature = "ssz")]
+        PublicValuesEncoding::Ssz => decode_packed_public_values(bytes, encoding, |packed| {
+            PublicValuesStruct::ssz_decode(packed)
+                .map_err(|err| alloy_sol_types::Error::custom(format!("{:?}", err)))
+        }),
+        #[cfg(not(feature = "ssz"))]
+        PublicValuesEncoding::Ssz => Err(alloy_sol_types::Error::custom(
+            "decoding SSZ-encoded public values needs the ssz feature",
+        )),
+    }
+}
+
+/// Decode public values committed in `encoding`, Borsh or SSZ, with `decode`, checking their
+/// version and the bytes after the inclusion proof as [`PublicValuesStruct::try_from`] does for
+/// ABI-encoded ones.
+#[cfg(any(feature = "borsh", feature = "ssz"))]
+fn decode_packed_public_values(
+    bytes: &[u8],
+    encoding: PublicValuesEncoding,
+    decode: impl FnOnce(&[u8]) -> Result<PublicValuesStruct, alloy_sol_types::Error>,
+) -> Result<PublicValuesStruct, alloy_sol_types::Error> {
+    let (packed, _, big_results) = split_public_values(bytes, encoding)?;
+    // The version is the first field, so it's the little-endian number in the first 4 bytes.
+    let version = packed
+        .get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+    check_version(version)?;
+    let values = decode(packed)?;
+    decode_big_results(values.big, big_results)?;
+    Ok(values)
+}
+
+/// Decode public values committed by the program, and check they are the ones it commits when run
+/// with `expected_inputs`.
+#[cfg(feature = "std")]
+pub fn verify_public_values(
+    bytes: &[u8],
+    expected_inputs: &FibonacciInputs,
+) -> Result<PublicValuesStruct, PublicValuesError> {
+    let values = match expected_inputs.encoding {
+        PublicValuesEncoding::Abi => match VersionedPublicValues::abi_decode(bytes)? {
+            VersionedPublicValues::Current(values) => values,
+            VersionedPublicValues::Legacy(_) => return Err(PublicValuesError::Mismatch("version")),
+        },
+        encoding => decode_public_values(bytes, encoding)?,
+    };
+    let (_, _, big_results) = split_public_values(bytes, expected_inputs.encoding)?;
+
+    let FibonacciInputs {
+        n,
+        offset,
+        salt,
+        nonce,
+        timestamp,
+        ..
+    } = *expected_inputs;
+
+    // Chaining onto a previous proof, the program verifies it first, whether it aborts or not.
+    let (child_vkey, previous_digest) = expected_inputs.chained();
+
+    // Given inputs it aborts for, the program commits the error code and what it read, but leaves
+    // the tree as it was and the results zero.
+    if expected_inputs.checked && expected_inputs.checked_results().is_err() {
+        let prior_root = values.prior_root.0;
+        let checks = [
+            ("version", values.version == PUBLIC_VALUES_VERSION),
+            ("n", values.n == n),
+            ("offset", values.offset_matches(offset, salt.as_ref())),
+            ("a", values.a == 0),
+            ("b", values.b == 0),
+            ("nonce", values.nonce == nonce),
+            ("timestamp", values.timestamp == timestamp),
+            (
+                "inputs_digest",
+                values.inputs_digest == expected_inputs.digest(),
+            ),
+            ("root", values.root == prior_root),
+            ("hasher", values.hasher == expected_inputs.hasher.id()),
+            ("error_code", values.error_code == ERROR_OVERFLOW),
+            ("child_vkey", values.child_vkey == child_vkey),
+            ("previous_digest", values.previous_digest == previous_digest),
+        ];
+        return match checks.iter().find(|(_, matches)| !matches) {
+            Some(&(field, _)) => Err(PublicValuesError::Mismatch(field)),
+            None => Ok(values),
+        };
+    }
+
+    let (a, b) = expected_inputs.results();
+    let (a, b, overflowed) = add_offset(a, b, offset);
+    let (key, leaf) = expected_inputs.tree_entry(b);
+    let (a_mod, b_mod) = expected_inputs
+        .modulus
+        .map_or((0, 0), |p| fibonacci_mod_offset(n, offset, p));
+    let (a_hi, b_hi) = match expected_inputs.wide {
+        true => {
+            let (a, b) = expected_inputs.wide_results();
+            ((a >> 64) as u64, (b >> 64) as u64)
+        }
+        false => (0, 0),
+    };
+    let (a_signed, b_signed) = expected_inputs.signed_results().unwrap_or_default();
+    let membership = expected_inputs.membership;
+    let membership_index = membership.and_then(is_fibonacci);
+    let ratio = match expected_inputs.golden_ratio {
+        true => fibonacci_ratio(n).unwrap_or_default(),
+        false => Q64x64::default(),
+    };
+    let table = expected_inputs
+        .table_stride
+        .map(|k| fibonacci_table(n, k, offset))
+        .unwrap_or_default();
+    let expected_big_results = expected_inputs.big.then(|| {
+        let (a, b) = fibonacci_big(n);
+        (a + offset, b + offset)
+    });
+    let checks = [
+        ("version", values.version == PUBLIC_VALUES_VERSION),
+        ("n", values.n == n),
+        ("offset", values.offset_matches(offset, salt.as_ref())),
+        ("a", values.a == a),
+        ("b", values.b == b),
+        ("key", values.key.0 == key),
+        ("leaf", values.leaf.0 == leaf),
+        ("insert_count", values.insert_count == (offset > 0) as u32),
+        ("nonce", values.nonce == nonce),
+        ("timestamp", values.timestamp == timestamp),
+        (
+            "inputs_digest",
+            values.inputs_digest == expected_inputs.digest(),
+        ),
+        ("child_vkey", values.child_vkey == child_vkey),
+        ("overflowed", values.overflowed == overflowed),
+        (
+            "entry_count",
+            values.entry_count as usize == expected_inputs.batch_len(),
+        ),
+        (
+            "inclusion_verified",
+            values.inclusion_verified == (offset > 0),
+        ),
+        (
+            "absent_key",
+            values.absent_key.0 == expected_inputs.absent_key.unwrap_or_default(),
+        ),
+        (
+            "updates_digest",
+            values.updates_digest == updates_digest(&expected_inputs.updates(b)),
+        ),
+        ("hasher", values.hasher == expected_inputs.hasher.id()),
+        (
+            "removal_count",
+            values.removal_count as usize == expected_inputs.removals.len(),
+        ),
+        (
+            "leaf_update",
+            (values.update_key.0, values.old_leaf.0, values.new_leaf.0)
+                == expected_inputs
+                    .leaf_update
+                    .map(|update| (update.key, update.old_leaf, update.new_leaf))
+                    .unwrap_or_default(),
+        ),
+        (
+            "namespace_count",
+            values.namespace_count as usize == expected_inputs.namespaces.len(),
+        ),
+        (
+            "blob_count",
+            values.blob_count as usize == expected_inputs.blobs.len(),
+        ),
+        (
+            "blobs_digest",
+            values.blobs_digest == blobs_digest(&expected_inputs.blobs),
+        ),
+        ("big", values.big == expected_inputs.big),
+        (
+            "big_results",
+            decode_big_results(values.big, big_results).ok() == Some(expected_big_results),
+        ),
+        (
+            "modulus",
+            values.modulus == expected_inputs.modulus.unwrap_or(0),
+        ),
+        ("a_mod", values.a_mod == a_mod),
+        ("b_mod", values.b_mod == b_mod),
+        (
+            "sequence",
+            (
+                values.recurrence_a0,
+                values.recurrence_a1,
+                values.recurrence_p,
+                values.recurrence_q,
+            ) == expected_inputs.sequence.params(),
+        ),
+        ("error_code", values.error_code == 0),
+        ("wide", values.wide == expected_inputs.wide),
+        ("a_hi", values.a_hi == a_hi),
+        ("b_hi", values.b_hi == b_hi),
+        (
+            "pisano",
+            (values.pisano_modulus, values.pisano_period)
+                == expected_inputs
+                    .pisano
+                    .map_or((0, 0), |m| (m, pisano_period(m))),
+        ),
+        (
+            "signed",
+            values.signed == expected_inputs.signed_index.is_some(),
+        ),
+        (
+            "signed_n",
+            values.signed_n == expected_inputs.signed_index.unwrap_or(0),
+        ),
+        ("a_signed", values.a_signed == a_signed),
+        ("b_signed", values.b_signed == b_signed),
+        (
+            "table_stride",
+            values.table_stride == expected_inputs.table_stride.unwrap_or(0),
+        ),
+        ("table_len", values.table_len as usize == table.len()),
+        ("table_root", values.table_root == table_root(&table)),
+        ("membership", values.membership == membership.is_some()),
+        (
+            "membership_x",
+            values.membership_x == membership.unwrap_or(0),
+        ),
+        (
+            "membership_found",
+            values.membership_found == membership_index.is_some(),
+        ),
+        (
+            "membership_index",
+            values.membership_index == membership_index.unwrap_or(0),
+        ),
+        (
+            "golden_ratio",
+            values.golden_ratio == expected_inputs.golden_ratio,
+        ),
+        ("ratio", values.ratio == ratio.0),
+        ("previous_digest", values.previous_digest == previous_digest),
+    ];
+    match checks.iter().find(|(_, matches)| !matches) {
+        Some(&(field, _)) => Err(PublicValuesError::Mismatch(field)),
+        None => Ok(values),
+    }
+}
+
+/// Add the offset to the fibonacci numbers `a` and `b`, wrapping around on overflows.
+///
+/// Also returns whether either addition overflowed, which the program commits as `overflowed`, so
+/// verifiers can tell wrapped results apart.
+pub fn add_offset(a: u64, b: u64, offset: u32) -> (u64, u64, bool) {
+    let (a, a_overflowed) = a.overflowing_add(u64::from(offset));
+    let (b, b_overflowed) = b.overflowing_add(u64::from(offset));
+    (a, b, a_overflowed || b_overflowed)
+}
+
+/// Extend the hash chain committed as `roots_digest` with the next root of the tree, i.e.
+/// `keccak256(digest || root)`.
+pub fn chain_root(digest: B256, root: &[u8; 32]) -> B256 {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(digest.as_slice());
+    preimage[32..].copy_from_slice(root);
+    keccak256(preimage)
+}
+
+/// The `roots_digest` the program commits for the roots the tree went through, one after each
+/// insertion, chained with [`chain_root`] starting from zero.
+///
+/// Verifiers given the claimed sequence of roots can check it against a proof with this.
+pub fn roots_digest<'a>(roots: impl IntoIterator<Item = &'a [u8; 32]>) -> B256 {
+    roots.into_iter().fold(B256::ZERO, chain_root)
+}
+
+/// The `updates_digest` the program commits for the `(key, leaf)` updates it applies to the tree,
+/// i.e. `keccak256(key_0 || leaf_0 || key_1 || leaf_1 || ...)`.
+///
+/// Together with `prior_root` and `root`, it makes every proof a statement about a state
+/// transition: applying exactly these updates to the tree at `prior_root` yields `root`.
+pub fn updates_digest(updates: &[([u8; 32], [u8; 32])]) -> B256 {
+    let preimage: Vec<u8> = updates
+        .iter()
+        .flat_map(|(key, leaf)| key.iter().chain(leaf))
+        .copied()
+        .collect();
+    keccak256(preimage)
+}
+
+/// The `namespaces_digest` the program commits for the trees of [`FibonacciInputs::namespaces`],
+/// given each one's name, entries, prior root and new root, with `None` for empty trees.
+///
+/// Each tree contributes `keccak256(name) || prior_root || root || updates_digest(entries)`, with
+/// zero for empty roots, and the digest is the `keccak256` of them all in order, so verifiers given
+/// the claimed roots of each tree can check them against a proof.
+pub fn namespaces_digest<'a>(
+    namespaces: impl IntoIterator<Item = (&'a Namespace, Option<[u8; 32]>, Option<[u8; 32]>)>,
+) -> B256 {
+    let mut preimage = Vec::new();
+    for (namespace, prior_root, root) in namespaces {
+        preimage.extend_from_slice(keccak256(namespace.name.as_bytes()).as_slice());
+        preimage.extend_from_slice(&prior_root.unwrap_or_default());
+        preimage.extend_from_slice(&root.unwrap_or_default());
+        preimage.extend_from_slice(updates_digest(&namespace.entries).as_slice());
+    }
+    keccak256(preimage)
+}
+
+/// `count` pseudorandom `(key, leaf)` entries derived from `seed` with ChaCha8, the same on the
+/// host and in the program, for reproducibly stress-testing proofs over many inserts.
+pub fn seeded_entries(seed: u64, count: u32) -> Vec<([u8; 32], [u8; 32])> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    (0..count)
+        .map(|_| {
+            let mut key = [0; 32];
+            let mut leaf = [0; 32];
+            rng.fill_bytes(&mut key);
+            rng.fill_bytes(&mut leaf);
+            (key, leaf)
+        })
+        .collect()
+}
+
+/// The leaf the program inserts for a blob of leaf data of any length, i.e. its `keccak256`.
+pub fn blob_leaf(blob: &[u8]) -> [u8; 32] {
+    keccak256(blob).0
+}
+
+/// The `blobs_digest` the program commits for the `(key, blob)` entries of
+/// [`FibonacciInputs::blobs`], i.e. the [`updates_digest`] of their keys and [`blob_leaf`]s, so
+/// verifiers holding the blobs can check which data the inserted leaves stand for.
+pub fn blobs_digest(blobs: &[([u8; 32], Vec<u8>)]) -> B256 {
+    let entries: Vec<_> = blobs
+        .iter()
+        .map(|(key, blob)| (*key, blob_leaf(blob)))
+        .collect();
+    updates_digest(&entries)
+}
+
+/// Every k'th fibonacci number up to the n'th, F(0), F(k), F(2k), ..., with the offset added,
+/// wrapping around on overflows, each with its index.
+///
+/// Panics if `k` is zero.
+pub fn fibonacci_table(n: u32, k: u32, offset: u32) -> Vec<(u32, u64)> {
+    assert_ne!(k, 0, "the stride must be non-zero");
+    // Count the indices in u32, as `n + 1` doesn't fit in the 32-bit usize of the zkVM for n =
+    // u32::MAX.
+    (0..=n)
+        .zip(FibonacciIter::new())
+        .step_by(k as usize)
+        .map(|(index, (a, _))| (index, a.wrapping_add(u64::from(offset))))
+        .collect()
+}
+
+/// The leaf of the Merkle tree over a [`fibonacci_table`] for the fibonacci number at `index`,
+/// i.e. `keccak256(index || value)`, with both big-endian.
+pub fn table_leaf(index: u32, value: u64) -> B256 {
+    keccak256([&index.to_be_bytes()[..], &value.to_be_bytes()].concat())
+}
+
+/// The `table_root` the program commits for a [`fibonacci_table`]: the root of a binary Merkle
+/// tree over its [`table_leaf`]s, hashing every pair of nodes as `keccak256(left || right)` and
+/// moving a node without a sibling up as is, or zero for an empty table.
+pub fn table_root(table: &[(u32, u64)]) -> B256 {
+    let mut layer: Vec<_> = table
+        .iter()
+        .map(|&(index, value)| table_leaf(index, value))
+        .collect();
+    while layer.len() > 1 {
+        layer = layer.chunks(2).map(table_node).collect();
+    }
+    layer.first().copied().unwrap_or_default()
+}
+
+/// The Merkle proof of the entry at `position` in `table` against its [`table_root`]: the
+/// siblings of the nodes on its path, from the leaf up, leaving out the levels it has none at.
+pub fn table_proof(table: &[(u32, u64)], mut position: usize) -> Vec<B256> {
+    let mut layer: Vec<_> = table
+        .iter()
+        .map(|&(index, value)| table_leaf(index, value))
+        .collect();
+    let mut proof = Vec::new();
+    while layer.len() > 1 {
+        if let Some(sibling) = layer.get(position ^ 1) {
+            proof.push(*sibling);
+        }
+        layer = layer.chunks(2).map(table_node).collect();
+        position /= 2;
+    }
+    proof
+}
+
+/// Whether `proof` shows that `(index, value)` is the entry at `position` of the table of `len`
+/// entries with the root `root`, e.g. a committed `table_root` and `table_len`.
+pub fn verify_table_entry(
+    root: B256,
+    len: u32,
+    position: u32,
+    (index, value): (u32, u64),
+    proof: &[B256],
+) -> bool {
+    if position >= len {
+        return false;
+    }
+    let (mut position, mut len) = (position, len);
+    let mut hash = table_leaf(index, value);
+    let mut siblings = proof.iter();
+    while len > 1 {
+        if position % 2 == 1 || position + 1 < len {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = match position % 2 {
+                0 => table_node(&[hash, *sibling]),
+                _ => table_node(&[*sibling, hash]),
+            };
+        }
+        position /= 2;
+        len = len.div_ceil(2);
+    }
+    siblings.next().is_none() && hash == root
+}
+
+/// The parent of one or two nodes of a table's Merkle tree, see [`table_root`].
+fn table_node(nodes: &[B256]) -> B256 {
+    match nodes {
+        [left, right] => keccak256([left.as_slice(), right.as_slice()].concat()),
+        [node] => *node,
+        _ => unreachable!("nodes are paired up"),
+    }
+}
+
+/// The default key and leaf the program inserts into the tree, for the offset fibonacci number `b`.
+pub fn tree_entry(b: u64) -> ([u8; 32], [u8; 32]) {
+    ([1; 32], [b as u8; 32])
+}
+
+/// The key and leaf the program inserts into the tree when indexing it by `n`: `keccak256` of the
+/// big-endian `n` as the key, and the offset fibonacci number `b` as a 32-byte big-endian number as
+/// the leaf, so the tree maps each `n` to its result.
+pub fn indexed_tree_entry(n: u32, b: u64) -> ([u8; 32], [u8; 32]) {
+    let mut leaf = [0; 32];
+    leaf[24..].copy_from_slice(&b.to_be_bytes());
+    (keccak256(n.to_be_bytes()).0, leaf)
 }
 
 /// Compute the n'th fibonacci number (wrapping around on overflows), using normal Rust code.
-pub fn fibonacci(n: u32) -> (u32, u32) {
-    let mut a = 0u32;
-    let mut b = 1u32;
+pub fn fibonacci(n: u32) -> (u64, u64) {
+    let mut a = 0u64;
+    let mut b = 1u64;
+    for _ in 0..n {
+        let c = a.wrapping_add(b);
+        a = b;
+        b = c;
+    }
+    (a, b)
+}
+
+/// The error of [`fibonacci_checked`] when the n'th fibonacci number or the next one overflows 64
+/// bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverflowError {
+    /// The n the fibonacci numbers were asked for.
+    pub n: u32,
+}
+
+impl core::fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "the fibonacci numbers for n = {} overflow 64 bits",
+            self.n
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OverflowError {}
+
+/// Compute the n'th fibonacci number like [`fibonacci`], but fail instead of wrapping around once
+/// it or the next one overflows, i.e. past n = 92.
+pub fn fibonacci_checked(n: u32) -> Result<(u64, u64), OverflowError> {
+    let mut a = 0u64;
+    let mut b = 1u64;
+    for _ in 0..n {
+        let c = a.checked_add(b).ok_or(OverflowError { n })?;
+        a = b;
+        b = c;
+    }
+    Ok((a, b))
+}
+
+/// The successive pairs of fibonacci numbers `(F(k), F(k+1))` from k = 0, wrapping around on
+/// overflows like [`fibonacci`], so its n'th item is `fibonacci(n)`.
+///
+/// Computing the results for several n in increasing order takes one pass over it, rather than one
+/// from zero for each n.
+#[derive(Clone, Debug)]
+pub struct FibonacciIter {
+    a: u64,
+    b: u64,
+}
+
+impl FibonacciIter {
+    pub fn new() -> Self {
+        Self { a: 0, b: 1 }
+    }
+}
+
+impl Default for FibonacciIter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for FibonacciIter {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = (self.a, self.b);
+        (self.a, self.b) = (self.b, self.a.wrapping_add(self.b));
+        Some(item)
+    }
+}
+
+/// The fibonacci numbers computed so far, so looking up the results for any n only computes the
+/// numbers past the largest n looked up before.
+#[derive(Clone, Debug)]
+pub struct FibonacciTable {
+    /// `F(0)` up to `F(k + 1)` for the largest k looked up so far.
+    numbers: Vec<u64>,
+}
+
+impl FibonacciTable {
+    pub fn new() -> Self {
+        Self {
+            numbers: vec![0, 1],
+        }
+    }
+
+    /// The n'th fibonacci number and the next one, like [`fibonacci`], computing the ones missing
+    /// from the table first.
+    pub fn get(&mut self, n: u32) -> (u64, u64) {
+        let n = n as usize;
+        while self.numbers.len() < n + 2 {
+            let [.., a, b] = self.numbers[..] else {
+                unreachable!("the table starts with two numbers")
+            };
+            self.numbers.push(a.wrapping_add(b));
+        }
+        (self.numbers[n], self.numbers[n + 1])
+    }
+}
+
+impl Default for FibonacciTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compute the n'th element and the next one of the linear recurrence
+/// `x(k+2) = p * x(k+1) - q * x(k)` from `x(0) = a0` and `x(1) = a1`, e.g. a Lucas sequence,
+/// wrapping around on overflows like [`fibonacci`]. Negative elements come out as
+/// two's-complement numbers.
+///
+/// The fibonacci numbers are `linear_recurrence(0, 1, 1, -1, n)`.
+pub fn linear_recurrence(a0: u64, a1: u64, p: i64, q: i64, n: u32) -> (u64, u64) {
+    let mut a = a0;
+    let mut b = a1;
+    for _ in 0..n {
+        let c = (p as u64)
+            .wrapping_mul(b)
+            .wrapping_sub((q as u64).wrapping_mul(a));
+        a = b;
+        b = c;
+    }
+    (a, b)
+}
+
+/// Compute the n'th Lucas number and the next one, wrapping around on overflows like
+/// [`fibonacci`]. They follow the fibonacci recurrence, from 2 and 1 rather than 0 and 1.
+pub fn lucas(n: u32) -> (u64, u64) {
+    linear_recurrence(2, 1, 1, -1, n)
+}
+
+/// Compute the n'th fibonacci number with 128 bits (wrapping around on overflows), which gives
+/// exact results up to n = 185, rather than 92 for [`fibonacci`], at a fraction of the cost of
+/// [`fibonacci_big`].
+pub fn fibonacci_u128(n: u32) -> (u128, u128) {
+    let mut a = 0u128;
+    let mut b = 1u128;
     for _ in 0..n {
         let c = a.wrapping_add(b);
         a = b;
@@ -21,3 +2085,163 @@ pub fn fibonacci(n: u32) -> (u32, u32) {
     }
     (a, b)
 }
+
+/// Compute the n'th fibonacci number and the next one exactly, where [`fibonacci`] wraps around
+/// once n is past 92, with the fast-doubling method of [`fibonacci_fast`].
+///
+/// The doubling starts out with 256-bit numbers, see [`u256::fibonacci`], which multiply with
+/// SP1's bigint precompile inside the zkVM, for as long as they fit, and only continues with
+/// `BigUint` for the bits of n left after that.
+pub fn fibonacci_big(n: u32) -> (BigUint, BigUint) {
+    let mut shift = 0;
+    while n >> shift > u256::FIBONACCI_U256_MAX_N {
+        shift += 1;
+    }
+    let (a, b) = u256::fibonacci(n >> shift);
+    let mut a = u256::to_biguint(&a);
+    let mut b = u256::to_biguint(&b);
+    for bit in (0..shift).rev() {
+        // F(k+1) >= F(k), so the subtraction can't underflow.
+        let c = &a * (&b * 2u32 - &a);
+        let d = &a * &a + &b * &b;
+        (a, b) = if (n >> bit) & 1 == 0 {
+            (c, d)
+        } else {
+            let e = &c + &d;
+            (d, e)
+        };
+    }
+    (a, b)
+}
+
+/// The index of `x` in the fibonacci numbers, if it's one of them, the lowest one for 1, which is
+/// both F(1) and F(2).
+///
+/// It steps through the fibonacci numbers until they reach `x`, which takes at most 94 steps, as
+/// they overflow 64 bits past F(93). They're computed with 128 bits, so the last steps don't
+/// overflow.
+pub fn is_fibonacci(x: u64) -> Option<u32> {
+    let x = u128::from(x);
+    let (mut a, mut b) = (0u128, 1u128);
+    let mut index = 0;
+    while a < x {
+        (a, b) = (b, a + b);
+        index += 1;
+    }
+    (a == x).then_some(index)
+}
+
+/// From F(51) on, `F(n+1) / F(n)` is within 2^-64 of the golden ratio, so it rounds down to
+/// [`Q64x64::GOLDEN_RATIO`] for every larger n.
+const FIBONACCI_RATIO_STABLE_N: u32 = 51;
+
+/// `F(n+1) / F(n)` as a Q64.64 fixed-point number rounded down, or `None` for n = 0, as F(0) is 0.
+///
+/// The ratios alternate around the golden ratio and tend to it, reaching it within the precision
+/// of Q64.64 from n = 51 on, so only the fibonacci numbers up to there, which fit in 64 bits, are
+/// computed.
+pub fn fibonacci_ratio(n: u32) -> Option<Q64x64> {
+    let (a, b) = fibonacci(n.min(FIBONACCI_RATIO_STABLE_N));
+    Q64x64::from_ratio(b, a)
+}
+
+/// The Pisano period of `m`: the period of the fibonacci numbers modulo `m`, which is at most
+/// `6 * m`.
+///
+/// It's found by stepping through the fibonacci numbers modulo `m` until they start over at 0 and
+/// 1, so it takes as many steps as the period.
+///
+/// Panics if `m` is zero.
+pub fn pisano_period(m: u64) -> u64 {
+    assert_ne!(m, 0, "the modulus must be non-zero");
+    if m == 1 {
+        return 1;
+    }
+    let m = u128::from(m);
+    let (mut a, mut b) = (0u128, 1u128);
+    let mut period = 0;
+    loop {
+        (a, b) = (b, (a + b) % m);
+        period += 1;
+        if (a, b) == (0, 1) {
+            return period;
+        }
+    }
+}
+
+/// Compute the n'th fibonacci number and the next one modulo `p`, e.g. a prime field's modulus, so
+/// the results stay bounded however large n is, with the fast-doubling method of
+/// [`fibonacci_fast`].
+///
+/// Panics if `p` is zero.
+pub fn fibonacci_mod(n: u32, p: u64) -> (u64, u64) {
+    let p = u128::from(p);
+    // F(k) and F(k+1) modulo p, each below 2^64, so products of two of them fit.
+    let mut a = 0u128;
+    let mut b = 1 % p;
+    for bit in (0..u32::BITS - n.leading_zeros()).rev() {
+        let c = a * ((2 * b + p - a) % p) % p;
+        let d = (a * a % p + b * b % p) % p;
+        (a, b) = if (n >> bit) & 1 == 0 {
+            (c, d)
+        } else {
+            (d, (c + d) % p)
+        };
+    }
+    (a as u64, b as u64)
+}
+
+/// The `a_mod` and `b_mod` the program commits for the modulus `p`: the n'th fibonacci number and
+/// the next one plus the offset, modulo `p`, see [`fibonacci_mod`]. Unlike `a` and `b`, they
+/// don't wrap around before being reduced.
+pub fn fibonacci_mod_offset(n: u32, offset: u32, p: u64) -> (u64, u64) {
+    let (a, b) = fibonacci_mod(n, p);
+    let add_offset = |x: u64| ((u128::from(x) + u128::from(offset)) % u128::from(p)) as u64;
+    (add_offset(a), add_offset(b))
+}
+
+/// Compute the n'th fibonacci number like [`fibonacci`], with the fast-doubling method, in about
+/// log2(n) steps rather than n.
+///
+/// Going through the bits of n from the highest, it doubles k with `F(2k) = F(k) * (2F(k+1) -
+/// F(k))` and `F(2k+1) = F(k)^2 + F(k+1)^2`, adding one for every set bit. Both hold modulo 2^64
+/// too, so it wraps around on overflows exactly like [`fibonacci`].
+pub fn fibonacci_fast(n: u32) -> (u64, u64) {
+    fast_doubling(u64::from(n))
+}
+
+/// [`fibonacci_fast`] for any 64-bit n.
+fn fast_doubling(n: u64) -> (u64, u64) {
+    // F(k) and F(k+1), for k the bits of n gone through so far.
+    let mut a = 0u64;
+    let mut b = 1u64;
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        let c = a.wrapping_mul(b.wrapping_mul(2).wrapping_sub(a));
+        let d = a.wrapping_mul(a).wrapping_add(b.wrapping_mul(b));
+        (a, b) = if (n >> bit) & 1 == 0 {
+            (c, d)
+        } else {
+            (d, c.wrapping_add(d))
+        };
+    }
+    (a, b)
+}
+
+/// Compute the n'th fibonacci number and the next one for a signed n, wrapping around on overflows
+/// of 64-bit two's complement numbers.
+///
+/// The sequence extends to negative indices by `F(k) = F(k+2) - F(k+1)`, giving the
+/// negafibonacci numbers `F(-k) = (-1)^(k+1) F(k)`, so the results are exact for n from -92 up to
+/// 91. It takes about log2(|n|) steps, with the fast-doubling method of [`fibonacci_fast`].
+pub fn fibonacci_signed(n: i64) -> (i64, i64) {
+    let k = n.unsigned_abs();
+    let (a, b) = fast_doubling(k);
+    if n >= 0 {
+        return (a as i64, b as i64);
+    }
+    // F(n) = F(-k) and F(n+1) = F(-(k-1)), with F(k-1) = F(k+1) - F(k).
+    let negate_if = |x: u64, negative: bool| if negative { x.wrapping_neg() } else { x };
+    let a_signed = negate_if(a, k % 2 == 0);
+    let b_signed = negate_if(b.wrapping_sub(a), k % 2 == 1);
+    (a_signed as i64, b_signed as i64)
+}
@@ -0,0 +1,89 @@
+//! 256-bit arithmetic modulo 2^256 for the exact fibonacci numbers, multiplying with SP1's bigint
+//! precompile inside the zkVM, where it takes a fraction of the cycles of `BigUint`, and in plain
+//! Rust elsewhere.
+
+use num_bigint::BigUint;
+
+/// A 256-bit number as little-endian 32-bit words, the layout of the bigint precompile.
+pub type U256 = [u32; 8];
+
+/// The largest n whose fibonacci number and the next one fit in 256 bits.
+pub const FIBONACCI_U256_MAX_N: u32 = 369;
+
+/// The operation the bigint precompile computes, of which multiplication is the only one.
+#[cfg(target_os = "zkvm")]
+const BIGINT_MUL: u32 = 0;
+
+/// `x * y` modulo 2^256.
+#[cfg(target_os = "zkvm")]
+pub fn mul(x: &U256, y: &U256) -> U256 {
+    let mut result = [0; 8];
+    // A zero modulus has the precompile reduce modulo 2^256.
+    unsafe { sp1_lib::sys_bigint(&mut result, BIGINT_MUL, x, y, &[0; 8]) };
+    result
+}
+
+/// `x * y` modulo 2^256.
+#[cfg(not(target_os = "zkvm"))]
+pub fn mul(x: &U256, y: &U256) -> U256 {
+    let mut result = [0; 8];
+    for i in 0..8 {
+        let mut carry = 0u64;
+        for j in 0..8 - i {
+            let product = u64::from(x[i]) * u64::from(y[j]) + u64::from(result[i + j]) + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+    }
+    result
+}
+
+/// `x + y` modulo 2^256.
+pub fn add(x: &U256, y: &U256) -> U256 {
+    let mut result = [0; 8];
+    let mut carry = false;
+    for ((word, x), y) in result.iter_mut().zip(x).zip(y) {
+        let (sum, overflowed) = x.overflowing_add(*y);
+        let (sum, carried) = sum.overflowing_add(carry as u32);
+        *word = sum;
+        carry = overflowed || carried;
+    }
+    result
+}
+
+/// `x - y` modulo 2^256.
+pub fn sub(x: &U256, y: &U256) -> U256 {
+    let mut result = [0; 8];
+    let mut borrow = false;
+    for ((word, x), y) in result.iter_mut().zip(x).zip(y) {
+        let (difference, underflowed) = x.overflowing_sub(*y);
+        let (difference, borrowed) = difference.overflowing_sub(borrow as u32);
+        *word = difference;
+        borrow = underflowed || borrowed;
+    }
+    result
+}
+
+/// `x` as a [`BigUint`].
+pub fn to_biguint(x: &U256) -> BigUint {
+    BigUint::from_slice(x)
+}
+
+/// Compute the n'th fibonacci number and the next one modulo 2^256, with the fast-doubling method
+/// of [`fibonacci_fast`](crate::fibonacci_fast), whose identities hold modulo 2^256 too. They're
+/// exact up to [`FIBONACCI_U256_MAX_N`].
+pub fn fibonacci(n: u32) -> (U256, U256) {
+    let mut a: U256 = [0; 8];
+    let mut b: U256 = [1, 0, 0, 0, 0, 0, 0, 0];
+    for bit in (0..u32::BITS - n.leading_zeros()).rev() {
+        let c = mul(&a, &sub(&add(&b, &b), &a));
+        let d = add(&mul(&a, &a), &mul(&b, &b));
+        (a, b) = if (n >> bit) & 1 == 0 {
+            (c, d)
+        } else {
+            let e = add(&c, &d);
+            (d, e)
+        };
+    }
+    (a, b)
+}
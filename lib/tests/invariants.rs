@@ -0,0 +1,458 @@
+//! Properties the fibonacci functions and the public values encodings must hold for any input.
+
+use alloy_sol_types::{SolCall, SolType};
+use fibonacci_lib::{
+    add_offset, decode_big_results, encode_big_results, fibonacci, fibonacci_big,
+    fibonacci_checked, fibonacci_fast, fibonacci_mod, fibonacci_ratio, fibonacci_signed,
+    fibonacci_table, fibonacci_u128, fixed::Q64x64, is_fibonacci, linear_recurrence, pisano_period,
+    proof_path, public_values_digest, split_public_values, table_proof, table_root, u256,
+    verifyFibonacciProofCall, verify_inclusion_proof, verify_table_entry, FibonacciInputs,
+    FibonacciIter, FibonacciTable, InclusionProofStruct, PreviousProof, PublicValuesEncoding,
+    PublicValuesStruct, PublicValuesWriter, Sequence, VersionedPublicValues, PUBLIC_VALUES_VERSION,
+};
+use num_bigint::BigUint;
+use proptest::{prelude::*, sample::Index};
+use sha2::{Digest, Sha256};
+
+/// Public values with every field picked at random.
+fn public_values() -> impl Strategy<Value = PublicValuesStruct> {
+    (
+        prop::array::uniform32(any::<u64>()),
+        prop::array::uniform17(any::<[u8; 32]>()),
+        prop::array::uniform8(any::<bool>()),
+        prop::array::uniform2(any::<u8>()),
+    )
+        .prop_map(|(numbers, hashes, flags, bytes)| PublicValuesStruct {
+            version: numbers[0] as u32,
+            n: numbers[1] as u32,
+            offset: numbers[2] as u32,
+            offset_hash: hashes[0].into(),
+            a: numbers[3],
+            b: numbers[4],
+            key: hashes[1].into(),
+            leaf: hashes[2].into(),
+            insert_count: numbers[5] as u32,
+            root: hashes[3],
+            nonce: numbers[6],
+            timestamp: numbers[7],
+            inputs_digest: hashes[4].into(),
+            child_vkey: hashes[5].into(),
+            overflowed: flags[0],
+            roots_digest: hashes[6].into(),
+            prior_root: hashes[7].into(),
+            entry_count: numbers[8] as u32,
+            inclusion_verified: flags[1],
+            absent_key: hashes[8].into(),
+            updates_digest: hashes[9].into(),
+            hasher: bytes[0],
+            removal_count: numbers[9] as u32,
+            update_key: hashes[10].into(),
+            old_leaf: hashes[11].into(),
+            new_leaf: hashes[12].into(),
+            namespace_count: numbers[10] as u32,
+            namespaces_digest: hashes[13].into(),
+            blob_count: numbers[11] as u32,
+            blobs_digest: hashes[14].into(),
+            big: flags[2],
+            modulus: numbers[12],
+            a_mod: numbers[13],
+            b_mod: numbers[14],
+            recurrence_a0: numbers[15],
+            recurrence_a1: numbers[16],
+            recurrence_p: numbers[17] as i64,
+            recurrence_q: numbers[18] as i64,
+            error_code: bytes[1],
+            wide: flags[3],
+            a_hi: numbers[19],
+            b_hi: numbers[20],
+            pisano_modulus: numbers[21],
+            pisano_period: numbers[22],
+            signed: flags[4],
+            signed_n: numbers[23] as i64,
+            a_signed: numbers[24] as i64,
+            b_signed: numbers[25] as i64,
+            table_stride: numbers[26] as u32,
+            table_len: numbers[27] as u32,
+            table_root: hashes[15].into(),
+            membership: flags[5],
+            membership_x: numbers[28],
+            membership_found: flags[6],
+            membership_index: numbers[29] as u32,
+            golden_ratio: flags[7],
+            ratio: (u128::from(numbers[30]) << 64) | u128::from(numbers[31]),
+            previous_digest: hashes[16].into(),
+        })
+}
+
+/// An inclusion proof with a handful of steps of random cuts.
+fn inclusion_proof() -> impl Strategy<Value = Vec<(bool, Vec<u8>)>> {
+    prop::collection::vec(
+        (any::<bool>(), prop::collection::vec(any::<u8>(), 1..64)),
+        0..8,
+    )
+}
+
+/// Check that public values committed in `encoding`, Borsh or SSZ, as `packed` decode only with
+/// the current version and nothing unread after the inclusion proof, as ABI-encoded ones do.
+#[cfg(any(feature = "borsh", feature = "ssz"))]
+fn check_packed_decoding(
+    encoding: PublicValuesEncoding,
+    version: u32,
+    packed: Vec<u8>,
+    extra: u8,
+) -> Result<(), TestCaseError> {
+    let mut bytes = [
+        packed,
+        InclusionProofStruct::abi_encode(&InclusionProofStruct::default()),
+    ]
+    .concat();
+    prop_assert_eq!(
+        fibonacci_lib::decode_public_values(&bytes, encoding).is_ok(),
+        version == PUBLIC_VALUES_VERSION
+    );
+    bytes.push(extra);
+    prop_assert!(fibonacci_lib::decode_public_values(&bytes, encoding).is_err());
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn fibonacci_follows_the_recurrence(n in 0u32..10_000) {
+        let (a, b) = fibonacci(n);
+        prop_assert_eq!(fibonacci(n + 1), (b, a.wrapping_add(b)));
+    }
+
+    #[test]
+    fn fibonacci_doubles(n in 0u32..5_000) {
+        // F(2n) = F(n) * (2 * F(n+1) - F(n)) and F(2n+1) = F(n)^2 + F(n+1)^2, wrapping around.
+        let (a, b) = fibonacci(n);
+        let doubled = (
+            a.wrapping_mul(b.wrapping_mul(2).wrapping_sub(a)),
+            a.wrapping_mul(a).wrapping_add(b.wrapping_mul(b)),
+        );
+        prop_assert_eq!(fibonacci(2 * n), doubled);
+    }
+
+    #[test]
+    fn methods_agree(n in 0u32..10_000) {
+        let expected = fibonacci(n);
+        prop_assert_eq!(fibonacci_fast(n), expected);
+        prop_assert_eq!(FibonacciIter::new().nth(n as usize), Some(expected));
+        prop_assert_eq!(FibonacciTable::new().get(n), expected);
+        prop_assert_eq!(linear_recurrence(0, 1, 1, -1, n), expected);
+    }
+
+    #[test]
+    fn checked_fibonacci_fails_only_on_overflow(n in 0u32..1_000) {
+        let (a, b) = fibonacci_big(n);
+        match fibonacci_checked(n) {
+            Ok(results) => prop_assert_eq!(results, fibonacci(n)),
+            Err(err) => {
+                prop_assert_eq!(err.n, n);
+                prop_assert!(b > BigUint::from(u64::MAX) || a > BigUint::from(u64::MAX));
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_fibonacci_is_exact_modulo_2_64(n in 0u32..2_000) {
+        let (a, b) = fibonacci_big(n);
+        let modulus = BigUint::from(1u8) << 64;
+        let truncate = |x: BigUint| u64::try_from(x % &modulus).unwrap();
+        prop_assert_eq!((truncate(a), truncate(b)), fibonacci(n));
+    }
+
+    #[test]
+    fn u256_arithmetic_wraps_like_biguint(x: [u32; 8], y: [u32; 8]) {
+        let modulus = BigUint::from(1u8) << 256;
+        let (x_big, y_big) = (u256::to_biguint(&x), u256::to_biguint(&y));
+        prop_assert_eq!(u256::to_biguint(&u256::mul(&x, &y)), &x_big * &y_big % &modulus);
+        prop_assert_eq!(u256::to_biguint(&u256::add(&x, &y)), (&x_big + &y_big) % &modulus);
+        prop_assert_eq!(
+            u256::to_biguint(&u256::sub(&x, &y)),
+            (&x_big + &modulus - &y_big) % &modulus
+        );
+    }
+
+    #[test]
+    fn u256_fibonacci_is_exact_while_it_fits(n in 0..=u256::FIBONACCI_U256_MAX_N) {
+        let (a, b) = u256::fibonacci(n);
+        prop_assert_eq!((u256::to_biguint(&a), u256::to_biguint(&b)), fibonacci_big(n));
+    }
+
+    #[test]
+    fn modular_fibonacci_is_exact(n in 0u32..2_000, p in 1u64..) {
+        let (a, b) = fibonacci_big(n);
+        let reduce = |x: BigUint| u64::try_from(x % p).unwrap();
+        prop_assert_eq!(fibonacci_mod(n, p), (reduce(a), reduce(b)));
+    }
+
+    #[test]
+    fn wide_fibonacci_extends_the_wrapped_one(n in 0u32..2_000) {
+        let (a, b) = fibonacci_u128(n);
+        prop_assert_eq!((a as u64, b as u64), fibonacci(n));
+        let (a_big, b_big) = fibonacci_big(n);
+        let modulus = BigUint::from(1u8) << 128;
+        let truncate = |x: BigUint| u128::try_from(x % &modulus).unwrap();
+        prop_assert_eq!((truncate(a_big), truncate(b_big)), (a, b));
+    }
+
+    #[test]
+    fn signed_fibonacci_extends_the_recurrence(n in -10_000i64..10_000) {
+        // F(n+2) = F(n+1) + F(n) holds for negative indices too, wrapping around.
+        let (a, b) = fibonacci_signed(n);
+        prop_assert_eq!(fibonacci_signed(n + 1), (b, a.wrapping_add(b)));
+    }
+
+    #[test]
+    fn negafibonacci_alternates_in_sign(k in 0u32..10_000) {
+        let (a, _) = fibonacci(k);
+        let expected = if k % 2 == 0 { (a as i64).wrapping_neg() } else { a as i64 };
+        prop_assert_eq!(fibonacci_signed(-i64::from(k)).0, expected);
+        prop_assert_eq!(fibonacci_signed(i64::from(k)).0, a as i64);
+    }
+
+    #[test]
+    fn pisano_period_is_the_period(m in 1u64..5_000) {
+        // The fibonacci numbers modulo m start over at 0 and 1 after the period, and not before.
+        let period = pisano_period(m);
+        prop_assert!(period <= 6 * m);
+        prop_assert_eq!(fibonacci_mod(period as u32, m), (0, 1 % m));
+        if m > 1 {
+            for k in 1..period {
+                prop_assert_ne!(fibonacci_mod(k as u32, m), (0, 1));
+            }
+        }
+    }
+
+    #[test]
+    fn membership_finds_the_lowest_index(n in 0u32..=93, x: u64) {
+        // F(1) = F(2) = 1 is the only number with two indices.
+        let lowest = if n == 2 { 1 } else { n };
+        prop_assert_eq!(is_fibonacci(fibonacci(n).0), Some(lowest));
+        match is_fibonacci(x) {
+            Some(index) => prop_assert_eq!(fibonacci(index).0, x),
+            None => prop_assert!((0..=93).all(|k| fibonacci(k).0 != x)),
+        }
+    }
+
+    #[test]
+    fn fibonacci_ratio_rounds_down_and_tends_to_the_golden_ratio(n in 1u32..10_000) {
+        // F(n+1) / F(n) is exact to 64 fractional bits while F(n+1) fits in 64 bits, and the
+        // golden ratio rounded down from there on.
+        let ratio = fibonacci_ratio(n).unwrap();
+        match fibonacci_checked(n + 1) {
+            Ok((b, _)) => {
+                let a = fibonacci(n).0;
+                let rest = (u128::from(b) << 64) - ratio.0 * u128::from(a);
+                prop_assert!(rest < u128::from(a));
+            }
+            Err(_) => prop_assert_eq!(ratio, Q64x64::GOLDEN_RATIO),
+        }
+        prop_assert!((ratio.to_f64() - 1.618_033_988_749_895).abs() <= 1.0 / f64::from(n));
+    }
+
+    #[test]
+    fn table_entries_are_proven_by_the_root(n in 0u32..500, k in 1u32..20, offset: u32) {
+        let table = fibonacci_table(n, k, offset);
+        prop_assert_eq!(table.len() as u32, n / k + 1);
+        let root = table_root(&table);
+        let len = table.len() as u32;
+        for (position, &(index, value)) in table.iter().enumerate() {
+            prop_assert_eq!(index, position as u32 * k);
+            prop_assert_eq!(add_offset(fibonacci(index).0, 0, offset).0, value);
+            let proof = table_proof(&table, position);
+            prop_assert!(verify_table_entry(root, len, position as u32, (index, value), &proof));
+            let wrong = (index, value.wrapping_add(1));
+            prop_assert!(!verify_table_entry(root, len, position as u32, wrong, &proof));
+        }
+    }
+
+    #[test]
+    fn inclusion_proofs_lead_to_their_root(
+        leaf: [u8; 32],
+        steps in inclusion_proof(),
+        malformed_step: Index,
+    ) {
+        let digest = |bytes: &[u8]| -> [u8; 32] { Sha256::digest(bytes).into() };
+        let (root, nodes) = proof_path(digest, &leaf, &steps).unwrap();
+        prop_assert_eq!(nodes.len(), steps.len());
+        prop_assert!(verify_inclusion_proof(digest, &root, &leaf, &steps));
+        // A step going right with an empty cut is rejected rather than panicking.
+        let mut malformed = steps.clone();
+        malformed.insert(malformed_step.index(steps.len() + 1), (true, Vec::new()));
+        prop_assert!(proof_path(digest, &leaf, &malformed).is_none());
+        prop_assert!(!verify_inclusion_proof(digest, &root, &leaf, &malformed));
+    }
+
+    #[test]
+    fn zero_offset_is_the_identity(a: u64, b: u64) {
+        prop_assert_eq!(add_offset(a, b, 0), (a, b, false));
+    }
+
+    #[test]
+    fn offset_wraps_exactly_on_overflow(a: u64, b: u64, offset: u32) {
+        let (a_offset, b_offset, overflowed) = add_offset(a, b, offset);
+        let a_exact = u128::from(a) + u128::from(offset);
+        let b_exact = u128::from(b) + u128::from(offset);
+        prop_assert_eq!(a_offset, a_exact as u64);
+        prop_assert_eq!(b_offset, b_exact as u64);
+        let max = u128::from(u64::MAX);
+        prop_assert_eq!(overflowed, a_exact > max || b_exact > max);
+    }
+
+    #[test]
+    fn offsets_add_up(a: u64, b: u64, first: u16, second: u16) {
+        // Without overflows, adding two offsets one after the other adds their sum.
+        let (a_once, b_once, _) = add_offset(a, b, u32::from(first));
+        let (a_twice, b_twice, _) = add_offset(a_once, b_once, u32::from(second));
+        let (a_sum, b_sum, _) = add_offset(a, b, u32::from(first) + u32::from(second));
+        prop_assert_eq!((a_twice, b_twice), (a_sum, b_sum));
+    }
+
+    #[test]
+    fn abi_encoding_round_trips(values in public_values()) {
+        let bytes = PublicValuesStruct::abi_encode(&values);
+        prop_assert_eq!(bytes.len(), PublicValuesEncoding::Abi.struct_size());
+        let decoded = PublicValuesStruct::abi_decode(&bytes, true).unwrap();
+        prop_assert_eq!(PublicValuesStruct::abi_encode(&decoded), bytes);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips(values in public_values()) {
+        let json = serde_json::to_string(&values).unwrap();
+        let decoded: PublicValuesStruct = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(
+            PublicValuesStruct::abi_encode(&decoded),
+            PublicValuesStruct::abi_encode(&values)
+        );
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh_encoding_round_trips(values in public_values()) {
+        let bytes = values.borsh_encode();
+        prop_assert_eq!(bytes.len(), PublicValuesEncoding::Borsh.struct_size());
+        let decoded = PublicValuesStruct::borsh_decode(&bytes).unwrap();
+        prop_assert_eq!(decoded.borsh_encode(), bytes);
+    }
+
+    #[cfg(feature = "ssz")]
+    #[test]
+    fn ssz_encoding_round_trips(values in public_values()) {
+        let bytes = values.ssz_encode();
+        prop_assert_eq!(bytes.len(), PublicValuesEncoding::Ssz.struct_size());
+        let decoded = PublicValuesStruct::ssz_decode(&bytes).unwrap();
+        prop_assert_eq!(decoded.ssz_encode(), bytes);
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh_public_values_are_checked(
+        values in public_values(),
+        version in prop_oneof![Just(PUBLIC_VALUES_VERSION), any::<u32>()],
+        extra: u8,
+    ) {
+        let values = PublicValuesStruct { version, big: false, ..values };
+        check_packed_decoding(PublicValuesEncoding::Borsh, version, values.borsh_encode(), extra)?;
+    }
+
+    #[cfg(feature = "ssz")]
+    #[test]
+    fn ssz_public_values_are_checked(
+        values in public_values(),
+        version in prop_oneof![Just(PUBLIC_VALUES_VERSION), any::<u32>()],
+        extra: u8,
+    ) {
+        let values = PublicValuesStruct { version, big: false, ..values };
+        check_packed_decoding(PublicValuesEncoding::Ssz, version, values.ssz_encode(), extra)?;
+    }
+
+    #[test]
+    fn committed_bytes_split_back(
+        values in public_values(),
+        steps in inclusion_proof(),
+        big_results: Option<(u128, u128)>,
+    ) {
+        // Lay the bytes out the way the program commits them.
+        let values = PublicValuesStruct {
+            version: PUBLIC_VALUES_VERSION,
+            big: big_results.is_some(),
+            ..values
+        };
+        let big_results = big_results.map(|(a, b)| (BigUint::from(a), BigUint::from(b)));
+        let mut bytes = PublicValuesWriter::new()
+            .push::<PublicValuesStruct>(&values)
+            .push_last::<InclusionProofStruct>(&InclusionProofStruct::from_steps(&steps));
+        if let Some((a, b)) = &big_results {
+            bytes.extend(encode_big_results(a, b));
+        }
+
+        let decoded = match VersionedPublicValues::abi_decode(&bytes).unwrap() {
+            VersionedPublicValues::Current(decoded) => decoded,
+            VersionedPublicValues::Legacy(_) => panic!("decoded as the legacy layout"),
+        };
+        prop_assert_eq!(
+            PublicValuesStruct::abi_encode(&decoded),
+            PublicValuesStruct::abi_encode(&values)
+        );
+        let (_, proof, trailer) = split_public_values(&bytes, PublicValuesEncoding::Abi).unwrap();
+        prop_assert_eq!(proof.steps(), steps);
+        prop_assert_eq!(decode_big_results(values.big, trailer).unwrap(), big_results);
+    }
+
+    #[test]
+    fn other_versions_fail_to_convert(values in public_values()) {
+        prop_assume!(values.version != PUBLIC_VALUES_VERSION);
+        let bytes = PublicValuesWriter::new()
+            .push::<PublicValuesStruct>(&values)
+            .push_last::<InclusionProofStruct>(&InclusionProofStruct::default());
+        prop_assert!(PublicValuesStruct::try_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn calldata_carries_the_committed_bytes(public_values: Vec<u8>, proof_bytes: Vec<u8>) {
+        let calldata = PublicValuesStruct::solidity_calldata(&public_values, &proof_bytes);
+        prop_assert_eq!(&calldata[..4], &verifyFibonacciProofCall::SELECTOR[..]);
+        let call = verifyFibonacciProofCall::abi_decode(&calldata, true).unwrap();
+        prop_assert_eq!(call._publicValues.to_vec(), public_values);
+        prop_assert_eq!(call._proofBytes.to_vec(), proof_bytes);
+    }
+
+    #[test]
+    fn chaining_commits_the_previous_vkey_and_digest(vkey: [u32; 8], public_values: Vec<u8>) {
+        prop_assume!(vkey != [0; 8]);
+        let inputs = FibonacciInputs::default();
+        prop_assert_eq!(inputs.chained(), Default::default());
+        let previous = PreviousProof {
+            vkey,
+            public_values: public_values.clone(),
+        };
+        let (child_vkey, previous_digest) = FibonacciInputs {
+            previous: Some(previous),
+            ..inputs
+        }
+        .chained();
+        let values = PublicValuesStruct {
+            child_vkey,
+            ..Default::default()
+        };
+        prop_assert_eq!(values.child_vkey_words(), Some(vkey));
+        prop_assert_eq!(previous_digest.0, public_values_digest(&public_values));
+    }
+
+    #[test]
+    fn big_results_reject_trailing_bytes(a: u128, b: u128, extra in 1u8..) {
+        let mut bytes = encode_big_results(&BigUint::from(a), &BigUint::from(b));
+        bytes.push(extra);
+        prop_assert!(decode_big_results(true, &bytes).is_err());
+    }
+
+    #[test]
+    fn sequences_parse_back(a0: u64, a1: u64, p: i64, q: i64) {
+        let sequence = Sequence::from_params(a0, a1, p, q);
+        prop_assert_eq!(sequence.to_string().parse::<Sequence>(), Ok(sequence));
+        prop_assert_eq!(sequence.params(), (a0, a1, p, q));
+    }
+}
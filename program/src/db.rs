@@ -0,0 +1,77 @@
+//! A monotree database holding the nodes of a [`TreeWitness`], so the program can update a tree
+//! without building all of it first.
+//!
+//! [`TreeWitness`]: fibonacci_lib::TreeWitness
+
+use std::{collections::HashMap, sync::Mutex};
+
+use monotree::database::Database;
+
+/// The nodes the next [`WitnessDB`] starts with. Monotree creates its database itself, given only a
+/// path, so they're handed over through here.
+static PRELOADED: Mutex<Option<HashMap<[u8; 32], Vec<u8>>>> = Mutex::new(None);
+
+/// An in-memory database starting out with the nodes given to [`WitnessDB::preload`].
+///
+/// Nodes are keyed by their hash as a fixed-size array rather than a `Vec`, so storing one doesn't
+/// allocate for its key, and the map is sized up front for the nodes the program is expected to
+/// write, so it doesn't grow, rehashing every node, while the program updates the tree.
+pub struct WitnessDB {
+    nodes: HashMap<[u8; 32], Vec<u8>>,
+}
+
+impl WitnessDB {
+    /// Set the `(hash, node)` pairs the next database created starts with, making room for the
+    /// nodes `updates` updates of the tree write on top of them.
+    pub fn preload(nodes: Vec<([u8; 32], Vec<u8>)>, updates: usize) {
+        let capacity = nodes.len() + expected_writes(updates, nodes.len());
+        let mut preloaded = HashMap::with_capacity(capacity);
+        preloaded.extend(nodes);
+        *PRELOADED.lock().unwrap() = Some(preloaded);
+    }
+}
+
+/// A rough upper bound of the nodes `updates` updates write to a tree of about `nodes` nodes: every
+/// update writes the nodes on the path to its key, which is about as deep as the log2 of the number
+/// of nodes, plus a couple at the key itself.
+fn expected_writes(updates: usize, nodes: usize) -> usize {
+    let depth = (usize::BITS - (updates + nodes).leading_zeros()) as usize;
+    updates * (depth + 2)
+}
+
+impl Database for WitnessDB {
+    fn new(_dbpath: &str) -> Self {
+        Self {
+            nodes: PRELOADED.lock().unwrap().take().unwrap_or_default(),
+        }
+    }
+
+    fn get(&mut self, key: &[u8]) -> monotree::Result<Option<Vec<u8>>> {
+        Ok(<[u8; 32]>::try_from(key)
+            .ok()
+            .and_then(|key| self.nodes.get(&key).cloned()))
+    }
+
+    fn put(&mut self, key: &[u8], value: Vec<u8>) -> monotree::Result<()> {
+        let key = key
+            .try_into()
+            .map_err(|_| monotree::Errors::new("node keys are 32-byte hashes"))?;
+        self.nodes.insert(key, value);
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> monotree::Result<()> {
+        if let Ok(key) = <[u8; 32]>::try_from(key) {
+            self.nodes.remove(&key);
+        }
+        Ok(())
+    }
+
+    fn init_batch(&mut self) -> monotree::Result<()> {
+        Ok(())
+    }
+
+    fn finish_batch(&mut self) -> monotree::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+//! A monotree hasher dispatching to the hasher the host selected, so the same program can build
+//! trees with any of them.
+
+use std::sync::Mutex;
+
+use fibonacci_lib::TreeHasher;
+use monotree::{hasher::*, Hash};
+
+/// The hasher the next [`SelectedHasher`]s are created with. Monotree creates its hasher itself,
+/// without arguments, so the selection is handed over through here.
+static SELECTED: Mutex<TreeHasher> = Mutex::new(TreeHasher::Blake3);
+
+/// A hasher hashing with the [`TreeHasher`] given to [`SelectedHasher::select`].
+pub struct SelectedHasher(TreeHasher);
+
+impl SelectedHasher {
+    /// Set the hasher the hashers created from now on hash with.
+    pub fn select(hasher: TreeHasher) {
+        *SELECTED.lock().unwrap() = hasher;
+    }
+}
+
+impl Hasher for SelectedHasher {
+    fn new() -> Self {
+        Self(*SELECTED.lock().unwrap())
+    }
+
+    fn digest(&self, bytes: &[u8]) -> Hash {
+        match self.0 {
+            TreeHasher::Blake3 => Blake3::new().digest(bytes),
+            TreeHasher::Blake2b => Blake2b::new().digest(bytes),
+            TreeHasher::Sha2 => Sha2::new().digest(bytes),
+            #[cfg(feature = "poseidon")]
+            TreeHasher::Poseidon => fibonacci_lib::poseidon_digest(bytes),
+        }
+    }
+}
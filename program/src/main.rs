@@ -8,9 +8,18 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
+mod db;
+mod hasher;
+
+use alloy_primitives::B256;
 use alloy_sol_types::SolType;
-use fibonacci_lib::{fibonacci, PublicValuesStruct};
-use monotree::database::*;
+use fibonacci_lib::{
+    add_offset, blobs_digest, chain_root, decode_public_values, encode_big_results, fibonacci_big,
+    fibonacci_mod_offset, fibonacci_ratio, fibonacci_table, inputs_digest, is_fibonacci,
+    namespaces_digest, offset_commitment, pisano_period, proof_path, table_root, updates_digest,
+    FibonacciInputs, InclusionProofStruct, PublicValuesEncoding, PublicValuesStruct,
+    PublicValuesWriter, Sequence, TreeWitness, ERROR_OVERFLOW, PUBLIC_VALUES_VERSION,
+};
 use monotree::hasher::*;
 // use monotree::utils::*;
 use monotree::*;
@@ -19,16 +28,203 @@ pub fn main() {
     // Read an input to the program.
     //
     // Behind the scenes, this compiles down to a custom system call which handles reading inputs
-    // from the prover.
-    let n = sp1_zkvm::io::read::<u32>();
+    // from the prover. The raw bytes are read first, so that exactly what was read can be hashed
+    // into the public values, binding the proof to those inputs.
+    let input_bytes = sp1_zkvm::io::read_vec();
+    let inputs_digest = inputs_digest([input_bytes.as_slice()]);
+    let inputs: FibonacciInputs =
+        bincode::deserialize(&input_bytes).expect("failed to deserialize the inputs");
+    let FibonacciInputs {
+        n,
+        offset,
+        salt,
+        nonce,
+        timestamp,
+        encoding,
+        sequence,
+        ..
+    } = inputs;
+
+    // Read the nodes of the tree the inserts below read, and check each of them against its hash,
+    // which is what monotree looks them up by. The root they hang off is committed, so the proof
+    // covers the inserts into that exact tree.
+    let witness: TreeWitness = sp1_zkvm::io::read();
+    hasher::SelectedHasher::select(inputs.hasher);
+    let hasher = hasher::SelectedHasher::new();
+    for (hash, node) in &witness.nodes {
+        assert_eq!(
+            &hasher.digest(node),
+            hash,
+            "witness node doesn't match its hash"
+        );
+    }
+
+    // Given the Merkle proof of the key to update, change its leaf by hashing along the path of
+    // the proof instead, checking that it leads from the old leaf to the prior root. The nodes on
+    // the path before and after the change both follow from the proof, so the host doesn't send
+    // them, and they're added to the witness for reading the tree afterwards. The proof doesn't
+    // say which key it's of, so the key is read back through them once the tree is set up below.
+    let mut nodes = witness.nodes;
+    let updated_root = match (&inputs.leaf_update, &witness.leaf_update_proof) {
+        (Some(update), Some(proof)) => {
+            let digest = |bytes: &[u8]| hasher.digest(bytes);
+            let (old_root, old_path) = proof_path(digest, &update.old_leaf, proof)
+                .expect("malformed Merkle proof of the key to update");
+            assert_eq!(
+                Some(old_root),
+                witness.root,
+                "the old leaf isn't at the key to update"
+            );
+            let (new_root, new_path) = proof_path(digest, &update.new_leaf, proof)
+                .expect("malformed Merkle proof of the key to update");
+            nodes.extend(old_path.into_iter().chain(new_path));
+            Some(new_root)
+        }
+        _ => None,
+    };
+    // Size the database for every update of the main tree and of the namespaces' trees, which
+    // the inputs tell before any of them is made.
+    let update_count = inputs.leaf_update.is_some() as usize
+        + inputs.batch_len()
+        + inputs.removals.len()
+        + 1
+        + inputs
+            .namespaces
+            .iter()
+            .map(|namespace| namespace.entries.len())
+            .sum::<usize>();
+    db::WitnessDB::preload(nodes, update_count);
+
+    // Verify the proof of the previous run, if chaining onto one, with the zkVM's deferred proof
+    // verification, and check that it left the tree at the prior root this run picks it up at.
+    // Its verification key is committed as `child_vkey`, and it must be a leaf proof or chained
+    // onto a proof with that same key, so every proof of the chain is one of this program.
+    let (child_vkey, previous_digest) = inputs.chained();
+    if let Some(previous) = &inputs.previous {
+        sp1_zkvm::lib::verify::verify_sp1_proof(&previous.vkey, &previous_digest.0);
+        let values = decode_public_values(&previous.public_values, encoding)
+            .expect("failed to decode the previous public values");
+        assert_eq!(
+            values.root,
+            witness.root.unwrap_or_default(),
+            "the previous run didn't leave the tree at the prior root"
+        );
+        assert!(
+            values.is_leaf() || values.child_vkey == child_vkey,
+            "the previous run chained onto a proof of another program"
+        );
+    }
+
+    // Compute the n'th fibonacci number, or element of the sequence the host picked instead, using
+    // a function from the workspace lib crate, with the method the host picked.
+    //
+    // The cycle-tracker markers let the host report the cycles spent in each phase.
+    println!("cycle-tracker-start: fibonacci");
+    let (a, b) = inputs.results();
+    println!("cycle-tracker-end: fibonacci");
 
-    let offset = sp1_zkvm::io::read::<u32>();
+    // offset for fun, noting whether it made the numbers wrap around.
+    let (a, b, overflowed) = add_offset(a, b, offset);
+    assert!(
+        sequence == Sequence::Fibonacci
+            || (!inputs.big
+                && inputs.modulus.is_none()
+                && !inputs.checked
+                && !inputs.wide
+                && inputs.signed_index.is_none()
+                && inputs.table_stride.is_none()
+                && !inputs.golden_ratio),
+        "only a and b are supported for sequences other than the fibonacci numbers"
+    );
+
+    // Abort if the host asked for checked results and they overflow, rather than commit wrapped
+    // ones that verifiers could take for the real fibonacci numbers. The error code is committed
+    // along with what was read, so the proof attests to why nothing else was, and the tree is left
+    // at its prior root.
+    if let (true, Err(err)) = (inputs.checked, inputs.checked_results()) {
+        println!("aborting: {}", err);
+        let prior_root = witness.root.unwrap_or_default();
+        let (offset, offset_hash) = committed_offset(offset, salt);
+        let public_values = PublicValuesStruct {
+            version: PUBLIC_VALUES_VERSION,
+            n,
+            offset,
+            offset_hash,
+            root: prior_root,
+            nonce,
+            timestamp,
+            inputs_digest,
+            prior_root: prior_root.into(),
+            hasher: inputs.hasher.id(),
+            error_code: ERROR_OVERFLOW,
+            child_vkey,
+            previous_digest,
+            ..Default::default()
+        };
+        commit(
+            encoding,
+            &public_values,
+            &InclusionProofStruct::default(),
+            None,
+        );
+        return;
+    }
 
-    // Compute the n'th fibonacci number using a function from the workspace lib crate.
-    let (a, b) = fibonacci(n);
+    // The exact fibonacci numbers, if the host asked for them, as `a` and `b` wrap around once n
+    // is past 92. Their size depends on n, so they're committed last, after the inclusion proof.
+    let big_results = inputs.big.then(|| {
+        let (a, b) = fibonacci_big(n);
+        encode_big_results(&(a + offset), &(b + offset))
+    });
 
-    // offset for fun
-    let (a, b) = (a + offset, b + offset);
+    // The numbers modulo the modulus the host picked, if any, e.g. a field's for interop with
+    // field-based systems, which stay bounded however large n is.
+    let modulus = inputs.modulus.unwrap_or(0);
+    assert_ne!(inputs.modulus, Some(0), "the modulus must be non-zero");
+    let (a_mod, b_mod) = inputs
+        .modulus
+        .map_or((0, 0), |p| fibonacci_mod_offset(n, offset, p));
+
+    // The high halves of the numbers computed with 128 bits, if the host asked for them, `a` and
+    // `b` being their low halves. They stay exact up to n = 185, without the cost of `big`.
+    let (a_hi, b_hi) = match inputs.wide {
+        true => {
+            let (a, b) = inputs.wide_results();
+            ((a >> 64) as u64, (b >> 64) as u64)
+        }
+        false => (0, 0),
+    };
+
+    // The fibonacci numbers at the signed index the host picked, if any, which extend to negative
+    // indices as the negafibonacci numbers, committed as two's complement numbers.
+    let (a_signed, b_signed) = inputs.signed_results().unwrap_or_default();
+
+    // Every k'th fibonacci number up to the n'th, for the k the host picked, if any, committed as
+    // the root of a Merkle tree over them, so the proof certifies the whole table rather than only
+    // the last pair, and any entry can be checked against it on its own.
+    let table = inputs
+        .table_stride
+        .map(|k| fibonacci_table(n, k, offset))
+        .unwrap_or_default();
+
+    // The index of the number the host asked about in the fibonacci numbers, if it's one of them,
+    // so the proof also attests to statements in the other direction: that x is the n'th one.
+    let membership_index = inputs.membership.and_then(is_fibonacci);
+
+    // F(n+1) / F(n), which tends to the golden ratio, if the host asked for it, committed as a
+    // Q64.64 fixed-point number, i.e. a non-integer result a contract can use as is. It's zero for
+    // n = 0, which has no ratio, as no ratio of fibonacci numbers is below 1.
+    let ratio = match inputs.golden_ratio {
+        true => fibonacci_ratio(n).unwrap_or_default(),
+        false => Default::default(),
+    };
+
+    // The Pisano period of the modulus the host picked, if any, i.e. the period of the fibonacci
+    // numbers modulo it. Finding it takes as many steps as the period, up to 6 times the modulus,
+    // which makes for a heavier workload than the rest to exercise proving costs with.
+    println!("cycle-tracker-start: pisano");
+    let (pisano_modulus, pisano_period) = inputs.pisano.map_or((0, 0), |m| (m, pisano_period(m)));
+    println!("cycle-tracker-end: pisano");
 
     let current_id = b.to_string(); // Get current fibonacci number as a String
 
@@ -40,38 +236,262 @@ pub fn main() {
     // Monotree::<DATABASE, HASHER>::new(DB_PATH)
     // where DATABASE = {MemoryDB, RocksDB, Sled}
     //         HASHER = {Blake3, Blake2s, Blake2b, Sha2, Sha3}
-    let mut tree = Monotree::<MemoryDB, Blake3>::new("/tmp/monotree");
+    // The witness database is a MemoryDB starting out with the witness nodes.
+    let mut tree = Monotree::<db::WitnessDB, hasher::SelectedHasher>::new("/tmp/monotree");
+
+    // The tree root is 'None' for an empty tree, or wherever the witness picks it up.
+    let mut root = witness.root;
+    let prior_root = root.unwrap_or_default();
+
+    // Insert the key and the leaf the host asked for, or the default ones.
+    let (key, leaf) = inputs.tree_entry(b);
+
+    // Prove that the key the host asked about isn't in the tree at the prior root, if any. The
+    // witness nodes are checked against their hashes, so monotree can't be fooled into missing it.
+    let absent_key = inputs.absent_key.unwrap_or_default();
+    if inputs.absent_key.is_some() {
+        let found = tree
+            .get(root.as_ref(), &absent_key)
+            .expect("couldn't look up the absent key");
+        assert_eq!(found, None, "the key is in the tree");
+    }
+
+    // The updates to apply to the tree at the prior root, committed as a digest so the proof
+    // attests to the transition from the prior root to the new one through exactly these updates.
+    let updates = inputs.updates(b);
+    let updates_digest = updates_digest(&updates);
+    let (_, rest) = updates.split_at(inputs.leaf_update.is_some() as usize);
+    let (batch, rest) = rest.split_at(inputs.batch_len());
+    let (removals, entry) = rest.split_at(inputs.removals.len());
+
+    println!("cycle-tracker-start: monotree");
+    let mut roots_digest = Default::default();
 
-    // It is natural the tree root initially has 'None'
-    let mut root = None;
+    // Change the leaf at a key already in the tree, if the host asked to, checking that the old
+    // leaf is the one there at the prior root first, unless it was changed along its proof above.
+    let leaf_update = inputs.leaf_update.unwrap_or_default();
+    if updated_root.is_some() {
+        // Reading follows the bits of the key, so a proof of another key holding the same old leaf
+        // leaves the new leaf somewhere else.
+        let found = tree
+            .get(updated_root.as_ref(), &leaf_update.key)
+            .expect("couldn't look up the key to update");
+        assert_eq!(
+            found,
+            Some(leaf_update.new_leaf),
+            "the Merkle proof isn't of the key to update"
+        );
+        root = updated_root;
+        roots_digest = chain_root(roots_digest, root.as_ref().unwrap());
+    } else if inputs.leaf_update.is_some() {
+        let found = tree
+            .get(root.as_ref(), &leaf_update.key)
+            .expect("couldn't look up the key to update");
+        assert_eq!(
+            found,
+            Some(leaf_update.old_leaf),
+            "the old leaf isn't at the key to update"
+        );
+        root = tree
+            .insert(root.as_ref(), &leaf_update.key, &leaf_update.new_leaf)
+            .expect("couldn't update");
+        roots_digest = chain_root(roots_digest, root.as_ref().unwrap());
+    }
 
-    // Prepare a random pair of key and leaf.
-    // random_hash() gives a fixed length of random array,
-    // where Hash -> [u8; HASH_LEN], HASH_LEN = 32
-    // let key = random_hash();
-    // let leaf = random_hash();
+    // Insert the batch of entries the host asked for in one go, if any, as one state transition.
+    // Entries derived from a seed are pseudorandom, but the same on the host, so it can reproduce
+    // the inserts. Blobs of leaf data of any length are inserted with their hash as the leaf.
+    let entry_count = batch.len() as u32;
+    if !batch.is_empty() {
+        let (keys, leaves): (Vec<_>, Vec<_>) = batch.iter().copied().unzip();
+        root = tree
+            .inserts(root.as_ref(), &keys, &leaves)
+            .expect("couldn't insert the entries");
+        roots_digest = chain_root(roots_digest, root.as_ref().unwrap());
+    }
 
-    // let bu8 = b.try_into().unwrap();
-    let key: [u8; 32] = [1; 32];
-    let leaf: [u8; 32] = [b as u8; 32];
+    // Remove the keys the host asked for, each of which must be in the tree, so the proof covers
+    // their deletion. Removing the last one empties the tree, chained as a zero root.
+    let removal_count = removals.len() as u32;
+    for (key, _) in removals {
+        let found = tree
+            .get(root.as_ref(), key)
+            .expect("couldn't look up the key to remove");
+        assert_ne!(found, None, "the key to remove isn't in the tree");
+        root = tree.remove(root.as_ref(), key).expect("couldn't remove");
+        roots_digest = chain_root(roots_digest, &root.unwrap_or_default());
+    }
 
-    for _i in 0..offset {
-        // Insert the entry (key, leaf) into tree, yielding a new root of tree
+    // Insert the entry (key, leaf) into tree once, yielding a new root of tree, unless the offset
+    // is zero. Inserting it again for every unit of offset would redo the same hashing only to
+    // leave the same root, so it's inserted once whatever the offset, and committed as one insert.
+    let insert_count = entry.len() as u32;
+    if let Some((key, leaf)) = entry.first() {
         root = tree
-            .insert(root.as_ref(), &key, &leaf)
-            .expect("coulnd't insert");
+            .insert(root.as_ref(), key, leaf)
+            .expect("couldn't insert");
         assert_ne!(root, None);
+
+        // Chain every intermediate root, so verifiers can audit each state transition.
+        roots_digest = chain_root(roots_digest, root.as_ref().unwrap());
     }
 
-    // Get the leaf inserted just before. Note that the last root was used.
-    let found = tree.get(root.as_ref(), &key).unwrap();
-    assert_eq!(found, Some(leaf));
+    // Insert the entries of every other tree into it, starting from its own prior root. The trees
+    // share the witness nodes, which are addressed by their hash, but not their roots.
+    let namespace_roots: Vec<_> = inputs
+        .namespaces
+        .iter()
+        .enumerate()
+        .map(|(i, namespace)| {
+            let prior_root = witness.namespace_roots.get(i).copied().flatten();
+            let root = if namespace.entries.is_empty() {
+                prior_root
+            } else {
+                let (keys, leaves): (Vec<_>, Vec<_>) = namespace.entries.iter().copied().unzip();
+                tree.inserts(prior_root.as_ref(), &keys, &leaves)
+                    .expect("couldn't insert the namespace entries")
+            };
+            (namespace, prior_root, root)
+        })
+        .collect();
+    let namespace_count = namespace_roots.len() as u32;
+    let namespaces_digest = namespaces_digest(namespace_roots);
+    println!("cycle-tracker-end: monotree");
+
+    // With a zero offset nothing was inserted, so there's no entry to prove the tree includes: the
+    // inclusion proof is left empty and unverified.
+    let mut inclusion_proof = InclusionProofStruct::default();
+    let mut inclusion_verified = false;
+    if insert_count > 0 {
+        // Get the leaf inserted just before. Note that the last root was used.
+        let found = tree.get(root.as_ref(), &key).unwrap();
+        assert_eq!(found, Some(leaf));
+
+        // Generate a Merkle proof of the entry against the last root, so verifiers can check that
+        // the tree includes it without re-running the program.
+        let proof = tree
+            .get_merkle_proof(root.as_ref(), &key)
+            .expect("couldn't generate the Merkle proof")
+            .expect("the inserted key has a Merkle proof");
+        inclusion_proof = InclusionProofStruct::from_steps(&proof);
+
+        // Check the proof against the root inside the zkVM too, so verifiers that don't check it
+        // themselves can rely on the committed flag.
+        println!("cycle-tracker-start: inclusion-proof");
+        inclusion_verified = verify_proof(&hasher, root.as_ref(), &leaf, Some(&proof));
+        println!("cycle-tracker-end: inclusion-proof");
+    }
 
     let root = root.unwrap();
     println!("root: {}", hex::encode(root));
 
     // Encode the public values of the program.
-    let bytes = PublicValuesStruct::abi_encode(&PublicValuesStruct { n, a, b, root });
+    //
+    // The offset is committed too, so verifiers can tell which offset `a` and `b` include. Given
+    // a salt, only a hash of the offset is committed, so it stays private. The key and the leaf
+    // are committed so verifiers can tell which entry the root attests to, and after how many
+    // insertions.
+    let (offset, offset_hash) = committed_offset(offset, salt);
+    // The parameters of the sequence are committed too, so verifiers can tell which sequence `a`
+    // and `b` are elements of.
+    let (recurrence_a0, recurrence_a1, recurrence_p, recurrence_q) = sequence.params();
+    let public_values = PublicValuesStruct {
+        version: PUBLIC_VALUES_VERSION,
+        n,
+        offset,
+        offset_hash,
+        a,
+        b,
+        key: key.into(),
+        leaf: leaf.into(),
+        insert_count,
+        root,
+        nonce,
+        timestamp,
+        inputs_digest,
+        child_vkey,
+        overflowed,
+        roots_digest,
+        prior_root: prior_root.into(),
+        entry_count,
+        inclusion_verified,
+        absent_key: absent_key.into(),
+        updates_digest,
+        hasher: inputs.hasher.id(),
+        removal_count,
+        update_key: leaf_update.key.into(),
+        old_leaf: leaf_update.old_leaf.into(),
+        new_leaf: leaf_update.new_leaf.into(),
+        namespace_count,
+        namespaces_digest,
+        blob_count: inputs.blobs.len() as u32,
+        blobs_digest: blobs_digest(&inputs.blobs),
+        big: inputs.big,
+        modulus,
+        a_mod,
+        b_mod,
+        recurrence_a0,
+        recurrence_a1,
+        recurrence_p,
+        recurrence_q,
+        error_code: 0,
+        wide: inputs.wide,
+        a_hi,
+        b_hi,
+        pisano_modulus,
+        pisano_period,
+        signed: inputs.signed_index.is_some(),
+        signed_n: inputs.signed_index.unwrap_or(0),
+        a_signed,
+        b_signed,
+        table_stride: inputs.table_stride.unwrap_or(0),
+        table_len: table.len() as u32,
+        table_root: table_root(&table),
+        membership: inputs.membership.is_some(),
+        membership_x: inputs.membership.unwrap_or(0),
+        membership_found: membership_index.is_some(),
+        membership_index: membership_index.unwrap_or(0),
+        golden_ratio: inputs.golden_ratio,
+        ratio: ratio.0,
+        previous_digest,
+    };
+    commit(encoding, &public_values, &inclusion_proof, big_results);
+}
+
+/// The offset to commit and the hash to commit of it, which hides it given a salt.
+fn committed_offset(offset: u32, salt: Option<[u8; 32]>) -> (u32, B256) {
+    match salt {
+        Some(salt) => (0, offset_commitment(offset, &salt)),
+        None => (offset, Default::default()),
+    }
+}
+
+/// Commit the public values, the inclusion proof and the exact fibonacci numbers, if any.
+fn commit(
+    encoding: PublicValuesEncoding,
+    public_values: &PublicValuesStruct,
+    inclusion_proof: &InclusionProofStruct,
+    big_results: Option<Vec<u8>>,
+) {
+    // ABI-encode the public values for Solidity verifiers, or Borsh-encode them for Solana or NEAR
+    // programs, as the host asked for. The inclusion proof follows them, ABI-encoded either way,
+    // and then the exact fibonacci numbers, if any.
+    let mut bytes = match encoding {
+        PublicValuesEncoding::Abi => PublicValuesWriter::new()
+            .push::<PublicValuesStruct>(public_values)
+            .push_last::<InclusionProofStruct>(inclusion_proof),
+        PublicValuesEncoding::Borsh => [
+            public_values.borsh_encode(),
+            InclusionProofStruct::abi_encode(inclusion_proof),
+        ]
+        .concat(),
+        PublicValuesEncoding::Ssz => [
+            public_values.ssz_encode(),
+            InclusionProofStruct::abi_encode(inclusion_proof),
+        ]
+        .concat(),
+    };
+    bytes.extend(big_results.unwrap_or_default());
 
     // Commit to the public values of the program. The final proof will have a commitment to all the
     // bytes that were committed to.
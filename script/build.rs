@@ -1,5 +1,19 @@
-use sp1_helper::build_program_with_args;
+use sp1_helper::{build_program_with_args, BuildArgs};
 
 fn main() {
-    build_program_with_args("../program", Default::default())
+    let mut args = BuildArgs::default();
+    // The program has to hash like the host's tree, so it's built with the same hashers.
+    if std::env::var_os("CARGO_FEATURE_POSEIDON").is_some() {
+        args.features.push("poseidon".to_string());
+    }
+    build_program_with_args("../program", args);
+
+    // The aggregator is built next to it, under its own name, see `AGGREGATOR_ELF`.
+    build_program_with_args(
+        "../aggregator",
+        BuildArgs {
+            elf_name: "aggregator-elf".to_string(),
+            ..BuildArgs::default()
+        },
+    )
 }
@@ -3,157 +3,2370 @@
 //!
 //! You can run this script using the following command:
 //! ```shell
-//! RUST_LOG=info cargo run --release -- --execute
+//! RUST_LOG=info cargo run --release -- execute
 //! ```
 //! or
 //! ```shell
-//! RUST_LOG=info cargo run --release -- --prove
+//! RUST_LOG=info cargo run --release -- prove
+//! ```
+//! or
+//! ```shell
+//! RUST_LOG=info cargo run --release -- verify --proof-path proof.json
+//! ```
+//! or, to generate an EVM-compatible proof and a fixture for the Solidity tests:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- evm --proof-system groth16
 //! ```
 
-use std::{fs::File, io::Write};
+use std::path::{Path, PathBuf};
 
 use alloy_sol_types::SolType;
-use clap::Parser;
-use fibonacci_lib::PublicValuesStruct;
-use hex::ToHex;
-use sp1_sdk::{ProverClient, SP1Stdin};
-use std::time::Instant;
-
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-pub const FIBONACCI_ELF: &[u8] = include_bytes!("../../../elf/riscv32im-succinct-zkvm-elf");
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand};
+use fibonacci_lib::{
+    decode_public_values, verify_aggregation, verify_public_values, AggregatedPublicValuesStruct,
+    FibonacciInputs, FibonacciMethod, LeafUpdate, Namespace, PreviousProof, PublicValuesEncoding,
+    PublicValuesError, PublicValuesStruct, Sequence, TreeHasher, TreeWitness,
+    VersionedPublicValues,
+};
+use fibonacci_script::{
+    cache::{cache_key, ProofCache},
+    config::Config,
+    error::{Error, UndecodablePublicValues},
+    estimate::CostModel,
+    fixture::{default_fixture_path, SP1FibonacciProofFixture},
+    inputs::{current_timestamp, load_batch_file, load_inputs_file, to_stdin},
+    logger::{setup_logger, Progress, Verbosity},
+    manifest::{Manifest, ManifestEntry},
+    proof::{
+        batch_proof_path, check_output_path, default_vk_path, is_stdio, load_envelope, load_proof,
+        load_vk_from_json, proof_vkey_hash, public_values_paths, save_proof, save_public_values,
+        save_vk_to_json, ProofEnvelope, ProofEnvironment, ProofFormat, ProofMetadata, ProofSystem,
+    },
+    prover::{PendingRequest, Prover, ProverMode},
+    report::{
+        AggregateReport, AppendReport, BenchReport, BenchRow, ChainCheckReport, ChainedProof,
+        DecodeReport, DiffReport, DiffedProof, EstimateReport, EvmReport, ExecuteReport,
+        ExportedInclusionProof, FieldDiff, GenSolReport, InclusionProofReport, ProofSummary,
+        ProveReport, PublicValues, Report, SchemaReport, TreeExportReport, TreeGetReport,
+        TreeLsReport, VerifyInclusionReport, VerifyReport, VkeyReport, WrapReport,
+    },
+    schema::PublicValuesSchema,
+    solidity::{default_solidity_path, public_values_solidity},
+    store::{input_hash, ProofStore, StoreEntry, StoreIndex},
+    tree::{HostTree, SnapshotFormat},
+    AGGREGATOR_ELF, FIBONACCI_ELF,
+};
+use sp1_sdk::{
+    HashableKey, SP1Proof, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 /// The arguments for the command.
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
-struct Args {
+struct Cli {
+    /// A `prover.toml` file to read default option values from.
+    #[clap(long, env = "FIBONACCI_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// The prover to generate proofs with. [default: local]
+    #[clap(long, value_enum, env = "SP1_PROVER", global = true)]
+    prover: Option<ProverMode>,
+
+    /// The whitelisted private key used to sign prover network requests.
+    #[clap(long, env = "SP1_PRIVATE_KEY", hide_env_values = true, global = true)]
+    private_key: Option<String>,
+
+    /// Print the results as a single JSON document on stdout.
+    #[clap(long, global = true)]
+    json: bool,
+
+    /// Don't show a progress bar while proving, e.g. to keep CI logs clean.
+    #[clap(long, global = true)]
+    no_progress: bool,
+
+    /// Log more of what the SP1 SDK is doing: `-v` for info logs, `-vv` for debug logs.
+    #[clap(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Print only the results (or errors), without logs, progress or status messages.
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// The available subcommands.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Execute the program without generating a proof.
+    Execute(ExecuteArgs),
+    /// Generate a proof of the program and save it to disk.
+    Prove(ProveArgs),
+    /// Verify a proof previously saved to disk.
+    Verify(VerifyArgs),
+    /// Print the program's verification key hash, for registering it with on-chain verifiers.
+    Vkey(VkeyArgs),
+    /// Execute the program over a range of inputs and report the cycles spent in each phase.
+    Bench(BenchArgs),
+    /// Generate a fixture for testing the verification of a proof inside Solidity.
+    Evm(EvmArgs),
+    /// Execute the program and estimate how long and how much proving it would take.
+    Estimate(EstimateArgs),
+    /// Decode the public values of a proof, or of a hex string, into their fields.
+    Decode(DecodeArgs),
+    /// Compare the public values and verifying keys of two proofs field by field.
+    Diff(DiffArgs),
+    /// Generate the Solidity definition of the public values the contracts decode.
+    GenSol(GenSolArgs),
+    /// Describe the layout of the public values the program commits, for decoding them elsewhere.
+    Schema(SchemaArgs),
+    /// Export the Merkle proof of the inserted entry a proof commits, for checking it elsewhere.
+    InclusionProof(InclusionProofArgs),
+    /// Check an inclusion proof exported with `inclusion-proof` against a root.
+    VerifyInclusion(VerifyInclusionArgs),
+    /// Check that a sequence of proofs each pick up the tree where the previous one left it.
+    ChainCheck(ChainCheckArgs),
+    /// Prove the next step of a chain of proofs over a tree and record it in the chain's manifest.
+    Append(AppendArgs),
+    /// Aggregate compressed proofs into one proof of the aggregator program, which verifies them.
+    Aggregate(AggregateArgs),
+    /// Wrap a compressed proof for the EVM, as the last stage of proving with PLONK or Groth16.
+    Wrap(WrapArgs),
+    /// Inspect a tree persisted on the host with `--tree-db`.
+    Tree(TreeArgs),
+}
+
+/// The subcommands of the `tree` subcommand.
+#[derive(Subcommand, Debug)]
+enum TreeCommand {
+    /// Export the root and all entries of the tree, to inspect or back up its state.
+    Export(TreeExportArgs),
+    /// List the entries of the tree under its current root or an older one.
+    Ls(TreeLsArgs),
+    /// Look up the leaf at a key of the tree under its current root or an older one.
+    Get(TreeGetArgs),
+}
+
+/// The inputs written to the program's stdin, as given on the command line.
+#[derive(Args, Debug)]
+struct InputArgs {
+    /// Which fibonacci number to compute. [default: 20]
+    #[clap(short, env = "FIBONACCI_N")]
+    n: Option<u32>,
+
+    /// The offset added to the computed fibonacci numbers. [default: 0]
+    #[clap(long, env = "FIBONACCI_OFFSET")]
+    offset: Option<u32>,
+
+    /// A JSON file with the inputs, e.g. `{"n": 20, "offset": 0}`. Inputs also given on the
+    /// command line override the ones in the file.
+    #[clap(long)]
+    input_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    overrides: InputOverrides,
+}
+
+/// The inputs written to the program's stdin besides n and the offset, as given on the command
+/// line, in place of the ones of the input file. `prove` applies them to every input it proves.
+#[derive(Args, Debug)]
+#[group(id = "input_overrides", multiple = true)]
+struct InputOverrides {
+    /// A hex-encoded 32-byte salt to hide the offset with. The program then commits
+    /// `keccak256(offset || salt)` instead of the offset.
+    #[clap(long, value_parser = parse_bytes32)]
+    salt: Option<[u8; 32]>,
+
+    /// A number to commit along with the results, e.g. to tell replayed proofs apart. [default: 0]
+    #[clap(long)]
+    nonce: Option<u64>,
+
+    /// The timestamp to commit along with the results, in seconds since the Unix epoch.
+    /// [default: the input file's, or now]
+    #[clap(long)]
+    timestamp: Option<u64>,
+
+    /// How the program encodes the public values, `abi` for Solidity, `borsh` for Solana and NEAR
+    /// programs or `ssz` for consensus tooling. [default: abi]
+    #[clap(long)]
+    encoding: Option<PublicValuesEncoding>,
+
+    /// The hex-encoded 32-byte key to insert into the tree. [default: 0x0101...01]
+    #[clap(long, value_parser = parse_bytes32)]
+    key: Option<[u8; 32]>,
+
+    /// The hex-encoded 32-byte leaf to insert into the tree. [default: the low byte of b, repeated]
+    #[clap(long, value_parser = parse_bytes32)]
+    leaf: Option<[u8; 32]>,
+
+    /// An entry to insert into the tree in one batch with the others, as hex-encoded 32-byte
+    /// `KEY:LEAF`. Can be given several times.
+    #[clap(long = "entry", value_name = "KEY:LEAF", value_parser = parse_entry)]
+    entries: Vec<([u8; 32], [u8; 32])>,
+
+    /// The hex-encoded 32-byte key to prove absent from the tree before inserting into it.
+    #[clap(long, value_parser = parse_bytes32)]
+    absent_key: Option<[u8; 32]>,
+
+    /// The hasher to build the tree with, `blake3`, `blake2b`, `sha2` or, with the `poseidon`
+    /// feature, `poseidon`. [default: blake3]
+    #[clap(long)]
+    hasher: Option<TreeHasher>,
+
+    /// A hex-encoded 32-byte key to remove from the tree, which must be in it. Can be given
+    /// several times.
+    #[clap(long = "remove", value_name = "KEY", value_parser = parse_bytes32)]
+    removals: Vec<[u8; 32]>,
+
+    /// Change the leaf at a key in the tree, as hex-encoded 32-byte `KEY:OLD_LEAF:NEW_LEAF`.
+    #[clap(long = "update", value_name = "KEY:OLD_LEAF:NEW_LEAF", value_parser = parse_leaf_update)]
+    leaf_update: Option<LeafUpdate>,
+
+    /// Key the tree by the hash of n, with the 32-byte big-endian b as the leaf, instead of the
+    /// default key and leaf.
+    #[clap(long)]
+    index_by_n: bool,
+
+    /// An entry to insert into the tree of a namespace, independent of the main tree, as
+    /// `NAME:KEY:LEAF` with a hex-encoded 32-byte key and leaf. Can be given several times.
+    #[clap(
+        long = "namespace-entry",
+        value_name = "NAME:KEY:LEAF",
+        value_parser = parse_namespace_entry
+    )]
+    namespace_entries: Vec<(String, ([u8; 32], [u8; 32]))>,
+
+    /// A seed to derive `--seeded-entries` pseudorandom entries from, inserted in one batch with
+    /// the others. The same seed always derives the same entries.
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// How many entries to derive from `--seed`. [default: 0]
+    #[clap(long, requires = "seed")]
+    seeded_entries: Option<u32>,
+
+    /// An entry with leaf data of any length, inserted in one batch with the others with the
+    /// keccak256 hash of the data as the leaf, as hex-encoded `KEY:DATA` with a 32-byte key. Can
+    /// be given several times.
+    #[clap(long = "blob", value_name = "KEY:DATA", value_parser = parse_blob)]
+    blobs: Vec<([u8; 32], Vec<u8>)>,
+
+    /// How to compute the fibonacci numbers, `iterative` or `fast-doubling`, which takes about
+    /// log2(n) steps rather than n. [default: iterative]
     #[clap(long)]
-    execute: bool,
+    method: Option<FibonacciMethod>,
 
+    /// Also commit the exact fibonacci numbers, which don't wrap around once n is past 92.
     #[clap(long)]
-    generate: bool,
+    big: bool,
+
+    /// Also commit the fibonacci numbers modulo this number, e.g. a prime field's modulus.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    modulus: Option<u64>,
+
+    /// The sequence to compute the n'th element of, `fibonacci`, `lucas` or, for the linear
+    /// recurrence `x(k+2) = P * x(k+1) - Q * x(k)` from `x(0) = A0` and `x(1) = A1`,
+    /// `recurrence:A0:A1:P:Q`. [default: fibonacci]
+    #[clap(long, allow_hyphen_values = true)]
+    sequence: Option<Sequence>,
+
+    /// Have the program abort with an error code if the fibonacci numbers overflow 64 bits,
+    /// rather than commit them wrapped around.
+    #[clap(long)]
+    checked: bool,
+
+    /// Also commit the high halves of the fibonacci numbers computed with 128 bits, which don't
+    /// wrap around until n is past 185.
+    #[clap(long)]
+    wide: bool,
+
+    /// Also commit the Pisano period of this number, i.e. the period of the fibonacci numbers
+    /// modulo it, which takes up to 6 times as many steps as the number to find.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pisano: Option<u64>,
+
+    /// Also commit the fibonacci numbers at this signed index, negative ones giving the
+    /// negafibonacci numbers `F(-k) = (-1)^(k+1) F(k)`.
+    #[clap(long, allow_hyphen_values = true)]
+    signed_index: Option<i64>,
+
+    /// Also commit every k'th fibonacci number up to the n'th, for this k, as the root of a Merkle
+    /// tree over them.
+    #[clap(long, value_name = "K", value_parser = clap::value_parser!(u32).range(1..))]
+    table_stride: Option<u32>,
+
+    /// Also commit whether this number is a fibonacci number, and if so its index.
+    #[clap(long = "is-fibonacci", value_name = "X")]
+    membership: Option<u64>,
+
+    /// Also commit F(n+1) / F(n), which tends to the golden ratio, as a Q64.64 fixed-point
+    /// number.
+    #[clap(long)]
+    golden_ratio: bool,
+}
+
+impl InputArgs {
+    /// Resolve the inputs, falling back to the input file, the config file and then to the
+    /// defaults.
+    fn resolve(&self, config: &Config) -> Result<FibonacciInputs, Error> {
+        let base = match &self.input_file {
+            Some(path) => load_inputs_file(path).map_err(Error::io(format!(
+                "failed to read inputs {}",
+                path.display()
+            )))?,
+            None => FibonacciInputs {
+                n: config.n.unwrap_or(20),
+                offset: config.offset.unwrap_or(0),
+                timestamp: current_timestamp(),
+                ..FibonacciInputs::default()
+            },
+        };
+        Ok(self.overrides.apply(FibonacciInputs {
+            n: self.n.unwrap_or(base.n),
+            offset: self.offset.unwrap_or(base.offset),
+            ..base
+        }))
+    }
+}
+
+impl InputOverrides {
+    /// The inputs with the ones given on the command line in place of theirs.
+    fn apply(&self, inputs: FibonacciInputs) -> FibonacciInputs {
+        FibonacciInputs {
+            n: inputs.n,
+            offset: inputs.offset,
+            salt: self.salt.or(inputs.salt),
+            nonce: self.nonce.unwrap_or(inputs.nonce),
+            timestamp: self.timestamp.unwrap_or(inputs.timestamp),
+            encoding: self.encoding.unwrap_or(inputs.encoding),
+            key: self.key.or(inputs.key),
+            leaf: self.leaf.or(inputs.leaf),
+            entries: match self.entries.as_slice() {
+                [] => inputs.entries,
+                entries => entries.to_vec(),
+            },
+            absent_key: self.absent_key.or(inputs.absent_key),
+            hasher: self.hasher.unwrap_or(inputs.hasher),
+            removals: match self.removals.as_slice() {
+                [] => inputs.removals,
+                removals => removals.to_vec(),
+            },
+            leaf_update: self.leaf_update.or(inputs.leaf_update),
+            index_by_n: self.index_by_n || inputs.index_by_n,
+            namespaces: match self.namespace_entries.as_slice() {
+                [] => inputs.namespaces,
+                entries => group_namespaces(entries),
+            },
+            seed: self.seed.or(inputs.seed),
+            seeded_entries: self.seeded_entries.unwrap_or(inputs.seeded_entries),
+            blobs: match self.blobs.as_slice() {
+                [] => inputs.blobs,
+                blobs => blobs.to_vec(),
+            },
+            method: self.method.unwrap_or(inputs.method),
+            big: self.big || inputs.big,
+            modulus: self.modulus.or(inputs.modulus),
+            sequence: self.sequence.unwrap_or(inputs.sequence),
+            checked: self.checked || inputs.checked,
+            wide: self.wide || inputs.wide,
+            pisano: self.pisano.or(inputs.pisano),
+            signed_index: self.signed_index.or(inputs.signed_index),
+            table_stride: self.table_stride.or(inputs.table_stride),
+            membership: self.membership.or(inputs.membership),
+            golden_ratio: self.golden_ratio || inputs.golden_ratio,
+            previous: inputs.previous,
+        }
+    }
+}
+
+/// Parse a hex-encoded 32-byte value like a salt or a tree key, with or without a `0x` prefix.
+fn parse_bytes32(value: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("expected 32 bytes, got {}", bytes.len()))
+}
+
+/// Parse a tree entry given as hex-encoded 32-byte `KEY:LEAF`.
+fn parse_entry(entry: &str) -> Result<([u8; 32], [u8; 32]), String> {
+    let (key, leaf) = entry
+        .split_once(':')
+        .ok_or_else(|| format!("expected KEY:LEAF, got {:?}", entry))?;
+    Ok((parse_bytes32(key)?, parse_bytes32(leaf)?))
+}
+
+/// Parse an entry with leaf data of any length given as hex-encoded `KEY:DATA`, with a 32-byte
+/// key.
+fn parse_blob(blob: &str) -> Result<([u8; 32], Vec<u8>), String> {
+    let (key, data) = blob
+        .split_once(':')
+        .ok_or_else(|| format!("expected KEY:DATA, got {:?}", blob))?;
+    let data = hex::decode(data.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+    Ok((parse_bytes32(key)?, data))
+}
+
+/// Parse a leaf update given as hex-encoded 32-byte `KEY:OLD_LEAF:NEW_LEAF`.
+fn parse_leaf_update(update: &str) -> Result<LeafUpdate, String> {
+    match update.split(':').collect::<Vec<_>>()[..] {
+        [key, old_leaf, new_leaf] => Ok(LeafUpdate {
+            key: parse_bytes32(key)?,
+            old_leaf: parse_bytes32(old_leaf)?,
+            new_leaf: parse_bytes32(new_leaf)?,
+        }),
+        _ => Err(format!("expected KEY:OLD_LEAF:NEW_LEAF, got {:?}", update)),
+    }
+}
+
+/// Parse an entry of the tree of a namespace given as `NAME:KEY:LEAF`, with a hex-encoded 32-byte
+/// key and leaf.
+fn parse_namespace_entry(entry: &str) -> Result<(String, ([u8; 32], [u8; 32])), String> {
+    let (name, entry) = entry
+        .split_once(':')
+        .ok_or_else(|| format!("expected NAME:KEY:LEAF, got {:?}", entry))?;
+    Ok((name.to_string(), parse_entry(entry)?))
+}
+
+/// Group the entries of namespaces given on the command line by namespace, in the order each
+/// namespace is first given in.
+fn group_namespaces(entries: &[(String, ([u8; 32], [u8; 32]))]) -> Vec<Namespace> {
+    let mut namespaces: Vec<Namespace> = Vec::new();
+    for (name, entry) in entries {
+        match namespaces
+            .iter_mut()
+            .find(|namespace| &namespace.name == name)
+        {
+            Some(namespace) => namespace.entries.push(*entry),
+            None => namespaces.push(Namespace {
+                name: name.clone(),
+                entries: vec![*entry],
+            }),
+        }
+    }
+    namespaces
+}
+
+/// The arguments for the `execute` subcommand.
+#[derive(Args, Debug)]
+struct ExecuteArgs {
+    #[clap(flatten)]
+    inputs: InputArgs,
+
+    /// A tree persisted on the host for the program to insert into, instead of an empty one. The
+    /// tree is left as is.
+    #[clap(long)]
+    tree_db: Option<PathBuf>,
+
+    /// Send the program the Merkle proof of the key to `--update` rather than the nodes on its
+    /// path, which it derives from the proof instead.
+    #[clap(long, requires = "tree_db")]
+    path_witness: bool,
+}
+
+/// The arguments for the `prove` subcommand.
+#[derive(Args, Debug)]
+struct ProveArgs {
+    /// Which fibonacci numbers to prove, separated by commas. [default: 20]
+    #[clap(short, value_delimiter = ',', env = "FIBONACCI_N")]
+    n: Vec<u32>,
+
+    /// The offsets to prove with, separated by commas. Every offset is proven for every value of
+    /// n. [default: 0]
+    #[clap(long, value_delimiter = ',', env = "FIBONACCI_OFFSET")]
+    offset: Vec<u32>,
+
+    /// A JSON file with the inputs to prove, e.g. `{"n": 20, "offset": 0}`, instead of `-n` and
+    /// `--offset`.
+    #[clap(long, conflicts_with_all = ["n", "offset", "batch_file"])]
+    input_file: Option<PathBuf>,
+
+    /// A JSON file with a list of `{"n": ..., "offset": ...}` inputs to prove, instead of `-n` and
+    /// `--offset`.
+    #[clap(long, conflicts_with_all = ["n", "offset"])]
+    batch_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    overrides: InputOverrides,
+
+    /// The proof system to generate the proof with. [default: groth16]
+    ///
+    /// To prove in two stages, prove with `--stage compressed` first, which is cheap enough to
+    /// keep up with a stream of inputs, and wrap the proofs to publish with `wrap` later.
+    #[clap(long, value_enum, visible_alias = "stage")]
+    proof_system: Option<ProofSystem>,
+
+    /// The path to save the proof to. Parent directories are created as needed.
+    ///
+    /// Defaults to `proof.json` for PLONK and Groth16 proofs, and to `proof.core.json` or
+    /// `proof.compressed.json` for core and compressed proofs (with a `.bin` extension instead
+    /// for `--format bincode`). When proving several inputs, each
+    /// proof is saved next to it with the inputs in its name, e.g. `proof-n20-offset0.json`.
+    ///
+    /// `-` writes the proof to stdout instead, and the report to stderr.
+    #[clap(long, env = "FIBONACCI_PROOF_PATH")]
+    proof_path: Option<PathBuf>,
+
+    /// Save the proofs in this directory by content instead, each to
+    /// `<vkey_hash>/<input_hash>.proof` (`.core.proof` and `.compressed.proof` for core and
+    /// compressed proofs), and record them in its `index.json`, so proofs of different inputs or
+    /// builds of the program never overwrite each other.
+    #[clap(long, conflicts_with_all = ["proof_path", "resume"])]
+    proof_dir: Option<PathBuf>,
+
+    /// The format to save the proof in. Either format is detected automatically when loading.
+    #[clap(long, value_enum, default_value = "json")]
+    format: ProofFormat,
 
+    /// Compress the proof file with zstd, which shrinks JSON proofs several times over. Compressed
+    /// files are detected and decompressed automatically when loading.
+    #[clap(long)]
+    compress: bool,
+
+    /// Overwrite the proof file if it already exists.
+    #[clap(long)]
+    force: bool,
+
+    /// Also save the verifying key, so the proof can be verified without the ELF.
+    #[clap(long)]
+    save_vk: bool,
+
+    /// Also save the public values next to each proof, raw and decoded, e.g. to
+    /// `proof.public_values.bin` and `proof.public_values.json`, for tools that only need what the
+    /// program committed.
+    #[clap(long)]
+    save_public_values: bool,
+
+    /// The path to save the verifying key to with `--save-vk`.
+    ///
+    /// Defaults to the proof path with a `.vk.json` extension, e.g. `proof.vk.json`, or to
+    /// `<vkey_hash>/vk.json` in the `--proof-dir`.
+    #[clap(long, requires = "save_vk")]
+    vk_path: Option<PathBuf>,
+
+    /// How many seconds to wait for a prover network request to be fulfilled before giving up.
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// The directory proofs are cached in, keyed by the ELF, the program's stdin and the proof
+    /// system, so proving the same inputs again reuses the earlier proof.
+    #[clap(long, env = "FIBONACCI_PROOF_CACHE", default_value = ".proof-cache")]
+    cache_dir: PathBuf,
+
+    /// Generate the proofs even if they're cached, and don't cache them.
+    #[clap(long)]
+    no_cache: bool,
+
+    /// Generate the proofs reproducibly, for comparing them with proofs of the same inputs by
+    /// other operators: commit the epoch as the timestamp of every input unless given
+    /// `--timestamp`, and record the environment the proofs are generated in in the proof files.
+    /// Cached deterministic proofs are reused like others, pass `--no-cache` to regenerate them.
+    #[clap(long, conflicts_with = "resume")]
+    deterministic: bool,
+
+    /// How many proofs to generate concurrently when proving several inputs.
+    ///
+    /// Each local proof uses a lot of memory, so this is mostly useful with the prover network.
+    #[clap(long, default_value = "1")]
+    jobs: usize,
+
+    /// Download the proof of an earlier prover network request instead of requesting a new one.
+    ///
+    /// The inputs of the request are read back from the pending request file saved next to the
+    /// proof, e.g. `proof.request.json`.
+    #[clap(long, value_name = "REQUEST_ID")]
+    resume: Option<String>,
+
+    /// A tree persisted on the host for the program to insert into, instead of an empty one. It's
+    /// created if it doesn't exist, and its root is updated once the proof is saved.
+    ///
+    /// The inserts of each proof depend on the tree left by the previous one, so only one input
+    /// can be proven at a time.
+    #[clap(long, conflicts_with = "resume")]
+    tree_db: Option<PathBuf>,
+
+    /// Send the program the Merkle proof of the key to `--update` rather than the nodes on its
+    /// path, which it derives from the proof instead.
+    #[clap(long, requires = "tree_db")]
+    path_witness: bool,
+
+    /// Embed the verifying key in the proof file, so `verify` needs only the file, and neither the
+    /// ELF nor a setup.
+    #[clap(long)]
+    embed_vk: bool,
+
+    /// A compressed proof of the previous run to chain this one onto. The program verifies it and
+    /// checks that it left the tree at the root this run starts from, so verifying the new proof
+    /// stands in for verifying every proof of the chain.
+    ///
+    /// Only one input can be proven at a time, as the next proof of the chain needs this one.
+    #[clap(long, value_name = "PROOF", conflicts_with = "resume")]
+    chain_from: Option<PathBuf>,
+}
+
+impl ProveArgs {
+    /// Resolve the inputs to prove, falling back to the config file and then to the defaults.
+    fn resolve_inputs(&self, config: &Config) -> std::io::Result<Vec<FibonacciInputs>> {
+        let mut batch = if let Some(input_file) = &self.input_file {
+            vec![load_inputs_file(input_file)?]
+        } else if let Some(batch_file) = &self.batch_file {
+            load_batch_file(batch_file)?
+        } else {
+            self.resolve_cli_inputs(config)
+        };
+        // The time the proof is generated at would make every proof different, so deterministic
+        // proofs commit the epoch instead, unless given `--timestamp`.
+        if self.deterministic {
+            for inputs in &mut batch {
+                inputs.timestamp = 0;
+            }
+        }
+        Ok(batch
+            .into_iter()
+            .map(|inputs| self.overrides.apply(inputs))
+            .collect())
+    }
+
+    /// Whether the inputs commit the time they're proven at, as neither `--timestamp`,
+    /// `--deterministic` nor an input file gives them a timestamp.
+    fn default_timestamp(&self) -> bool {
+        self.input_file.is_none()
+            && self.batch_file.is_none()
+            && self.overrides.timestamp.is_none()
+            && !self.deterministic
+    }
+
+    /// Every combination of the values of n and the offsets given on the command line.
+    fn resolve_cli_inputs(&self, config: &Config) -> Vec<FibonacciInputs> {
+        let ns = match self.n.as_slice() {
+            [] => vec![config.n.unwrap_or(20)],
+            ns => ns.to_vec(),
+        };
+        let offsets = match self.offset.as_slice() {
+            [] => vec![config.offset.unwrap_or(0)],
+            offsets => offsets.to_vec(),
+        };
+        let timestamp = current_timestamp();
+        ns.iter()
+            .flat_map(|&n| {
+                offsets.iter().map(move |&offset| FibonacciInputs {
+                    n,
+                    offset,
+                    timestamp,
+                    ..FibonacciInputs::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// The arguments for the `verify` subcommand.
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// The path of the proof to verify, e.g. one produced on another machine or by an older run,
+    /// or `-` to read it from stdin. [default: proof.json]
+    #[clap(long, visible_alias = "proof", env = "FIBONACCI_PROOF_PATH")]
+    proof_path: Option<PathBuf>,
+
+    /// A verifying key saved with `prove --save-vk` to verify against.
+    ///
+    /// Without it, the verifying key embedded in the proof file with `prove --embed-vk` is used,
+    /// and otherwise the one derived from the ELF embedded in this binary.
+    #[clap(long, visible_alias = "vk")]
+    vk_path: Option<PathBuf>,
+
+    /// Never verify against a verifying key embedded in the proof file, only against `--vk-path`
+    /// or the one derived from the ELF, for when the file isn't trusted to say which program the
+    /// proof is of.
+    #[clap(long)]
+    external_vk: bool,
+
+    /// Also check that the proof was generated with this offset.
+    #[clap(long)]
+    offset: Option<u32>,
+
+    /// The hex-encoded salt the offset was hidden with, to check `--offset` against the committed
+    /// hash.
+    #[clap(long, value_parser = parse_bytes32, requires = "offset")]
+    salt: Option<[u8; 32]>,
+}
+
+/// The arguments for the `vkey` subcommand.
+#[derive(Args, Debug)]
+struct VkeyArgs {
+    /// Also write the bytes32 verification key hash to this file.
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Overwrite the output file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+/// The arguments for the `bench` subcommand.
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// The values of n to execute the program with, separated by commas.
+    #[clap(short, value_delimiter = ',', default_value = "10,20,40")]
+    n: Vec<u32>,
+
+    /// The offsets to execute the program with, separated by commas. Every offset is run for
+    /// every value of n.
+    #[clap(long, value_delimiter = ',', default_value = "0,1,10")]
+    offset: Vec<u32>,
+
+    /// Print the results as CSV instead of a table.
+    #[clap(long)]
+    csv: bool,
+}
+
+/// The arguments for the `evm` subcommand.
+#[derive(Args, Debug)]
+struct EvmArgs {
+    #[clap(flatten)]
+    inputs: InputArgs,
+
+    /// The proof system to generate the proof with, either plonk or groth16.
+    #[clap(long, value_enum, default_value = "groth16")]
+    proof_system: ProofSystem,
+
+    /// Create the fixture from a proof saved with `prove` instead of generating a new one.
+    #[clap(
+        long,
+        conflicts_with_all = ["n", "offset", "input_file", "input_overrides", "proof_system"]
+    )]
+    proof_path: Option<PathBuf>,
+
+    /// The path to save the fixture to.
+    ///
+    /// Defaults to the fixture the Foundry tests load, e.g.
+    /// `contracts/src/fixtures/groth16-fixture.json`.
+    #[clap(long)]
+    fixture_path: Option<PathBuf>,
+}
+
+/// The arguments for the `estimate` subcommand.
+#[derive(Args, Debug)]
+struct EstimateArgs {
+    #[clap(flatten)]
+    inputs: InputArgs,
+
+    /// The proof system to estimate proving with.
+    #[clap(long, value_enum, default_value = "groth16")]
+    proof_system: ProofSystem,
+
+    /// How many cycles per second this machine proves in the core proving phase.
+    #[clap(long, default_value_t = CostModel::default().cycles_per_sec)]
+    cycles_per_sec: f64,
+
+    /// The approximate price of proving a billion cycles on the prover network, in USD.
+    #[clap(long, default_value_t = CostModel::default().network_usd_per_billion_cycles)]
+    usd_per_billion_cycles: f64,
+}
+
+/// The arguments for the `decode` subcommand.
+#[derive(Args, Debug)]
+#[clap(group(ArgGroup::new("source").required(true).args(["proof_path", "public_values"])))]
+struct DecodeArgs {
+    /// A proof saved with `prove` to decode the public values of.
+    #[clap(long, visible_alias = "proof")]
+    proof_path: Option<PathBuf>,
+
+    /// The raw public values to decode, hex-encoded with or without a `0x` prefix.
+    #[clap(long)]
+    public_values: Option<String>,
+}
+
+/// The arguments for the `diff` subcommand.
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// The first proof to compare, e.g. a stored one.
+    a: PathBuf,
+
+    /// The second proof to compare, e.g. a re-generated one.
+    b: PathBuf,
+}
+
+/// The arguments for the `gen-sol` subcommand.
+#[derive(Args, Debug)]
+struct GenSolArgs {
+    /// Where to write the Solidity file. Defaults to `contracts/src/PublicValues.sol`.
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Only check that the file is up to date with the Rust definition, without writing it.
+    #[clap(long)]
+    check: bool,
+}
+
+/// The arguments for the `inclusion-proof` subcommand.
+#[derive(Args, Debug)]
+struct InclusionProofArgs {
+    /// The proof saved with `prove` to export the inclusion proof of.
+    #[clap(long, visible_alias = "proof")]
+    proof_path: PathBuf,
+
+    /// Also write the inclusion proof as JSON to this file.
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Overwrite the output file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+/// The arguments for the `verify-inclusion` subcommand.
+#[derive(Args, Debug)]
+struct VerifyInclusionArgs {
+    /// The inclusion proof written with `inclusion-proof --output`.
+    input: PathBuf,
+
+    /// Check the proof against this hex-encoded root, e.g. one read from a contract, instead of
+    /// the root it was exported with.
+    #[clap(long, value_parser = parse_bytes32)]
+    root: Option<[u8; 32]>,
+}
+
+/// The arguments for the `chain-check` subcommand.
+#[derive(Args, Debug)]
+struct ChainCheckArgs {
+    /// The proofs saved with `prove`, in the order they were generated.
+    #[clap(required_unless_present = "manifest", conflicts_with = "manifest")]
+    proof_paths: Vec<PathBuf>,
+
+    /// Check the proofs recorded in a manifest written by `append` instead.
+    #[clap(long)]
+    manifest: Option<PathBuf>,
+
+    /// Also verify every proof, rather than only checking that their roots link up.
     #[clap(long)]
     verify: bool,
 
-    #[clap(short, default_value = "20")]
-    n: u32,
+    /// A verifying key saved with `prove --save-vk` to verify against. [default: the one derived
+    /// from the embedded ELF]
+    #[clap(long, visible_alias = "vk", requires = "verify")]
+    vk_path: Option<PathBuf>,
+}
+
+/// The arguments for the `append` subcommand.
+#[derive(Args, Debug)]
+struct AppendArgs {
+    /// The manifest recording the chain, created if it doesn't exist.
+    #[clap(long, default_value = "chain.json")]
+    manifest: PathBuf,
 
-    #[clap(long, default_value = "0")]
-    offset: u32,
+    /// The proof to pick up the tree from, e.g. to start the manifest from an earlier proof.
+    /// [default: the last proof in the manifest]
+    #[clap(long)]
+    previous: Option<PathBuf>,
+
+    /// The proof is generated as with `prove`, over the tree given with `--tree-db`. Unless
+    /// `--proof-path` is given, it's saved next to the manifest, numbered after its position in
+    /// the chain, e.g. `proof-3.json`.
+    #[clap(flatten)]
+    prove: ProveArgs,
 }
 
-fn main() {
-    // Setup the logger.
-    sp1_sdk::utils::setup_logger();
+/// The arguments for the `aggregate` subcommand.
+#[derive(Args, Debug)]
+struct AggregateArgs {
+    /// The compressed proofs to aggregate, saved with `prove --proof-system compressed`.
+    #[clap(required = true)]
+    proof_paths: Vec<PathBuf>,
+
+    /// The proof system to generate the aggregated proof with.
+    #[clap(long, value_enum, default_value = "groth16")]
+    proof_system: ProofSystem,
+
+    /// The path to save the aggregated proof to, or `-` for stdout. Parent directories are created
+    /// as needed.
+    #[clap(long, default_value = "proof.aggregate.json")]
+    proof_path: PathBuf,
+
+    /// The format to save the proof in. Either format is detected automatically when loading.
+    #[clap(long, value_enum, default_value = "json")]
+    format: ProofFormat,
+
+    /// Compress the proof file with zstd, which shrinks JSON proofs several times over. Compressed
+    /// files are detected and decompressed automatically when loading.
+    #[clap(long)]
+    compress: bool,
 
+    /// Overwrite the proof file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+/// The arguments for the `wrap` subcommand.
+#[derive(Args, Debug)]
+struct WrapArgs {
+    /// The compressed proof to wrap, saved with `prove --stage compressed`.
+    compressed_path: PathBuf,
+
+    /// The proof system to wrap the proof with, either plonk or groth16.
+    #[clap(long, value_enum, default_value = "groth16")]
+    proof_system: ProofSystem,
+
+    /// The path to save the wrapped proof to, or `-` for stdout. Parent directories are created as
+    /// needed.
+    ///
+    /// Defaults to `proof.json` (or `proof.bin` for `--format bincode`).
+    #[clap(long)]
+    proof_path: Option<PathBuf>,
+
+    /// The format to save the proof in. Either format is detected automatically when loading.
+    #[clap(long, value_enum, default_value = "json")]
+    format: ProofFormat,
+
+    /// Compress the proof file with zstd, which shrinks JSON proofs several times over. Compressed
+    /// files are detected and decompressed automatically when loading.
+    #[clap(long)]
+    compress: bool,
+
+    /// Overwrite the proof file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+/// The arguments for the `tree` subcommand.
+#[derive(Args, Debug)]
+struct TreeArgs {
+    /// The tree database, as passed to `--tree-db`.
+    #[clap(long, global = true, default_value = "tree.sled")]
+    tree_db: PathBuf,
+
+    #[clap(subcommand)]
+    command: TreeCommand,
+}
+
+/// The arguments for the `tree export` subcommand.
+#[derive(Args, Debug)]
+struct TreeExportArgs {
+    /// The format to export the tree in.
+    #[clap(long, value_enum, default_value = "json")]
+    format: SnapshotFormat,
+
+    /// Write the snapshot to this file rather than printing it.
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Overwrite the output file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+/// The root of the tree to read, as given to `tree ls` and `tree get`.
+#[derive(Args, Debug)]
+struct TreeRootArgs {
+    /// The hex-encoded root to read the tree under, e.g. the `root` or `prior_root` a proof
+    /// commits. [default: the current root]
+    #[clap(long, value_parser = parse_bytes32)]
+    root: Option<[u8; 32]>,
+
+    /// Read the tree of this namespace under its current root instead of the main tree.
+    #[clap(long, conflicts_with = "root")]
+    namespace: Option<String>,
+}
+
+impl TreeRootArgs {
+    /// The root to read `tree` under, or `None` if that tree is empty.
+    fn resolve(&self, tree: &HostTree) -> anyhow::Result<Option<[u8; 32]>> {
+        match (&self.root, &self.namespace) {
+            (Some(root), _) => Ok(Some(*root)),
+            (None, Some(namespace)) => tree.namespace_root(namespace),
+            (None, None) => tree.root(),
+        }
+    }
+}
+
+/// The arguments for the `tree ls` subcommand.
+#[derive(Args, Debug)]
+struct TreeLsArgs {
+    #[clap(flatten)]
+    root: TreeRootArgs,
+}
+
+/// The arguments for the `tree get` subcommand.
+#[derive(Args, Debug)]
+struct TreeGetArgs {
+    /// The hex-encoded 32-byte key to look up.
+    #[clap(value_parser = parse_bytes32)]
+    key: [u8; 32],
+
+    #[clap(flatten)]
+    root: TreeRootArgs,
+}
+
+/// The arguments for the `schema` subcommand.
+#[derive(Args, Debug)]
+struct SchemaArgs {
+    /// Also write the schema as JSON to this file.
+    #[clap(long)]
+    output: Option<PathBuf>,
+
+    /// Overwrite the output file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+fn main() {
     // Parse the command line arguments.
-    let args = Args::parse();
+    let cli = Cli::parse();
+    let json = cli.json;
 
-    if !args.execute && !args.generate && !args.verify {
-        eprintln!("Error: You must specify either --execute, --generate or --verify");
-        std::process::exit(1);
+    // Report errors on stderr, with an exit code telling the caller what went wrong.
+    let report = run(cli).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(err.exit_code());
+    });
+    report.print(json);
+
+    // Still report the results above, but make failed checks visible to the caller.
+    if let Some(err) = report.failure() {
+        std::process::exit(err.exit_code());
     }
+}
+
+/// Setup the prover and run the selected command.
+fn run(cli: Cli) -> Result<Report, Error> {
+    // Setup the logger, and the progress bar it feeds. Concurrent proofs would all advance the
+    // same progress bar, so it's only shown when proving one at a time.
+    let jobs = match &cli.command {
+        Command::Prove(args) => args.jobs,
+        Command::Append(args) => args.prove.jobs,
+        _ => 1,
+    };
+    let verbosity = Verbosity::from_flags(cli.quiet, cli.verbose);
+    let progress = Progress::new(!cli.no_progress && !verbosity.is_quiet() && jobs <= 1);
+    setup_logger(verbosity, Some(&progress));
+
+    // Load the config file, if any.
+    let config = match &cli.config {
+        Some(path) => Config::load(path).map_err(Error::io(format!(
+            "failed to load config {}",
+            path.display()
+        )))?,
+        None => Config::default(),
+    };
 
     // Setup the prover client.
-    let client = ProverClient::new();
+    let mode = cli.prover.or(config.prover).unwrap_or(ProverMode::Local);
+    let timeout = match &cli.command {
+        Command::Prove(args) => args.timeout.map(Duration::from_secs),
+        Command::Append(args) => args.prove.timeout.map(Duration::from_secs),
+        _ => None,
+    };
+    let prover = Prover::new(
+        mode,
+        cli.private_key.as_deref(),
+        timeout,
+        progress,
+        verbosity,
+    )
+    .map_err(Error::Other)?;
 
-    // Setup the inputs.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&args.n);
-    stdin.write(&args.offset);
-
-    println!("n: {}", args.n);
-
-    if args.execute {
-        // Execute the program
-        let start_time = Instant::now();
-        let (output, report) = client.execute(FIBONACCI_ELF, stdin).run().unwrap();
-        println!("Program executed successfully.");
-        println!("Time elapsed: {:?}", start_time.elapsed());
-
-        // Read the output.
-        let decoded = PublicValuesStruct::abi_decode(output.as_slice(), true).unwrap();
-        let PublicValuesStruct { n, a, b, root } = decoded;
-        println!("n: {}", n);
-        println!("a: {}", a);
-        println!("b: {}", b);
-        println!("root: {:?}", root);
-        println!("offset: {}", args.offset);
-
-        let (expected_a, expected_b) = fibonacci_lib::fibonacci(n);
-        assert_eq!(a, expected_a + args.offset);
-        assert_eq!(b, expected_b + args.offset);
-        println!("Values are correct!");
+    match cli.command {
+        Command::Execute(args) => execute(&prover, &config, args),
+        Command::Prove(args) => prove(&prover, &config, args),
+        Command::Verify(args) => verify(&prover, &config, args),
+        Command::Vkey(args) => vkey(&prover, args),
+        Command::Bench(args) => bench(&prover, args),
+        Command::Evm(args) => evm(&prover, &config, args),
+        Command::Estimate(args) => estimate(&prover, &config, args),
+        Command::Decode(args) => decode(args),
+        Command::Diff(args) => diff(args),
+        Command::GenSol(args) => gen_sol(args),
+        Command::Schema(args) => schema(&prover, args),
+        Command::InclusionProof(args) => inclusion_proof(args),
+        Command::VerifyInclusion(args) => verify_inclusion(args),
+        Command::ChainCheck(args) => chain_check(&prover, args),
+        Command::Append(args) => append(&prover, &config, args),
+        Command::Aggregate(args) => aggregate(&prover, args),
+        Command::Wrap(args) => wrap(&prover, args),
+        Command::Tree(args) => tree(args),
+    }
+}
+
+/// Execute the program and check its public values against the expected ones.
+fn execute(prover: &Prover, config: &Config, args: ExecuteArgs) -> Result<Report, Error> {
+    let inputs = args.inputs.resolve(config)?;
+    let (witness, tree, tree_stats) = match &args.tree_db {
+        Some(tree_db) => {
+            let mut tree = HostTree::open(tree_db, inputs.hasher).map_err(Error::Other)?;
+            let (witness, root) = tree
+                .prepare(&inputs, args.path_witness)
+                .map_err(Error::Other)?;
+            let tree_stats = tree.stats();
+            (witness, Some((tree, root)), tree_stats)
+        }
+        None => {
+            // The program starts from an empty tree, so count its tree work on an empty one too.
+            // Not being able to is no reason to fail the execution.
+            let tree_stats = HostTree::temporary(inputs.hasher)
+                .and_then(|mut tree| tree.prepare(&inputs, false).map(|_| tree.stats()))
+                .ok()
+                .flatten();
+            (TreeWitness::default(), None, tree_stats)
+        }
+    };
+    let stdin = to_stdin(&inputs, &witness);
 
+    // Execute the program
+    let start_time = Instant::now();
+    let (output, report) = prover
+        .client
+        .execute(FIBONACCI_ELF, stdin)
+        .run()
+        .map_err(|err| Error::Execution(err.to_string()))?;
+    let execution_time = start_time.elapsed();
+
+    // Read the output.
+    let public_values =
+        PublicValues::decode(output.as_slice()).map_err(Error::public_values(output.as_slice()))?;
+
+    // Check the program committed to the inputs it was given, and computed the right values.
+    let mismatch = match verify_public_values(output.as_slice(), &inputs) {
+        Ok(values) => tree
+            .as_ref()
+            .and_then(|(tree, root)| root_mismatch(&values, tree, &witness, root)),
+        Err(PublicValuesError::Mismatch(field)) => Some(field),
+        Err(PublicValuesError::Decode(err)) => {
+            return Err(Error::public_values(output.as_slice())(err))
+        }
+    };
+
+    Ok(Report::Execute(ExecuteReport {
+        public_values,
+        values_correct: mismatch.is_none(),
+        mismatch,
         // Record the number of cycles executed.
-        println!("Number of cycles: {}", report.total_instruction_count());
-    } else if args.generate {
-        // Setup the program for proving.
-        let (pk, _) = client.setup(FIBONACCI_ELF);
-
-        // Generate the proof
-        let proof = client
-            .prove(&pk, stdin)
-            .groth16()
-            .run()
-            .expect("failed to generate proof");
-
-        println!("Successfully generated proof! {:#?}", proof);
-
-        save_proof_to_json(&proof).expect("failed to save proof to disk");
-    } else if args.verify {
-        // Setup the program for proving.
-        let (_, vk) = client.setup(FIBONACCI_ELF);
-
-        let proof = load_proof_from_json();
-
-        println!("loaded proof.json from disk: {:#?}", proof);
-
-        let mut proof = proof;
-        proof.stdin = sp1_sdk::SP1Stdin::default();
-        println!("mutated proof, now is: {:#?}", proof);
-
-        // Verify the proof.
-        client.verify(&proof, &vk).expect("failed to verify proof");
-        println!("Successfully verified proof!");
-        println!(
-            "I don't know which offset was used:
-            on the proof.public_values i can see n, a, b but not the offset,
-            yet i know the proof is valid"
-        );
+        cycles: report.total_instruction_count(),
+        monotree_cycles: report.cycle_tracker.get("monotree").copied(),
+        inclusion_proof_cycles: report.cycle_tracker.get("inclusion-proof").copied(),
+        pisano_cycles: report.cycle_tracker.get("pisano").copied(),
+        tree_stats,
+        execution_time_secs: execution_time.as_secs_f64(),
+    }))
+}
 
-        println!(
-            "public_values slice is {}",
-            proof.public_values.encode_hex::<String>()
-        );
+/// The first of the roots committed in `values` that doesn't match the ones of the host tree the
+/// witness was taken from, if any, including the roots of its namespaces.
+fn root_mismatch(
+    values: &PublicValuesStruct,
+    tree: &HostTree,
+    witness: &TreeWitness,
+    root: &[u8; 32],
+) -> Option<&'static str> {
+    if values.prior_root.0 != witness.root.unwrap_or_default() {
+        Some("prior_root")
+    } else if &values.root != root {
+        Some("root")
+    } else if values.namespaces_digest.0 != tree.namespaces_digest() {
+        Some("namespaces_digest")
+    } else {
+        None
+    }
+}
+
+/// Generate a proof of the program with the selected proof system and save it to disk.
+fn prove(prover: &Prover, config: &Config, args: ProveArgs) -> Result<Report, Error> {
+    let proof_system = args
+        .proof_system
+        .or(config.proof_system)
+        .unwrap_or(ProofSystem::Groth16);
+    let proof_path = args
+        .proof_path
+        .clone()
+        .or_else(|| config.proof_path.clone())
+        .unwrap_or_else(|| proof_system.default_proof_path(args.format));
+
+    let mut batch = args
+        .resolve_inputs(config)
+        .map_err(Error::io("failed to read the inputs to prove"))?;
+    let proof_paths: Vec<PathBuf> = match batch.as_slice() {
+        [_] => vec![proof_path.clone()],
+        batch => batch
+            .iter()
+            .map(|inputs| batch_proof_path(&proof_path, inputs.n, inputs.offset))
+            .collect(),
+    };
+
+    // A proof written to stdout has no file to name the others after, nor to share stdout with.
+    if is_stdio(&proof_path) {
+        if batch.len() > 1 {
+            return Err(Error::Other(anyhow::anyhow!(
+                "--proof-path - can only be used to prove one input at a time"
+            )));
+        }
+        if args.save_public_values || (args.save_vk && args.vk_path.is_none()) {
+            return Err(Error::Other(anyhow::anyhow!(
+                "--proof-path - can't be used with --save-public-values, nor with --save-vk \
+                 without --vk-path"
+            )));
+        }
+    }
+
+    let mut vk_path = args.save_vk.then(|| {
+        args.vk_path
+            .clone()
+            .unwrap_or_else(|| default_vk_path(&proof_path))
+    });
+
+    if let Some(request_id) = &args.resume {
+        return resume(prover, &args, request_id, proof_path, vk_path);
+    }
+
+    // Fail before spending minutes on proving if the proofs can't be saved afterwards. Proofs
+    // saved by content are named after the verification key, so they're only checked after setup.
+    if args.proof_dir.is_none() {
+        check_prove_outputs(&proof_paths, vk_path.as_ref(), &args)?;
+    }
+
+    // The proof of the previous run to chain onto, which the program verifies as a deferred proof,
+    // so it must be a compressed one.
+    let previous = match &args.chain_from {
+        Some(_) if batch.len() > 1 => {
+            return Err(Error::Other(anyhow::anyhow!(
+                "--chain-from can only be used to prove one input at a time"
+            )))
+        }
+        Some(path) => {
+            let proof = load_proof(path)?;
+            if !matches!(proof.proof, SP1Proof::Compressed(_)) {
+                return Err(Error::invalid_proof_file(
+                    path,
+                    "only compressed proofs can be chained onto, prove with --proof-system \
+                     compressed",
+                ));
+            }
+            Some(proof)
+        }
+        None => None,
+    };
+
+    // Take the witness of the host tree for the program, or have it start from an empty tree.
+    let tree = match &args.tree_db {
+        Some(_) if batch.len() > 1 => {
+            return Err(Error::Other(anyhow::anyhow!(
+                "--tree-db can only be used to prove one input at a time"
+            )))
+        }
+        Some(tree_db) => {
+            let mut tree = HostTree::open(tree_db, batch[0].hasher).map_err(Error::Other)?;
+            let (witness, root) = tree
+                .prepare(&batch[0], args.path_witness)
+                .map_err(Error::Other)?;
+            Some((tree, witness, root))
+        }
+        None => None,
+    };
+    let empty_witness = TreeWitness::default();
+    let witness = tree
+        .as_ref()
+        .map_or(&empty_witness, |(_, witness, _)| witness);
+
+    // The program would fail to chain onto a previous run that left the tree elsewhere, so check
+    // before proving.
+    if let Some(previous) = &previous {
+        let bytes = previous.public_values.as_slice();
+        let values =
+            decode_public_values(bytes, batch[0].encoding).map_err(Error::public_values(bytes))?;
+        if values.root != witness.root.unwrap_or_default() {
+            return Err(Error::Other(anyhow::anyhow!(
+                "the previous proof left the tree at {}, not at the root this run starts from, \
+                 pass the --tree-db it was proven with",
+                hex::encode(values.root)
+            )));
+        }
+    }
+
+    // Setup the program for proving once, and reuse the proving key for every input.
+    let start_time = Instant::now();
+    let (pk, vk) = prover.client.setup(FIBONACCI_ELF);
+    let setup_time = start_time.elapsed();
+
+    // The previous proof is one of this program, so it has the same verification key.
+    if let Some(previous) = &previous {
+        batch[0].previous = Some(PreviousProof {
+            vkey: vk.hash_u32(),
+            public_values: previous.public_values.to_vec(),
+        });
+    }
+
+    let store = args.proof_dir.as_deref().map(ProofStore::new);
+    let proof_paths = match &store {
+        Some(store) => {
+            let vkey_hash = vk.bytes32();
+            let proof_paths: Vec<_> = batch
+                .iter()
+                .map(|inputs| store.proof_path(&vkey_hash, inputs, proof_system))
+                .collect();
+            if args.save_vk && args.vk_path.is_none() {
+                vk_path = Some(store.vk_path(&vkey_hash));
+            }
+            check_prove_outputs(&proof_paths, vk_path.as_ref(), &args)?;
+            proof_paths
+        }
+        None => proof_paths,
+    };
+
+    // Prove the inputs on a pool of workers sharing the prover and the proving key.
+    let work: Vec<_> = batch.iter().zip(proof_paths).collect();
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let proofs = Mutex::new(Vec::with_capacity(work.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..args.jobs.clamp(1, work.len().max(1)) {
+            scope.spawn(|| loop {
+                // Stop handing out inputs once a proof failed, the command fails either way.
+                if failed.load(Ordering::Relaxed) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((inputs, proof_path)) = work.get(index) else {
+                    break;
+                };
+                let result = prove_one(
+                    prover,
+                    &pk,
+                    inputs,
+                    witness,
+                    previous.as_ref(),
+                    proof_path,
+                    proof_system,
+                    &args,
+                );
+                if result.is_err() {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                proofs.lock().unwrap().push((index, result));
+            });
+        }
+    });
+    // Report the proofs in the order the inputs were given, not the order they finished in.
+    let mut proofs = proofs.into_inner().unwrap();
+    proofs.sort_by_key(|(index, _)| *index);
+    let proofs = proofs
+        .into_iter()
+        .map(|(_, result)| result)
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if let Some(vk_path) = &vk_path {
+        save_vk(&vk, vk_path, args.force)?;
+    }
+
+    // Record the proofs in the index of the store, now that they're all saved.
+    if let Some(store) = &store {
+        let index_path = store.index_path();
+        let mut index = StoreIndex::load_or_default(&index_path).map_err(Error::io(format!(
+            "failed to read the proof index {}",
+            index_path.display()
+        )))?;
+        for (inputs, proof) in batch.iter().zip(&proofs) {
+            index.insert(StoreEntry {
+                vkey_hash: vk.bytes32(),
+                input_hash: input_hash(inputs),
+                proof_system,
+                inputs: inputs.clone(),
+                proof_path: proof.proof_path.clone(),
+                public_values_paths: proof.public_values_paths.clone(),
+            });
+        }
+        index.save(&index_path).map_err(Error::io(format!(
+            "failed to save the proof index {}",
+            index_path.display()
+        )))?;
+    }
+
+    // Move the host tree on to the root the proof ends up with, once it's checked to be the one
+    // the host expects.
+    if let Some((mut tree, witness, root)) = tree {
+        let public_values = hex::decode(&proofs[0].public_values).expect("encoded with hex");
+        let (_, values) = PublicValues::decode_struct(&public_values)
+            .map_err(Error::public_values(&public_values))?;
+        if root_mismatch(&values, &tree, &witness, &root).is_some() {
+            return Err(Error::ValuesMismatch);
+        }
+        tree.commit(&root).map_err(Error::Other)?;
+    }
+
+    Ok(Report::Prove(ProveReport {
+        proof_system,
+        prover: prover.mode,
+        setup_secs: setup_time.as_secs_f64(),
+        vk_path,
+        proofs,
+    }))
+}
+
+/// Check that the proofs and the other files `prove` saves can be written to.
+fn check_prove_outputs(
+    proof_paths: &[PathBuf],
+    vk_path: Option<&PathBuf>,
+    args: &ProveArgs,
+) -> Result<(), Error> {
+    let detached = proof_paths
+        .iter()
+        .filter(|_| args.save_public_values)
+        .flat_map(|path| public_values_paths(path));
+    let outputs = proof_paths.iter().chain(vk_path).cloned();
+    for path in outputs.chain(detached) {
+        check_output_path(&path, args.force).map_err(Error::io("refusing to start proving"))?;
+    }
+    Ok(())
+}
+
+/// Generate and save the proof for one set of inputs, chaining onto the `previous` proof, if any.
+#[allow(clippy::too_many_arguments)]
+fn prove_one(
+    prover: &Prover,
+    pk: &SP1ProvingKey,
+    inputs: &FibonacciInputs,
+    witness: &TreeWitness,
+    previous: Option<&SP1ProofWithPublicValues>,
+    proof_path: &Path,
+    proof_system: ProofSystem,
+    args: &ProveArgs,
+) -> Result<ProofSummary, Error> {
+    let request_path = PendingRequest::path_for(proof_path);
+
+    // Reuse the proof of the same stdin if it's cached, which the program turns into the same
+    // public values, but for a timestamp the inputs were given none of, see `cache_key`.
+    // Deterministic proofs must record the environment they were generated in, which proofs cached
+    // by other runs of the same stdin don't.
+    let cache = (!args.no_cache).then(|| ProofCache::new(&args.cache_dir));
+    let key = cache_key(
+        FIBONACCI_ELF,
+        inputs,
+        witness,
+        proof_system,
+        args.default_timestamp(),
+    );
+    let cached = match &cache {
+        Some(cache) => cache.get(&key)?.filter(|envelope| {
+            !args.deterministic
+                || envelope
+                    .metadata
+                    .as_ref()
+                    .is_some_and(|metadata| metadata.environment.is_some())
+        }),
+        None => None,
+    };
+    let is_cached = cached.is_some();
+
+    let (envelope, proving_time, timings) = match cached {
+        Some(envelope) => (envelope, Duration::ZERO, None),
+        None => {
+            // Generate the proof based on the selected proof system.
+            let start_time = Instant::now();
+            let (proof, timings) = prover
+                .prove(
+                    pk,
+                    inputs,
+                    witness,
+                    previous,
+                    proof_system,
+                    Some(&request_path),
+                )
+                .map_err(Error::Proving)?;
+            let proving_time = start_time.elapsed();
+
+            let cycles = timings.map(|timings| timings.cycles);
+            let metadata = ProofMetadata {
+                environment: args
+                    .deterministic
+                    .then(|| ProofEnvironment::capture(key.clone(), proof_system)),
+                ..ProofMetadata::new(&pk.vk, FIBONACCI_ELF, prover.mode, cycles)
+            };
+            (ProofEnvelope::new(proof, metadata), proving_time, timings)
+        }
+    };
+    let envelope = ProofEnvelope {
+        vk: args.embed_vk.then(|| pk.vk.clone()),
+        ..envelope
+    };
+    let saved = save_proof(
+        &envelope,
+        proof_path,
+        args.format,
+        args.compress,
+        args.force,
+    );
+    saved.map_err(Error::io(format!(
+        "failed to save proof to {}",
+        proof_path.display()
+    )))?;
+    // The proof is safely on disk, so the request doesn't need resuming anymore.
+    let _ = std::fs::remove_file(&request_path);
+    if let Some(cache) = cache.filter(|_| !is_cached) {
+        cache.insert(&key, &envelope).map_err(Error::io(format!(
+            "failed to cache proof in {}",
+            cache.path(&key).display()
+        )))?;
+    }
+    let proof = envelope.proof;
+    let public_values_paths = if args.save_public_values {
+        save_detached_public_values(&proof, proof_path, args.force)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(ProofSummary {
+        n: inputs.n,
+        offset: inputs.offset,
+        proof_path: proof_path.to_path_buf(),
+        public_values: hex::encode(proof.public_values.as_slice()),
+        proving_time_secs: proving_time.as_secs_f64(),
+        cached: is_cached,
+        public_values_paths,
+        // Concurrent proofs go through the same phases at the same time, so only time them when
+        // proving one at a time.
+        timings: timings.filter(|_| args.jobs <= 1),
+    })
+}
+
+/// Download the proof of an earlier prover network request and save it like `prove` would.
+fn resume(
+    prover: &Prover,
+    args: &ProveArgs,
+    request_id: &str,
+    proof_path: PathBuf,
+    vk_path: Option<PathBuf>,
+) -> Result<Report, Error> {
+    // Recover the inputs the request was made with, so they can be reported.
+    let request_path = PendingRequest::path_for(&proof_path);
+    let inputs = match PendingRequest::load(&request_path) {
+        Ok(pending) if pending.request_id == request_id => pending.inputs,
+        _ => {
+            return Err(Error::Other(anyhow::anyhow!(
+                "no pending request {} found in {}, pass the --proof-path it was made with",
+                request_id,
+                request_path.display()
+            )))
+        }
+    };
 
-        let decoded = PublicValuesStruct::abi_decode(proof.public_values.as_slice(), true).unwrap();
-        let PublicValuesStruct { n, a, b, root } = decoded;
-        println!(
-            "so in public_values i see n={}, a={}, b={}, root={:?}",
-            n, a, b, root
+    let start_time = Instant::now();
+    let proof = prover.resume(request_id).map_err(Error::Proving)?;
+    let proving_time = start_time.elapsed();
+
+    // The proof file records the program's verification key hash, so set it up either way.
+    let start_time = Instant::now();
+    let (_, vk) = prover.client.setup(FIBONACCI_ELF);
+    let setup_time = start_time.elapsed();
+
+    let metadata = ProofMetadata::new(&vk, FIBONACCI_ELF, prover.mode, None);
+    let envelope = ProofEnvelope {
+        vk: args.embed_vk.then(|| vk.clone()),
+        ..ProofEnvelope::new(proof, metadata)
+    };
+    let saved = save_proof(
+        &envelope,
+        &proof_path,
+        args.format,
+        args.compress,
+        args.force,
+    );
+    saved.map_err(Error::io(format!(
+        "failed to save proof to {}",
+        proof_path.display()
+    )))?;
+    let _ = std::fs::remove_file(&request_path);
+    let proof = envelope.proof;
+    let public_values_paths = if args.save_public_values {
+        save_detached_public_values(&proof, &proof_path, args.force)?
+    } else {
+        Vec::new()
+    };
+
+    if let Some(vk_path) = &vk_path {
+        save_vk(&vk, vk_path, args.force)?;
+    }
+
+    Ok(Report::Prove(ProveReport {
+        proof_system: ProofSystem::of(&proof),
+        prover: prover.mode,
+        setup_secs: setup_time.as_secs_f64(),
+        vk_path,
+        proofs: vec![ProofSummary {
+            n: inputs.n,
+            offset: inputs.offset,
+            proof_path,
+            public_values: hex::encode(proof.public_values.as_slice()),
+            proving_time_secs: proving_time.as_secs_f64(),
+            cached: false,
+            public_values_paths,
+            timings: None,
+        }],
+    }))
+}
+
+/// Save the verifying key, describing where it was saved to if that fails.
+fn save_vk(vk: &SP1VerifyingKey, vk_path: &Path, force: bool) -> Result<(), Error> {
+    save_vk_to_json(vk, vk_path, force).map_err(Error::io(format!(
+        "failed to save verifying key to {}",
+        vk_path.display()
+    )))
+}
+
+/// Save the public values of the proof at `proof_path` next to it, raw and decoded.
+fn save_detached_public_values(
+    proof: &SP1ProofWithPublicValues,
+    proof_path: &Path,
+    force: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let public_values = proof.public_values.as_slice();
+    let decoded =
+        PublicValues::decode(public_values).map_err(Error::public_values(public_values))?;
+    save_public_values(public_values, &decoded, proof_path, force).map_err(Error::io(format!(
+        "failed to save the public values next to {}",
+        proof_path.display()
+    )))
+}
+
+/// Load a proof from disk and verify it against the program's verifying key.
+fn verify(prover: &Prover, config: &Config, args: VerifyArgs) -> Result<Report, Error> {
+    let proof_path = args
+        .proof_path
+        .or_else(|| config.proof_path.clone())
+        .unwrap_or_else(|| PathBuf::from("proof.json"));
+
+    let ProofEnvelope {
+        metadata,
+        vk: embedded_vk,
+        proof: mut proof,
+        ..
+    } = load_envelope(&proof_path)?;
+    proof.stdin = sp1_sdk::SP1Stdin::default();
+
+    // Load the verifying key, take the one embedded in the proof file, or setup the program to get
+    // it, in that order.
+    let embedded_vk = embedded_vk.filter(|_| args.vk_path.is_none() && !args.external_vk);
+    let embedded = embedded_vk.is_some();
+    let (vk, setup_time) = match (&args.vk_path, embedded_vk) {
+        (Some(vk_path), _) => (load_vk_from_json(vk_path)?, None),
+        (None, Some(vk)) => (vk, None),
+        (None, None) => {
+            let start_time = Instant::now();
+            let (_, vk) = prover.client.setup(FIBONACCI_ELF);
+            (vk, Some(start_time.elapsed()))
+        }
+    };
+
+    // Verify the proof, unless the proof file records that it's of another program (or another
+    // build of this one), which tells without the cost of verifying it.
+    let start_time = Instant::now();
+    let result = match &metadata {
+        Some(metadata) if metadata.vkey_hash != vk.bytes32() => Err(format!(
+            "the proof is of the program with verification key hash {}, not {}",
+            metadata.vkey_hash,
+            vk.bytes32()
+        )),
+        _ => prover
+            .client
+            .verify(&proof, &vk)
+            .map_err(|err| err.to_string()),
+    };
+    let verification_time = start_time.elapsed();
+
+    let bytes = proof.public_values.as_slice();
+    let invalid =
+        |err| Error::invalid_proof_file(&proof_path, UndecodablePublicValues::new(bytes, err));
+    let public_values = PublicValues::decode(bytes).map_err(invalid)?;
+    let offset_matches = match args.offset {
+        Some(offset) => match PublicValues::decode_versioned(bytes).map_err(invalid)?.1 {
+            VersionedPublicValues::Current(values) => {
+                Some(values.offset_matches(offset, args.salt.as_ref()))
+            }
+            // The first version didn't commit the offset, so there is nothing to check it against.
+            VersionedPublicValues::Legacy(_) => Some(false),
+        },
+        None => None,
+    };
+
+    Ok(Report::Verify(VerifyReport {
+        proof_system: ProofSystem::of(&proof),
+        vk_path: args.vk_path,
+        vkey: vk.bytes32(),
+        embedded_vk: embedded,
+        metadata,
+        verified: result.is_ok(),
+        error: result.err(),
+        public_values,
+        offset_matches,
+        proof_path,
+        setup_secs: setup_time.map(|time| time.as_secs_f64()),
+        verification_time_secs: verification_time.as_secs_f64(),
+    }))
+}
+
+/// Compute the program's verification key hash, and optionally write it to a file.
+fn vkey(prover: &Prover, args: VkeyArgs) -> Result<Report, Error> {
+    let (_, vk) = prover.client.setup(FIBONACCI_ELF);
+    let bytes32 = vk.bytes32();
+
+    if let Some(output) = &args.output {
+        check_output_path(output, args.force)
+            .and_then(|()| std::fs::write(output, format!("{}\n", bytes32)))
+            .map_err(Error::io(format!(
+                "failed to write verification key hash to {}",
+                output.display()
+            )))?;
+    }
+
+    Ok(Report::Vkey(VkeyReport {
+        bytes32,
+        words: vk.hash_u32(),
+        output: args.output,
+    }))
+}
+
+/// Describe the layout of the public values committed by the program, and optionally write it to
+/// a file.
+fn schema(prover: &Prover, args: SchemaArgs) -> Result<Report, Error> {
+    let (_, vk) = prover.client.setup(FIBONACCI_ELF);
+    let schema = PublicValuesSchema::new(vk.bytes32());
+
+    if let Some(output) = &args.output {
+        let json = serde_json::to_string_pretty(&schema).expect("failed to serialize schema");
+        check_output_path(output, args.force)
+            .and_then(|()| std::fs::write(output, format!("{}\n", json)))
+            .map_err(Error::io(format!(
+                "failed to write schema to {}",
+                output.display()
+            )))?;
+    }
+
+    Ok(Report::Schema(SchemaReport {
+        schema,
+        output: args.output,
+    }))
+}
+
+/// Execute the program for every combination of the given inputs and collect the cycle counts,
+/// hashing the tree with Blake3 and with SHA-256.
+fn bench(prover: &Prover, args: BenchArgs) -> Result<Report, Error> {
+    let mut rows = Vec::new();
+    for &n in &args.n {
+        for &offset in &args.offset {
+            let execute = |hasher, method| {
+                let inputs = FibonacciInputs {
+                    n,
+                    offset,
+                    hasher,
+                    method,
+                    ..FibonacciInputs::default()
+                };
+                let stdin = to_stdin(&inputs, &TreeWitness::default());
+                prover
+                    .client
+                    .execute(FIBONACCI_ELF, stdin)
+                    .run()
+                    .map(|(_, report)| report)
+                    .map_err(|err| Error::Execution(err.to_string()))
+            };
+            let report = execute(TreeHasher::Blake3, FibonacciMethod::Iterative)?;
+            let sha2_report = execute(TreeHasher::Sha2, FibonacciMethod::Iterative)?;
+            let fast_report = execute(TreeHasher::Blake3, FibonacciMethod::FastDoubling)?;
+            let phase_cycles = |report: &sp1_sdk::ExecutionReport, name: &str| {
+                report.cycle_tracker.get(name).copied().unwrap_or(0)
+            };
+            rows.push(BenchRow {
+                n,
+                offset,
+                cycles: report.total_instruction_count(),
+                syscalls: report.total_syscall_count(),
+                fibonacci_cycles: phase_cycles(&report, "fibonacci"),
+                fast_fibonacci_cycles: phase_cycles(&fast_report, "fibonacci"),
+                monotree_cycles: phase_cycles(&report, "monotree"),
+                sha2_cycles: sha2_report.total_instruction_count(),
+                sha2_monotree_cycles: phase_cycles(&sha2_report, "monotree"),
+            });
+        }
+    }
+
+    Ok(Report::Bench(BenchReport {
+        rows,
+        csv: args.csv,
+    }))
+}
+
+/// Generate (or load) an EVM-compatible proof and save it as a Solidity test fixture.
+fn evm(prover: &Prover, config: &Config, args: EvmArgs) -> Result<Report, Error> {
+    // Setup the program.
+    let (pk, vk) = prover.client.setup(FIBONACCI_ELF);
+
+    let proof = match &args.proof_path {
+        Some(proof_path) => load_proof(proof_path)?,
+        None => {
+            if !matches!(args.proof_system, ProofSystem::Plonk | ProofSystem::Groth16) {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "only plonk and groth16 proofs can be verified inside Solidity"
+                )));
+            }
+            let inputs = args.inputs.resolve(config)?;
+            if inputs.encoding != PublicValuesEncoding::Abi {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "only ABI-encoded public values can be decoded inside Solidity"
+                )));
+            }
+            let (proof, _) = prover
+                .prove(
+                    &pk,
+                    &inputs,
+                    &TreeWitness::default(),
+                    None,
+                    args.proof_system,
+                    None,
+                )
+                .map_err(Error::Proving)?;
+            proof
+        }
+    };
+    let proof_system = ProofSystem::of(&proof);
+
+    let fixture = SP1FibonacciProofFixture::new(&proof, &vk)
+        .map_err(Error::public_values(proof.public_values.as_slice()))?;
+
+    // Save the fixture to a file.
+    let fixture_path = args
+        .fixture_path
+        .unwrap_or_else(|| default_fixture_path(proof_system));
+    fixture.save(&fixture_path).map_err(Error::io(format!(
+        "failed to write fixture to {}",
+        fixture_path.display()
+    )))?;
+
+    let calldata =
+        PublicValuesStruct::solidity_calldata(proof.public_values.as_slice(), &proof.bytes());
+    Ok(Report::Evm(EvmReport {
+        proof_system,
+        fixture_path,
+        fixture,
+        calldata: format!("0x{}", hex::encode(calldata)),
+    }))
+}
+
+/// Execute the program and turn its cycle count into proving time and cost estimates.
+fn estimate(prover: &Prover, config: &Config, args: EstimateArgs) -> Result<Report, Error> {
+    let stdin = to_stdin(&args.inputs.resolve(config)?, &TreeWitness::default());
+    let (_, report) = prover
+        .client
+        .execute(FIBONACCI_ELF, stdin)
+        .run()
+        .map_err(|err| Error::Execution(err.to_string()))?;
+    let cycles = report.total_instruction_count();
+
+    let model = CostModel {
+        cycles_per_sec: args.cycles_per_sec,
+        network_usd_per_billion_cycles: args.usd_per_billion_cycles,
+        ..CostModel::default()
+    };
+    Ok(Report::Estimate(EstimateReport {
+        cycles,
+        proof_system: args.proof_system,
+        local_proving_secs: model.local_proving_secs(cycles, args.proof_system),
+        network_cost_usd: model.network_cost_usd(cycles),
+    }))
+}
+
+/// Decode public values from a proof file or a hex string.
+fn decode(args: DecodeArgs) -> Result<Report, Error> {
+    let bytes = match (&args.proof_path, &args.public_values) {
+        (Some(proof_path), _) => load_proof(proof_path)?.public_values.to_vec(),
+        (None, Some(public_values)) => hex::decode(public_values.trim_start_matches("0x"))
+            .map_err(|err| Error::Other(anyhow::anyhow!("invalid hex public values: {}", err)))?,
+        (None, None) => unreachable!("clap requires one of the sources"),
+    };
+
+    let public_values = PublicValues::decode(&bytes).map_err(Error::public_values(&bytes))?;
+    Ok(Report::Decode(DecodeReport {
+        proof_path: args.proof_path,
+        public_values,
+    }))
+}
+
+/// Load two proofs and compare their proof systems, verifying keys and public values.
+fn diff(args: DiffArgs) -> Result<Report, Error> {
+    let load = |proof_path: &Path| -> Result<_, Error> {
+        let proof = load_proof(proof_path)?;
+        let bytes = proof.public_values.as_slice();
+        let public_values = PublicValues::decode(bytes).map_err(|err| {
+            Error::invalid_proof_file(proof_path, UndecodablePublicValues::new(bytes, err))
+        })?;
+        let diffed = DiffedProof {
+            proof_path: proof_path.to_path_buf(),
+            proof_system: ProofSystem::of(&proof),
+            vkey: proof_vkey_hash(&proof),
+        };
+        Ok((diffed, public_values))
+    };
+    let (a, a_values) = load(&args.a)?;
+    let (b, b_values) = load(&args.b)?;
+
+    let fields: Vec<_> = a_values
+        .fields()
+        .into_iter()
+        .zip(b_values.fields())
+        .map(|((field, a), (_, b))| FieldDiff {
+            field,
+            equal: a == b,
+            a,
+            b,
+        })
+        .collect();
+    let vkeys_match = a.vkey.as_ref().zip(b.vkey.as_ref()).map(|(a, b)| a == b);
+    let identical = a.proof_system == b.proof_system
+        && vkeys_match != Some(false)
+        && fields.iter().all(|diff| diff.equal);
+
+    Ok(Report::Diff(DiffReport {
+        a,
+        b,
+        fields,
+        vkeys_match,
+        identical,
+    }))
+}
+
+/// Write the Solidity definition of the public values, or check that it's up to date.
+fn gen_sol(args: GenSolArgs) -> Result<Report, Error> {
+    let output = args.output.unwrap_or_else(default_solidity_path);
+    let solidity = public_values_solidity();
+
+    // A missing file is simply out of date.
+    let up_to_date = std::fs::read_to_string(&output).is_ok_and(|existing| existing == solidity);
+    let written = !args.check && !up_to_date;
+    if written {
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(Error::io(format!("failed to create {}", parent.display())))?;
+        }
+        std::fs::write(&output, solidity)
+            .map_err(Error::io(format!("failed to write {}", output.display())))?;
+    }
+
+    Ok(Report::GenSol(GenSolReport {
+        output,
+        check: args.check,
+        up_to_date,
+        written,
+    }))
+}
+
+/// Export the Merkle proof a proof commits, along with what it proves, and optionally write it to
+/// a file.
+fn inclusion_proof(args: InclusionProofArgs) -> Result<Report, Error> {
+    let proof = load_proof(&args.proof_path)?;
+    let bytes = proof.public_values.as_slice();
+    let public_values = PublicValues::decode(bytes).map_err(|err| {
+        Error::invalid_proof_file(&args.proof_path, UndecodablePublicValues::new(bytes, err))
+    })?;
+    let PublicValues {
+        root,
+        key: Some(key),
+        leaf: Some(leaf),
+        inclusion_verified: Some(verified_in_guest),
+        inclusion_proof: Some(steps),
+        hasher,
+        ..
+    } = public_values
+    else {
+        return Err(Error::Other(anyhow::anyhow!(
+            "proofs of the first version don't commit an inclusion proof"
+        )));
+    };
+    let hasher = match hasher {
+        Some(hasher) => hasher
+            .parse()
+            .map_err(|err: String| Error::invalid_proof_file(&args.proof_path, err))?,
+        None => TreeHasher::default(),
+    };
+    let inclusion_proof = ExportedInclusionProof {
+        root,
+        hasher,
+        key,
+        leaf,
+        verified_in_guest,
+        steps,
+    };
+
+    if let Some(output) = &args.output {
+        let json =
+            serde_json::to_string_pretty(&inclusion_proof).expect("failed to serialize the proof");
+        check_output_path(output, args.force)
+            .and_then(|()| std::fs::write(output, format!("{}\n", json)))
+            .map_err(Error::io(format!(
+                "failed to write inclusion proof to {}",
+                output.display()
+            )))?;
+    }
+
+    Ok(Report::InclusionProof(InclusionProofReport {
+        proof_path: args.proof_path,
+        inclusion_proof,
+        output: args.output,
+    }))
+}
+
+/// Check an exported inclusion proof against the root it was exported with, or another one,
+/// without linking monotree.
+fn verify_inclusion(args: VerifyInclusionArgs) -> Result<Report, Error> {
+    let json = std::fs::read_to_string(&args.input).map_err(Error::io(format!(
+        "failed to read {}",
+        args.input.display()
+    )))?;
+    let inclusion_proof: ExportedInclusionProof = serde_json::from_str(&json).map_err(|err| {
+        Error::Other(anyhow::anyhow!(
+            "{} isn't an inclusion proof: {}",
+            args.input.display(),
+            err
+        ))
+    })?;
+    let root = match args.root {
+        Some(root) => hex::encode(root),
+        None => inclusion_proof.root.clone(),
+    };
+    let verified = inclusion_proof.verify(&root).map_err(|err| {
+        Error::Other(err.context(format!("{} is malformed", args.input.display())))
+    })?;
+
+    Ok(Report::VerifyInclusion(VerifyInclusionReport {
+        input: args.input,
+        inclusion_proof,
+        root,
+        verified,
+    }))
+}
+
+/// Check that each proof's prior root is the previous proof's root, so the proofs attest to one
+/// unbroken sequence of state transitions, optionally verifying each of them too.
+fn chain_check(prover: &Prover, args: ChainCheckArgs) -> Result<Report, Error> {
+    let vk = match (&args.vk_path, args.verify) {
+        (Some(vk_path), _) => Some(load_vk_from_json(vk_path)?),
+        (None, true) => Some(prover.client.setup(FIBONACCI_ELF).1),
+        (None, false) => None,
+    };
+
+    let proof_paths = match &args.manifest {
+        Some(manifest) => Manifest::load_or_default(manifest)
+            .map_err(Error::io(format!(
+                "failed to read the manifest {}",
+                manifest.display()
+            )))?
+            .proofs
+            .into_iter()
+            .map(|entry| entry.proof_path)
+            .collect(),
+        None => args.proof_paths,
+    };
+
+    let mut proofs: Vec<ChainedProof> = Vec::new();
+    for proof_path in proof_paths {
+        let mut proof = load_proof(&proof_path)?;
+        let bytes = proof.public_values.as_slice();
+        let public_values = PublicValues::decode(bytes).map_err(|err| {
+            Error::invalid_proof_file(&proof_path, UndecodablePublicValues::new(bytes, err))
+        })?;
+        let linked = match proofs.last() {
+            Some(previous) => public_values.prior_root.as_ref() == Some(&previous.root),
+            None => true,
+        };
+        let verified = vk.as_ref().map(|vk| {
+            proof.stdin = sp1_sdk::SP1Stdin::default();
+            prover.client.verify(&proof, vk).is_ok()
+        });
+        proofs.push(ChainedProof {
+            proof_path,
+            prior_root: public_values.prior_root,
+            root: public_values.root,
+            linked,
+            verified,
+        });
+    }
+
+    Ok(Report::ChainCheck(ChainCheckReport { proofs }))
+}
+
+/// Prove the next step of the chain recorded in the manifest, from the root the previous proof
+/// ended at, and record it in the manifest.
+fn append(prover: &Prover, config: &Config, mut args: AppendArgs) -> Result<Report, Error> {
+    let mut manifest = Manifest::load_or_default(&args.manifest).map_err(Error::io(format!(
+        "failed to read the manifest {}",
+        args.manifest.display()
+    )))?;
+    let tree_db = args.prove.tree_db.clone().ok_or_else(|| {
+        Error::Other(anyhow::anyhow!(
+            "append needs the tree the chain is over, pass --tree-db"
+        ))
+    })?;
+
+    // The next proof starts from the tree's current root, so check that it's where the previous
+    // proof left it, rather than recording a proof that doesn't pick up from it.
+    let previous_root = match &args.previous {
+        Some(previous) => {
+            let proof = load_proof(previous)?;
+            let bytes = proof.public_values.as_slice();
+            let public_values = PublicValues::decode(bytes).map_err(|err| {
+                Error::invalid_proof_file(previous, UndecodablePublicValues::new(bytes, err))
+            })?;
+            Some(public_values.root)
+        }
+        None => manifest.root().map(str::to_string),
+    };
+    let root = if tree_db.exists() {
+        HostTree::open_existing(&tree_db)
+            .and_then(|tree| tree.root())
+            .map_err(Error::Other)?
+            .map(hex::encode)
+    } else {
+        None
+    };
+    if let Some(previous_root) = previous_root {
+        if root.as_ref() != Some(&previous_root) {
+            return Err(Error::Other(anyhow::anyhow!(
+                "the tree {} is at root {}, not at {} where the previous proof ended",
+                tree_db.display(),
+                root.as_deref().unwrap_or("empty"),
+                previous_root
+            )));
+        }
+    }
+
+    if args.prove.proof_path.as_deref().is_some_and(is_stdio) {
+        return Err(Error::Other(anyhow::anyhow!(
+            "the manifest can't record a proof written to stdout, pass a file as --proof-path"
+        )));
+    }
+    if args.prove.proof_path.is_none() && config.proof_path.is_none() {
+        let file_name = format!(
+            "proof-{}.{}",
+            manifest.proofs.len() + 1,
+            args.prove.format.extension()
         );
+        args.prove.proof_path = Some(args.manifest.with_file_name(file_name));
     }
+    let report = match prove(prover, config, args.prove)? {
+        Report::Prove(report) => report,
+        _ => unreachable!("prove reports a ProveReport"),
+    };
+
+    // The proof was checked to start from the tree's root and to end at the one it moved on to.
+    let proof = &report.proofs[0];
+    let bytes = hex::decode(&proof.public_values).expect("encoded with hex");
+    let public_values = PublicValues::decode(&bytes).map_err(Error::public_values(&bytes))?;
+    let entry = ManifestEntry {
+        proof_path: proof.proof_path.clone(),
+        prior_root: public_values.prior_root.unwrap_or_default(),
+        root: public_values.root,
+    };
+    manifest.proofs.push(entry.clone());
+    manifest.save(&args.manifest).map_err(Error::io(format!(
+        "failed to write the manifest {}",
+        args.manifest.display()
+    )))?;
+
+    Ok(Report::Append(AppendReport {
+        manifest: args.manifest,
+        position: manifest.proofs.len(),
+        entry,
+        prove: report,
+    }))
 }
 
-// save `proof` to disk
-fn save_proof_to_json(proof: &sp1_sdk::SP1ProofWithPublicValues) -> std::io::Result<()> {
-    // Open the file in write mode
-    let mut file = File::create("proof.json")?;
+/// Aggregate compressed proofs of the program into one proof of the aggregator program, which
+/// verifies every one of them and commits a digest of their public values.
+fn aggregate(prover: &Prover, args: AggregateArgs) -> Result<Report, Error> {
+    let (_, vk) = prover.client.setup(FIBONACCI_ELF);
+    let (pk, aggregator_vk) = prover.client.setup(AGGREGATOR_ELF);
 
-    // Serialize the proof to a JSON string
-    let proof_json = serde_json::to_string(&proof).expect("Failed to serialize proof");
+    // The aggregator reads the program's verification key and the public values of every proof,
+    // and verifies the proofs themselves as deferred proofs.
+    let mut stdin = SP1Stdin::new();
+    let mut public_values = Vec::new();
+    for proof_path in &args.proof_paths {
+        let proof = load_proof(proof_path)?;
+        let SP1Proof::Compressed(compressed) = proof.proof else {
+            return Err(Error::invalid_proof_file(
+                proof_path,
+                "only compressed proofs can be aggregated, prove with --proof-system compressed",
+            ));
+        };
+        stdin.write_proof(compressed, vk.vk.clone());
+        public_values.push(proof.public_values.to_vec());
+    }
+    stdin.write(&vk.hash_u32());
+    stdin.write(&public_values);
 
-    // Write the serialized JSON to the file
-    file.write_all(proof_json.as_bytes())?;
+    let (proof, timings) = prover
+        .aggregate(&pk, stdin, args.proof_system)
+        .map_err(Error::Proving)?;
+    let cycles = timings.map(|timings| timings.cycles);
+    let metadata = ProofMetadata::new(&aggregator_vk, AGGREGATOR_ELF, prover.mode, cycles);
+    let envelope = ProofEnvelope::new(proof, metadata);
+    let saved = save_proof(
+        &envelope,
+        &args.proof_path,
+        args.format,
+        args.compress,
+        args.force,
+    );
+    saved.map_err(Error::io(format!(
+        "failed to save proof to {}",
+        args.proof_path.display()
+    )))?;
+    let proof = envelope.proof;
 
-    println!("Proof saved to proof.json");
-    Ok(())
+    // Check that the aggregated proof covers exactly the given proofs, in order.
+    let bytes = proof.public_values.as_slice();
+    let aggregated = AggregatedPublicValuesStruct::abi_decode(bytes, true)
+        .map_err(Error::public_values(bytes))?;
+    let covered = verify_aggregation(
+        &aggregated,
+        &vk.hash_u32(),
+        public_values.iter().map(Vec::as_slice),
+    );
+
+    Ok(Report::Aggregate(AggregateReport {
+        proof_path: args.proof_path,
+        proof_system: args.proof_system,
+        proofs: args.proof_paths,
+        vkey: vk.bytes32(),
+        aggregator_vkey: aggregator_vk.bytes32(),
+        public_values_digest: aggregated.public_values_digest.to_string(),
+        public_values: hex::encode(bytes),
+        covered,
+        timings,
+    }))
 }
 
-fn load_proof_from_json() -> sp1_sdk::SP1ProofWithPublicValues {
-    let file = File::open("proof.json").expect("Failed to open proof file");
-    let proof: sp1_sdk::SP1ProofWithPublicValues =
-        serde_json::from_reader(file).expect("Failed to deserialize proof");
-    proof
+/// Wrap a compressed proof with PLONK or Groth16 and save it to disk.
+fn wrap(prover: &Prover, args: WrapArgs) -> Result<Report, Error> {
+    let proof_path = args
+        .proof_path
+        .unwrap_or_else(|| args.proof_system.default_proof_path(args.format));
+    check_output_path(&proof_path, args.force).map_err(Error::io("refusing to start wrapping"))?;
+
+    let ProofEnvelope {
+        metadata,
+        vk,
+        proof: compressed,
+        ..
+    } = load_envelope(&args.compressed_path)?;
+    if !matches!(compressed.proof, SP1Proof::Compressed(_)) {
+        return Err(Error::invalid_proof_file(
+            &args.compressed_path,
+            "only compressed proofs can be wrapped, prove with --stage compressed",
+        ));
+    }
+
+    let start_time = Instant::now();
+    let proof = prover
+        .wrap(compressed, args.proof_system)
+        .map_err(Error::Proving)?;
+    let wrapping_time = start_time.elapsed();
+
+    // The wrapped proof is of the same program as the compressed one, and ran for as many cycles,
+    // so it keeps its metadata and its embedded verifying key, if any, but not the environment,
+    // which wrapping changes. Compressed proofs saved without metadata are taken to be of this
+    // program.
+    let metadata = match metadata {
+        Some(metadata) => ProofMetadata {
+            created_at: current_timestamp(),
+            prover: prover.mode,
+            environment: None,
+            ..metadata
+        },
+        None => {
+            let (_, vk) = prover.client.setup(FIBONACCI_ELF);
+            ProofMetadata::new(&vk, FIBONACCI_ELF, prover.mode, None)
+        }
+    };
+    let envelope = ProofEnvelope {
+        vk,
+        ..ProofEnvelope::new(proof, metadata)
+    };
+    let saved = save_proof(
+        &envelope,
+        &proof_path,
+        args.format,
+        args.compress,
+        args.force,
+    );
+    saved.map_err(Error::io(format!(
+        "failed to save proof to {}",
+        proof_path.display()
+    )))?;
+    let proof = envelope.proof;
+
+    Ok(Report::Wrap(WrapReport {
+        compressed_path: args.compressed_path,
+        proof_path,
+        proof_system: args.proof_system,
+        public_values: hex::encode(proof.public_values.as_slice()),
+        wrapping_secs: wrapping_time.as_secs_f64(),
+    }))
+}
+
+/// Run a `tree` subcommand over the tree persisted on the host.
+fn tree(args: TreeArgs) -> Result<Report, Error> {
+    let mut tree = HostTree::open_existing(&args.tree_db).map_err(Error::Other)?;
+    match args.command {
+        TreeCommand::Export(export) => tree_export(&tree, args.tree_db, export),
+        TreeCommand::Ls(ls) => tree_ls(&mut tree, args.tree_db, ls),
+        TreeCommand::Get(get) => tree_get(&mut tree, args.tree_db, get),
+    }
+}
+
+/// List the entries of the tree under the given root, checking that they're all of them.
+fn tree_ls(tree: &mut HostTree, tree_db: PathBuf, args: TreeLsArgs) -> Result<Report, Error> {
+    let root = args.root.resolve(tree).map_err(Error::Other)?;
+    let (entries, complete) = tree.entries(root.as_ref()).map_err(Error::Other)?;
+    Ok(Report::TreeLs(TreeLsReport {
+        tree_db,
+        root: root.map(hex::encode),
+        entries,
+        complete,
+    }))
+}
+
+/// Look up the leaf at a key of the tree under the given root.
+fn tree_get(tree: &mut HostTree, tree_db: PathBuf, args: TreeGetArgs) -> Result<Report, Error> {
+    let root = args.root.resolve(tree).map_err(Error::Other)?;
+    let leaf = tree.get(root.as_ref(), &args.key).map_err(Error::Other)?;
+    Ok(Report::TreeGet(TreeGetReport {
+        tree_db,
+        root: root.map(hex::encode),
+        key: hex::encode(args.key),
+        leaf: leaf.map(hex::encode),
+    }))
+}
+
+/// Export a snapshot of the tree, checking that it has all of the tree's entries.
+fn tree_export(tree: &HostTree, tree_db: PathBuf, args: TreeExportArgs) -> Result<Report, Error> {
+    let (snapshot, complete) = tree.snapshot().map_err(Error::Other)?;
+    let encoded = snapshot.encode(args.format);
+    if let Some(output) = &args.output {
+        check_output_path(output, args.force)
+            .and_then(|()| std::fs::write(output, &encoded))
+            .map_err(Error::io(format!(
+                "failed to write the snapshot to {}",
+                output.display()
+            )))?;
+    }
+
+    Ok(Report::TreeExport(TreeExportReport {
+        tree_db,
+        snapshot,
+        complete,
+        format: args.format,
+        encoded: args.output.is_none().then_some(encoded),
+        output: args.output,
+    }))
 }
@@ -0,0 +1,84 @@
+//! A cache of the proofs `prove` generates, keyed by everything that determines them, so proving
+//! the same inputs to the same program with the same proof system again reuses the earlier proof
+//! rather than spending minutes regenerating it.
+
+use std::path::{Path, PathBuf};
+
+use fibonacci_lib::{FibonacciInputs, TreeWitness};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::Error,
+    inputs::to_stdin,
+    proof::{load_envelope, save_proof, ProofEnvelope, ProofFormat, ProofSystem},
+};
+
+/// A directory of cached proofs, each saved to `<key>.bin` for its [`cache_key`].
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+/// The hex-encoded SHA-256 hash of the ELF of the program, the bytes written to its stdin for
+/// `inputs` and `witness` and the proof system, which together determine what a proof proves.
+///
+/// With `default_timestamp`, the timestamp of `inputs` is the time they're proven at rather than
+/// one asked for, so it's left out of the key: proving the same inputs again reuses the proof, and
+/// with it the timestamp it was first proven at.
+pub fn cache_key(
+    elf: &[u8],
+    inputs: &FibonacciInputs,
+    witness: &TreeWitness,
+    system: ProofSystem,
+    default_timestamp: bool,
+) -> String {
+    let stdin = if default_timestamp {
+        let inputs = FibonacciInputs {
+            timestamp: 0,
+            ..inputs.clone()
+        };
+        to_stdin(&inputs, witness)
+    } else {
+        to_stdin(inputs, witness)
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(elf));
+    // Prefix each buffer with its length, so moving bytes between buffers changes the key.
+    for buffer in &stdin.buffer {
+        hasher.update((buffer.len() as u64).to_le_bytes());
+        hasher.update(buffer);
+    }
+    hasher.update(format!("{:?}", system));
+    // Keep the proofs of inputs given a timestamp of 0 apart from the ones given none.
+    if default_timestamp {
+        hasher.update("default timestamp");
+    }
+    hex::encode(hasher.finalize())
+}
+
+impl ProofCache {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// The file the proof with `key` is cached in.
+    pub fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.bin", key))
+    }
+
+    /// The cached proof with `key`, if there is one.
+    pub fn get(&self, key: &str) -> Result<Option<ProofEnvelope>, Error> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        load_envelope(&path).map(Some)
+    }
+
+    /// Cache the proof in `envelope` with `key`, compressed, as it's only ever read back by the
+    /// script.
+    pub fn insert(&self, key: &str, envelope: &ProofEnvelope) -> std::io::Result<()> {
+        save_proof(envelope, &self.path(key), ProofFormat::Bincode, true, true)
+    }
+}
@@ -0,0 +1,37 @@
+//! The `prover.toml` configuration file.
+//!
+//! Every value in the file is optional, and values given on the command line take precedence
+//! over the file. For example:
+//! ```toml
+//! n = 30
+//! offset = 5
+//! proof-system = "plonk"
+//! proof-path = "proofs/fib-30.json"
+//! prover = "network"
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{proof::ProofSystem, prover::ProverMode};
+
+/// The values that can be set in the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub n: Option<u32>,
+    pub offset: Option<u32>,
+    pub proof_system: Option<ProofSystem>,
+    pub proof_path: Option<PathBuf>,
+    pub prover: Option<ProverMode>,
+}
+
+impl Config {
+    /// Load the configuration file at `path`.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
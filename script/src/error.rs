@@ -0,0 +1,141 @@
+//! The errors a command can fail with, and the exit codes they're reported with.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use alloy_sol_types::SolStruct;
+use fibonacci_lib::{InclusionProofStruct, PublicValuesEncoding, PublicValuesStruct};
+
+/// An error that stops a command from completing.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Generating a proof, or downloading it from the prover network, failed.
+    #[error("failed to generate proof: {0:#}")]
+    Proving(anyhow::Error),
+
+    /// A proof didn't verify against the verifying key.
+    #[error("failed to verify proof: {0}")]
+    Verification(String),
+
+    /// A proof or verifying key file was read, but isn't a valid proof or verifying key.
+    #[error("invalid proof file {}: {reason}", path.display())]
+    InvalidProofFile { path: PathBuf, reason: String },
+
+    /// Reading or writing a file failed.
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Executing the program failed.
+    #[error("failed to execute the program: {0}")]
+    Execution(String),
+
+    /// The public values couldn't be decoded.
+    #[error("failed to decode the public values: {0}")]
+    PublicValues(Box<UndecodablePublicValues>),
+
+    /// The program committed different public values than the ones computed on the host.
+    #[error("the public values don't match the ones computed on the host")]
+    ValuesMismatch,
+
+    /// The proofs compared with `diff` differ.
+    #[error("the proofs differ")]
+    ProofsDiffer,
+
+    /// The proofs checked with `chain-check` don't pick up the tree where the previous one left it.
+    #[error("the proofs don't form a chain")]
+    BrokenChain,
+
+    /// A generated file checked with `gen-sol --check` doesn't match what would be generated.
+    #[error("{} is out of date", .0.display())]
+    OutOfDate(PathBuf),
+
+    /// Any other error, e.g. invalid arguments.
+    #[error("{0:#}")]
+    Other(anyhow::Error),
+}
+
+impl Error {
+    /// The process exit code to report this error with.
+    ///
+    /// Exit code 2 is left to clap, which exits with it on invalid command lines.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Proving(_) => 3,
+            Error::Verification(_) => 4,
+            Error::InvalidProofFile { .. } => 5,
+            Error::Io { .. } => 6,
+            Error::Execution(_)
+            | Error::PublicValues(_)
+            | Error::ValuesMismatch
+            | Error::ProofsDiffer
+            | Error::BrokenChain
+            | Error::OutOfDate(_)
+            | Error::Other(_) => 1,
+        }
+    }
+
+    /// Wrap an IO error with a description of what was being done, for use with `map_err`.
+    pub fn io(context: impl Into<String>) -> impl FnOnce(std::io::Error) -> Self {
+        let context = context.into();
+        move |source| Error::Io { context, source }
+    }
+
+    /// Wrap an error decoding the public values `bytes`, for use with `map_err`.
+    pub fn public_values(bytes: &[u8]) -> impl FnOnce(alloy_sol_types::Error) -> Self + '_ {
+        move |err| Error::PublicValues(Box::new(UndecodablePublicValues::new(bytes, err)))
+    }
+
+    /// An error for a file at `path` that can't be deserialized.
+    pub fn invalid_proof_file(path: &Path, reason: impl ToString) -> Self {
+        Error::InvalidProofFile {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Public values that couldn't be decoded, displayed with their length, a hex dump and the layout
+/// they were expected to have, to tell what the program actually committed.
+#[derive(Debug)]
+pub struct UndecodablePublicValues {
+    pub reason: alloy_sol_types::Error,
+    pub bytes: Vec<u8>,
+}
+
+impl UndecodablePublicValues {
+    pub fn new(bytes: &[u8], reason: alloy_sol_types::Error) -> Self {
+        Self {
+            reason,
+            bytes: bytes.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for UndecodablePublicValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.reason)?;
+        writeln!(f, "got {} bytes:", self.bytes.len())?;
+        // One ABI word per line, so the fields of ABI-encoded public values line up.
+        for (index, word) in self.bytes.chunks(32).enumerate() {
+            writeln!(f, "  {:#06x}: {}", index * 32, hex::encode(word))?;
+        }
+        writeln!(f, "expected {}", PublicValuesStruct::eip712_root_type())?;
+        writeln!(
+            f,
+            "  ({} bytes ABI-encoded, or {} bytes Borsh- or SSZ-encoded),",
+            PublicValuesEncoding::Abi.struct_size(),
+            PublicValuesEncoding::Borsh.struct_size()
+        )?;
+        write!(
+            f,
+            "followed by the ABI-encoded {}",
+            InclusionProofStruct::eip712_root_type()
+        )
+    }
+}
@@ -0,0 +1,52 @@
+//! Rough estimates of how long and how much it takes to prove an execution.
+//!
+//! The numbers are ballpark figures meant to help decide whether proving is worth it, not
+//! benchmarks: actual proving times depend heavily on the hardware, and network prices change.
+//! Every constant can be overridden from the command line.
+
+use crate::proof::ProofSystem;
+
+/// The parameters used to turn a cycle count into proving estimates.
+#[derive(Clone, Copy, Debug)]
+pub struct CostModel {
+    /// How many cycles per second the local machine proves in the core (shard proving) phase.
+    pub cycles_per_sec: f64,
+    /// The fixed time it takes to compress the shard proofs into a single proof, in seconds.
+    pub compress_secs: f64,
+    /// The fixed time it takes to wrap a compressed proof into a Groth16 proof, in seconds.
+    pub groth16_secs: f64,
+    /// The fixed time it takes to wrap a compressed proof into a PLONK proof, in seconds.
+    pub plonk_secs: f64,
+    /// The approximate price of proving a billion cycles on the prover network, in USD.
+    pub network_usd_per_billion_cycles: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            cycles_per_sec: 1_000_000.0,
+            compress_secs: 60.0,
+            groth16_secs: 120.0,
+            plonk_secs: 300.0,
+            network_usd_per_billion_cycles: 1.0,
+        }
+    }
+}
+
+impl CostModel {
+    /// Estimate how many seconds it takes to prove `cycles` locally with `system`.
+    pub fn local_proving_secs(&self, cycles: u64, system: ProofSystem) -> f64 {
+        let core_secs = cycles as f64 / self.cycles_per_sec;
+        match system {
+            ProofSystem::Core => core_secs,
+            ProofSystem::Compressed => core_secs + self.compress_secs,
+            ProofSystem::Groth16 => core_secs + self.compress_secs + self.groth16_secs,
+            ProofSystem::Plonk => core_secs + self.compress_secs + self.plonk_secs,
+        }
+    }
+
+    /// Estimate how much it costs to prove `cycles` on the prover network, in USD.
+    pub fn network_cost_usd(&self, cycles: u64) -> f64 {
+        cycles as f64 / 1e9 * self.network_usd_per_billion_cycles
+    }
+}
@@ -0,0 +1,69 @@
+//! Fixtures for testing the verification of SP1 zkVM proofs inside Solidity.
+
+use std::path::{Path, PathBuf};
+
+use fibonacci_lib::PublicValuesStruct;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::proof::ProofSystem;
+
+/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SP1FibonacciProofFixture {
+    pub a: u64,
+    pub b: u64,
+    pub n: u32,
+    /// The offset, or zero if it was hidden behind `offset_hash`.
+    pub offset: u32,
+    pub offset_hash: String,
+    pub vkey: String,
+    pub public_values: String,
+    pub proof: String,
+}
+
+impl SP1FibonacciProofFixture {
+    /// Create a fixture for the given proof.
+    pub fn new(
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+    ) -> Result<Self, alloy_sol_types::Error> {
+        // Deserialize the public values.
+        let bytes = proof.public_values.as_slice();
+        let PublicValuesStruct {
+            n,
+            offset,
+            offset_hash,
+            a,
+            b,
+            ..
+        } = PublicValuesStruct::try_from(bytes)?;
+
+        Ok(Self {
+            a,
+            b,
+            n,
+            offset,
+            offset_hash: offset_hash.to_string(),
+            vkey: vk.bytes32().to_string(),
+            public_values: format!("0x{}", hex::encode(bytes)),
+            proof: format!("0x{}", hex::encode(proof.bytes())),
+        })
+    }
+
+    /// Save the fixture to `path`, creating its parent directories if needed.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// The file the Foundry tests in `contracts/` load the fixture for `system` from.
+pub fn default_fixture_path(system: ProofSystem) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../contracts/src/fixtures")
+        .join(format!("{:?}-fixture.json", system).to_lowercase())
+}
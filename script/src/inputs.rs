@@ -0,0 +1,46 @@
+//! Writing the program's inputs to stdin, and reading them from files.
+
+use std::{
+    fs::File,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use fibonacci_lib::{FibonacciInputs, TreeWitness};
+use serde::de::DeserializeOwned;
+use sp1_sdk::SP1Stdin;
+
+/// Write the inputs and the witness of the tree to insert into to a new [`SP1Stdin`], in the form
+/// the program reads them.
+///
+/// An empty witness has the program build the tree from scratch.
+pub fn to_stdin(inputs: &FibonacciInputs, witness: &TreeWitness) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(inputs);
+    stdin.write(witness);
+    stdin
+}
+
+/// The current time in seconds since the Unix epoch, the default timestamp committed by the
+/// program.
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+/// Load the inputs from a JSON file, e.g. `{"n": 20, "offset": 0}`.
+pub fn load_inputs_file(path: &Path) -> std::io::Result<FibonacciInputs> {
+    load_json(path)
+}
+
+/// Load a list of inputs from a JSON file, e.g. `[{"n": 10, "offset": 0}, {"n": 20, "offset": 0}]`.
+pub fn load_batch_file(path: &Path) -> std::io::Result<Vec<FibonacciInputs>> {
+    load_json(path)
+}
+
+fn load_json<T: DeserializeOwned>(path: &Path) -> std::io::Result<T> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
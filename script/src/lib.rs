@@ -0,0 +1,23 @@
+//! Host-side helpers shared by the fibonacci scripts.
+
+pub mod cache;
+pub mod config;
+pub mod error;
+pub mod estimate;
+pub mod fixture;
+pub mod inputs;
+pub mod logger;
+pub mod manifest;
+pub mod proof;
+pub mod prover;
+pub mod report;
+pub mod schema;
+pub mod solidity;
+pub mod store;
+pub mod tree;
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const FIBONACCI_ELF: &[u8] = include_bytes!("../../elf/riscv32im-succinct-zkvm-elf");
+
+/// The ELF of the aggregator program, which verifies many compressed fibonacci proofs at once.
+pub const AGGREGATOR_ELF: &[u8] = include_bytes!("../../elf/aggregator-elf");
@@ -0,0 +1,277 @@
+//! Setting up logging, and the proving progress bar fed by the SP1 SDK's tracing spans.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use tracing::{span, Subscriber};
+use tracing_subscriber::{
+    filter::EnvFilter,
+    fmt::format::FmtSpan,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+use crate::report::PhaseTimings;
+
+/// How much is logged to stderr on top of the results of a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Nothing but errors, no logs, no progress bar and no status messages.
+    Quiet,
+    /// Status messages and the progress bar, and logs only as configured with `RUST_LOG`.
+    Normal,
+    /// Also the SP1 SDK's info logs, e.g. the time spent in each proving step.
+    Verbose,
+    /// Also the SP1 SDK's debug logs, with the module each log comes from.
+    Debug,
+}
+
+impl Verbosity {
+    /// The verbosity for the `--quiet` flag and the number of `-v` flags given.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+
+    /// Whether status messages, like the ID of a prover network request, are left out.
+    pub fn is_quiet(self) -> bool {
+        self == Verbosity::Quiet
+    }
+}
+
+/// Setup the logger like [`sp1_sdk::utils::setup_logger`] does, additionally feeding the SP1
+/// SDK's tracing spans to `progress`, if any.
+///
+/// `-v` flags take precedence over `RUST_LOG`, and `--quiet` turns logging off entirely.
+pub fn setup_logger(verbosity: Verbosity, progress: Option<&Progress>) {
+    let env_filter = match verbosity {
+        Verbosity::Quiet => EnvFilter::new("off"),
+        Verbosity::Normal => {
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("off"))
+        }
+        Verbosity::Verbose => EnvFilter::new("info"),
+        Verbosity::Debug => EnvFilter::new("debug"),
+    };
+    let env_filter = env_filter
+        .add_directive("hyper=off".parse().unwrap())
+        .add_directive("p3_keccak_air=off".parse().unwrap())
+        .add_directive("p3_fri=off".parse().unwrap())
+        .add_directive("p3_dft=off".parse().unwrap())
+        .add_directive("p3_challenger=off".parse().unwrap());
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .compact()
+        .with_file(false)
+        .with_target(verbosity == Verbosity::Debug)
+        .with_thread_names(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .with_filter(env_filter);
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(progress.map(Progress::layer))
+        .init();
+}
+
+/// Estimate how many shards the prover splits an execution of `cycles` cycles into.
+pub fn estimate_shards(cycles: u64) -> u64 {
+    // The prover's default shard size, which can be overridden with `SHARD_SIZE`.
+    let shard_size = std::env::var("SHARD_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(1u64 << 22);
+    cycles.div_ceil(shard_size).max(1)
+}
+
+/// The phases of generating a proof, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    Waiting,
+    Core,
+    Compress,
+    Wrap,
+}
+
+impl Phase {
+    /// Map the name of a span opened by the SP1 prover to the phase it starts, if any.
+    ///
+    /// The SDK has no progress callbacks, so this relies on the names the prover instruments its
+    /// top-level functions with.
+    fn from_span_name(name: &str) -> Option<Self> {
+        match name {
+            "prove_core" => Some(Phase::Core),
+            "compress" | "shrink" => Some(Phase::Compress),
+            name if name.starts_with("wrap")
+                || name.contains("groth16")
+                || name.contains("plonk") =>
+            {
+                Some(Phase::Wrap)
+            }
+            _ => None,
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Phase::Waiting => "setting up",
+            Phase::Core => "proving shards (core)",
+            Phase::Compress => "compressing (compress)",
+            Phase::Wrap => "wrapping (wrap)",
+        }
+    }
+}
+
+/// The current proving phase, and how long each phase took so far.
+struct PhaseClock {
+    phase: Phase,
+    since: Instant,
+    elapsed: [Duration; 4],
+}
+
+impl PhaseClock {
+    fn new() -> Self {
+        Self {
+            phase: Phase::Waiting,
+            since: Instant::now(),
+            elapsed: [Duration::ZERO; 4],
+        }
+    }
+
+    /// Move on to `phase`, returning whether it's later than the current phase.
+    fn advance(&mut self, phase: Phase) -> bool {
+        if phase <= self.phase {
+            return false;
+        }
+        self.stop();
+        self.phase = phase;
+        true
+    }
+
+    /// Add the time spent in the current phase up to now.
+    fn stop(&mut self) {
+        let now = Instant::now();
+        self.elapsed[self.phase as usize] += now - self.since;
+        self.since = now;
+    }
+
+    fn timings(&self) -> PhaseTimings {
+        let secs = |phase: Phase| self.elapsed[phase as usize].as_secs_f64();
+        PhaseTimings {
+            cycles: 0,
+            execution_secs: 0.0,
+            core_secs: secs(Phase::Core),
+            compress_secs: secs(Phase::Compress),
+            wrap_secs: secs(Phase::Wrap),
+        }
+    }
+}
+
+/// A progress bar showing the proving phase, the shards proven so far and an ETA.
+///
+/// Whether or not the progress bar is drawn, it also times the proving phases.
+#[derive(Clone)]
+pub struct Progress {
+    bar: ProgressBar,
+    clock: Arc<Mutex<PhaseClock>>,
+}
+
+impl Progress {
+    /// Create a progress bar drawn to stderr, or a hidden one if `enabled` isn't set.
+    pub fn new(enabled: bool) -> Self {
+        let target = if enabled {
+            ProgressDrawTarget::stderr()
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        let bar = ProgressBar::with_draw_target(None, target);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{spinner} [{elapsed_precise}] {msg:24} [{bar:30}] {pos}/{len} shards (ETA {eta})",
+            )
+            .expect("invalid progress bar template")
+            .progress_chars("=> "),
+        );
+        Self {
+            bar,
+            clock: Arc::new(Mutex::new(PhaseClock::new())),
+        }
+    }
+
+    /// Whether the progress bar is drawn at all.
+    pub fn is_enabled(&self) -> bool {
+        !self.bar.is_hidden()
+    }
+
+    /// Start showing progress for proving an execution split into `shards` shards.
+    pub fn start(&self, shards: u64) {
+        *self.clock.lock().unwrap() = PhaseClock::new();
+        self.bar.reset();
+        self.bar.set_length(shards);
+        self.bar.set_message(Phase::Waiting.message());
+        self.bar.enable_steady_tick(Duration::from_millis(200));
+    }
+
+    /// Stop showing progress and clear the progress bar, returning how long each phase took
+    /// since [`Progress::start`].
+    pub fn finish(&self) -> PhaseTimings {
+        self.bar.finish_and_clear();
+        let mut clock = self.clock.lock().unwrap();
+        clock.stop();
+        clock.timings()
+    }
+
+    fn layer(&self) -> ProgressLayer {
+        ProgressLayer {
+            bar: self.bar.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+/// A tracing layer advancing the progress bar as the prover opens and closes spans.
+struct ProgressLayer {
+    bar: ProgressBar,
+    clock: Arc<Mutex<PhaseClock>>,
+}
+
+impl<S> Layer<S> for ProgressLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, _id: &span::Id, _ctx: Context<'_, S>) {
+        let Some(phase) = Phase::from_span_name(attrs.metadata().name()) else {
+            return;
+        };
+        // Nested spans can share names with earlier phases, so never move backwards.
+        let mut clock = self.clock.lock().unwrap();
+        let previous = clock.phase;
+        if clock.advance(phase) {
+            if previous == Phase::Core {
+                // All shards are proven once the core phase is over.
+                self.bar.set_position(self.bar.length().unwrap_or(0));
+            }
+            self.bar.set_message(phase.message());
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let in_core = self.clock.lock().unwrap().phase == Phase::Core;
+        if let Some(span) = ctx.span(&id) {
+            if in_core && span.name().contains("shard") {
+                // Don't run past the estimated number of shards.
+                if self.bar.position() + 1 < self.bar.length().unwrap_or(0) {
+                    self.bar.inc(1);
+                }
+            }
+        }
+    }
+}
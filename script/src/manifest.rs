@@ -0,0 +1,47 @@
+//! The manifest of a chain of proofs built with `append`, recording the history of a tree.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// The proofs of a chain, in the order they were generated, each picking up the tree where the
+/// previous one left it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub proofs: Vec<ManifestEntry>,
+}
+
+/// A proof recorded in a [`Manifest`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEntry {
+    pub proof_path: PathBuf,
+    /// The hex-encoded root of the tree the proof started from.
+    pub prior_root: String,
+    /// The hex-encoded root of the tree the proof ended at.
+    pub root: String,
+}
+
+impl Manifest {
+    /// Load the manifest at `path`, or start an empty one if there is none yet.
+    pub fn load_or_default(path: &Path) -> std::io::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// The hex-encoded root the last proof of the chain ended at, if any.
+    pub fn root(&self) -> Option<&str> {
+        self.proofs.last().map(|entry| entry.root.as_str())
+    }
+}
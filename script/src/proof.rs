@@ -0,0 +1,417 @@
+//! Selecting proof systems and saving/loading proofs to/from disk.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{HashableKey, SP1Proof, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::{error::Error, inputs::current_timestamp, prover::ProverMode, report::PublicValues};
+
+/// Enum representing the available proof systems
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofSystem {
+    Core,
+    Compressed,
+    Plonk,
+    Groth16,
+}
+
+impl ProofSystem {
+    /// Detect the proof system a proof was generated with.
+    pub fn of(proof: &SP1ProofWithPublicValues) -> Self {
+        match proof.proof {
+            SP1Proof::Core(_) => ProofSystem::Core,
+            SP1Proof::Compressed(_) => ProofSystem::Compressed,
+            SP1Proof::Plonk(_) => ProofSystem::Plonk,
+            SP1Proof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+
+    /// The file a proof of this system is saved to when no `--proof-path` is given.
+    ///
+    /// Core and compressed proofs can't be verified on-chain, so they get their own extension to
+    /// keep them from being mistaken for (or overwriting) an EVM-compatible `proof.json`.
+    pub fn default_proof_path(self, format: ProofFormat) -> PathBuf {
+        let extension = format.extension();
+        match self {
+            ProofSystem::Core => PathBuf::from(format!("proof.core.{}", extension)),
+            ProofSystem::Compressed => PathBuf::from(format!("proof.compressed.{}", extension)),
+            ProofSystem::Plonk | ProofSystem::Groth16 => {
+                PathBuf::from(format!("proof.{}", extension))
+            }
+        }
+    }
+}
+
+/// Enum representing the available proof file formats
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofFormat {
+    /// Human-readable, but large and slow to parse.
+    #[default]
+    Json,
+    /// Compact binary encoding.
+    Bincode,
+}
+
+impl ProofFormat {
+    /// The file extension for proofs in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ProofFormat::Json => "json",
+            ProofFormat::Bincode => "bin",
+        }
+    }
+
+    /// Detect the format of a serialized proof.
+    ///
+    /// A JSON proof is an object, so it starts with `{`. A bincode proof starts with
+    /// [`ENVELOPE_MAGIC`], or, saved before proofs had an envelope, with the variant index of
+    /// [`SP1Proof`] as a little-endian u32, neither of whose first byte is `{`.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') => ProofFormat::Json,
+            _ => ProofFormat::Bincode,
+        }
+    }
+}
+
+/// The layout version of [`ProofEnvelope`].
+pub const ENVELOPE_VERSION: u32 = 3;
+
+/// The bytes bincode-encoded proof envelopes start with, to tell them apart from bare proofs saved
+/// before there were envelopes, whose first bytes could pass for the envelope's version.
+pub const ENVELOPE_MAGIC: &[u8; 8] = b"FIBPROOF";
+
+/// The bytes zstd frames start with, which tell proof files saved with `--compress` apart from
+/// uncompressed ones, which start with `{` or [`ENVELOPE_MAGIC`].
+pub const ZSTD_MAGIC: &[u8; 4] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// What a proof file records about the proof besides the proof itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofMetadata {
+    /// The bytes32 hash of the verifying key of the program the proof is of, see
+    /// [`HashableKey::bytes32`].
+    pub vkey_hash: String,
+    /// The hex-encoded SHA-256 hash of the ELF of the program the proof is of.
+    pub elf_hash: String,
+    /// The version of the script that generated the proof.
+    pub program_version: String,
+    /// When the proof was generated, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// The prover that generated the proof.
+    pub prover: ProverMode,
+    /// How many cycles executing the program took, or `None` if the proof wasn't generated
+    /// locally.
+    pub cycles: Option<u64>,
+    /// The environment the proof was generated in, recorded by `prove --deterministic`.
+    #[serde(default)]
+    pub environment: Option<ProofEnvironment>,
+}
+
+/// The environment variables SP1 reads the settings that shape its proofs from.
+pub const PROVER_PARAMETERS: [&str; 6] = [
+    "SHARD_SIZE",
+    "SHARD_BATCH_SIZE",
+    "SHARD_CHUNKING_MULTIPLIER",
+    "SPLIT_THRESHOLD",
+    "RECONSTRUCT_COMMITMENTS",
+    "FRI_QUERIES",
+];
+
+/// What a proof was generated with besides its inputs, so operators comparing proofs of the same
+/// inputs can tell a difference in the proofs from a difference in how they were generated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEnvironment {
+    /// The version of the SP1 circuits the proof was generated with.
+    pub sp1_circuit_version: String,
+    /// The operating system of the machine that generated the proof.
+    pub os: String,
+    /// The CPU architecture of the machine that generated the proof.
+    pub arch: String,
+    pub proof_system: ProofSystem,
+    /// The hash of the ELF, the stdin and the proof system, see
+    /// [`cache_key`](crate::cache::cache_key), which match for proofs of the same inputs.
+    pub stdin_hash: String,
+    /// The values of the [`PROVER_PARAMETERS`] that were set, by name.
+    pub parameters: BTreeMap<String, String>,
+}
+
+impl ProofEnvironment {
+    /// The environment of a proof with `proof_system` of the stdin hashing to `stdin_hash`
+    /// generated just now.
+    pub fn capture(stdin_hash: String, proof_system: ProofSystem) -> Self {
+        let parameters = PROVER_PARAMETERS
+            .iter()
+            .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+            .collect();
+        Self {
+            sp1_circuit_version: sp1_sdk::SP1_CIRCUIT_VERSION.to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            proof_system,
+            stdin_hash,
+            parameters,
+        }
+    }
+}
+
+impl ProofMetadata {
+    /// The metadata of a proof of `elf` generated just now by `prover`.
+    pub fn new(vk: &SP1VerifyingKey, elf: &[u8], prover: ProverMode, cycles: Option<u64>) -> Self {
+        Self {
+            vkey_hash: vk.bytes32(),
+            elf_hash: hex::encode(Sha256::digest(elf)),
+            program_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: current_timestamp(),
+            prover,
+            cycles,
+            environment: None,
+        }
+    }
+}
+
+/// A proof as saved to disk: the proof along with its metadata, in a versioned layout.
+#[derive(Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    /// The layout version, [`ENVELOPE_VERSION`].
+    pub version: u32,
+    /// What the file records about the proof. Saved proofs always have it, but proofs saved before
+    /// there were envelopes load without it.
+    pub metadata: Option<ProofMetadata>,
+    /// The verifying key of the program the proof is of, if it was embedded with `--embed-vk`, so
+    /// the proof can be verified from the file alone.
+    pub vk: Option<SP1VerifyingKey>,
+    pub proof: SP1ProofWithPublicValues,
+}
+
+impl ProofEnvelope {
+    pub fn new(proof: SP1ProofWithPublicValues, metadata: ProofMetadata) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            metadata: Some(metadata),
+            vk: None,
+            proof,
+        }
+    }
+
+    /// A proof saved before there were envelopes, which records nothing else.
+    fn bare(proof: SP1ProofWithPublicValues) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            metadata: None,
+            vk: None,
+            proof,
+        }
+    }
+}
+
+/// The bytes32 hash of the verifying key a PLONK or Groth16 proof is for, formatted like
+/// [`sp1_sdk::HashableKey::bytes32`].
+///
+/// The hash is the first public input of the wrapped proof. Core and compressed proofs don't have
+/// it, so `None` is returned for them.
+pub fn proof_vkey_hash(proof: &SP1ProofWithPublicValues) -> Option<String> {
+    let public_inputs = match &proof.proof {
+        SP1Proof::Plonk(proof) => &proof.public_inputs,
+        SP1Proof::Groth16(proof) => &proof.public_inputs,
+        SP1Proof::Core(_) | SP1Proof::Compressed(_) => return None,
+    };
+    let hash = BigUint::parse_bytes(public_inputs[0].as_bytes(), 10)?;
+    Some(format!("0x{:0>64}", hash.to_str_radix(16)))
+}
+
+/// The file the verifying key for the proof at `proof_path` is saved to when no `--vk-path` is
+/// given, e.g. `proof.vk.json` for `proof.json`.
+pub fn default_vk_path(proof_path: &Path) -> PathBuf {
+    proof_path.with_extension("vk.json")
+}
+
+/// The file the proof for the given inputs is saved to when proving a batch of inputs, e.g.
+/// `proof-n20-offset0.json` for `proof.json`.
+pub fn batch_proof_path(proof_path: &Path, n: u32, offset: u32) -> PathBuf {
+    let file_name = proof_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // Insert the inputs before the first dot, to keep multi-part extensions like `.core.json`.
+    let (stem, extension) = file_name.split_at(file_name.find('.').unwrap_or(file_name.len()));
+    proof_path.with_file_name(format!("{}-n{}-offset{}{}", stem, n, offset, extension))
+}
+
+/// The files the public values of the proof at `proof_path` are saved to with
+/// `--save-public-values`, raw and decoded, e.g. `proof.public_values.bin` and
+/// `proof.public_values.json` for `proof.json`.
+pub fn public_values_paths(proof_path: &Path) -> [PathBuf; 2] {
+    [
+        proof_path.with_extension("public_values.bin"),
+        proof_path.with_extension("public_values.json"),
+    ]
+}
+
+/// The proof path standing for stdout when saving a proof and for stdin when loading one, so the
+/// script can be piped into itself, e.g. `prove --proof-path - | verify --proof-path -`.
+pub const STDIO_PATH: &str = "-";
+
+/// Whether `path` is [`STDIO_PATH`].
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO_PATH)
+}
+
+/// Check that `path` can be written to, i.e. that it doesn't exist yet unless `force` is set.
+pub fn check_output_path(path: &Path, force: bool) -> std::io::Result<()> {
+    if !is_stdio(path) && path.exists() && !force {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, pass --force to overwrite it",
+                path.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// save the proof in `envelope` to disk at `path` in `format`, along with its metadata, compressed
+// with zstd if `compress` is set, creating its parent directories if needed, or write it to stdout
+// if `path` is `-`
+pub fn save_proof(
+    envelope: &ProofEnvelope,
+    path: &Path,
+    format: ProofFormat,
+    compress: bool,
+    force: bool,
+) -> std::io::Result<()> {
+    check_output_path(path, force)?;
+    if let Some(parent) = path.parent().filter(|_| !is_stdio(path)) {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Serialize the proof
+    let bytes = match format {
+        ProofFormat::Json => serde_json::to_vec(envelope).expect("Failed to serialize proof"),
+        ProofFormat::Bincode => {
+            let envelope = bincode::serialize(envelope).expect("Failed to serialize proof");
+            [ENVELOPE_MAGIC.as_slice(), &envelope].concat()
+        }
+    };
+    let bytes = if compress {
+        zstd::encode_all(bytes.as_slice(), zstd::DEFAULT_COMPRESSION_LEVEL)?
+    } else {
+        bytes
+    };
+
+    // Write the serialized proof to the file
+    if is_stdio(path) {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&bytes)?;
+        return stdout.flush();
+    }
+    let mut file = File::create(path)?;
+    file.write_all(&bytes)?;
+
+    Ok(())
+}
+
+// load a proof saved with `save_proof` in any format
+pub fn load_proof(path: &Path) -> Result<SP1ProofWithPublicValues, Error> {
+    load_envelope(path).map(|envelope| envelope.proof)
+}
+
+// load a proof saved with `save_proof` in any format, compressed or not, from `path` or from
+// stdin if it's `-`, along with what its file records about it, which proofs saved before there
+// were envelopes don't have
+pub fn load_envelope(path: &Path) -> Result<ProofEnvelope, Error> {
+    let bytes = if is_stdio(path) {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map(|_| bytes)
+            .map_err(Error::io("failed to read proof from stdin"))?
+    } else {
+        std::fs::read(path).map_err(Error::io(format!(
+            "failed to read proof file {}",
+            path.display()
+        )))?
+    };
+    let invalid = |err| Error::invalid_proof_file(path, err);
+    let bytes = if bytes.starts_with(ZSTD_MAGIC) {
+        zstd::decode_all(bytes.as_slice()).map_err(|err| invalid(err.to_string()))?
+    } else {
+        bytes
+    };
+    let envelope: ProofEnvelope = match ProofFormat::detect(&bytes) {
+        ProofFormat::Json => match serde_json::from_slice(&bytes) {
+            Ok(envelope) => envelope,
+            // Report why the file isn't an envelope, rather than why it isn't a bare proof.
+            Err(err) => match serde_json::from_slice(&bytes) {
+                Ok(proof) => return Ok(ProofEnvelope::bare(proof)),
+                Err(_) => return Err(invalid(err.to_string())),
+            },
+        },
+        ProofFormat::Bincode => match bytes.strip_prefix(ENVELOPE_MAGIC.as_slice()) {
+            Some(bytes) => bincode::deserialize(bytes).map_err(|err| invalid(err.to_string()))?,
+            None => {
+                let proof = bincode::deserialize(&bytes).map_err(|err| invalid(err.to_string()))?;
+                return Ok(ProofEnvelope::bare(proof));
+            }
+        },
+    };
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(invalid(format!(
+            "unsupported proof file version {}, expected {}",
+            envelope.version, ENVELOPE_VERSION
+        )));
+    }
+    Ok(envelope)
+}
+
+// save the raw public values of the proof at `proof_path` and them `decoded` next to it, so tools
+// only interested in what the program committed don't have to parse the proof
+pub fn save_public_values(
+    public_values: &[u8],
+    decoded: &PublicValues,
+    proof_path: &Path,
+    force: bool,
+) -> std::io::Result<Vec<PathBuf>> {
+    let [raw_path, decoded_path] = public_values_paths(proof_path);
+    check_output_path(&raw_path, force)?;
+    check_output_path(&decoded_path, force)?;
+    if let Some(parent) = proof_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&raw_path, public_values)?;
+    let file = File::create(&decoded_path)?;
+    serde_json::to_writer_pretty(file, decoded)?;
+    Ok(vec![raw_path, decoded_path])
+}
+
+// save `vk` to disk at `path`, creating its parent directories if needed
+pub fn save_vk_to_json(vk: &SP1VerifyingKey, path: &Path, force: bool) -> std::io::Result<()> {
+    check_output_path(path, force)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = File::create(path)?;
+    serde_json::to_writer(file, vk)?;
+    Ok(())
+}
+
+pub fn load_vk_from_json(path: &Path) -> Result<SP1VerifyingKey, Error> {
+    let file = File::open(path).map_err(Error::io(format!(
+        "failed to open verifying key file {}",
+        path.display()
+    )))?;
+    serde_json::from_reader(file).map_err(|err| Error::invalid_proof_file(path, err))
+}
@@ -0,0 +1,319 @@
+//! Selecting which prover backs the [`ProverClient`], and proving with it.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use fibonacci_lib::{FibonacciInputs, TreeWitness};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{
+    install::try_install_circuit_artifacts, proto::network::ProofMode, NetworkProver, Prover as _,
+    ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin,
+};
+
+use crate::{
+    inputs::to_stdin,
+    logger::{estimate_shards, Progress, Verbosity},
+    proof::ProofSystem,
+    report::PhaseTimings,
+    AGGREGATOR_ELF, FIBONACCI_ELF,
+};
+
+/// Enum representing the available provers
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverMode {
+    /// Generate proofs on this machine.
+    Local,
+    /// Generate proofs using the Succinct prover network.
+    Network,
+    /// Generate mock proofs that always verify, for testing.
+    Mock,
+}
+
+/// A prover client, plus what's needed to send proof requests to the prover network.
+pub struct Prover {
+    /// The prover that generates proofs.
+    pub mode: ProverMode,
+    /// The client used for executing, setting up and verifying the program.
+    ///
+    /// The network prover does all of these locally anyway, so in network mode this is a local
+    /// client and only proving goes through the network.
+    pub client: ProverClient,
+    network: Option<NetworkProver>,
+    network_timeout: Option<Duration>,
+    progress: Progress,
+    verbosity: Verbosity,
+}
+
+impl Prover {
+    /// Setup a prover for `mode`.
+    ///
+    /// In network mode `private_key` must be the whitelisted key proof requests are signed with,
+    /// and proofs that aren't fulfilled within `network_timeout` are given up on. Local proving
+    /// progress is shown on `progress`, and network status messages are printed to stderr unless
+    /// `verbosity` is quiet.
+    pub fn new(
+        mode: ProverMode,
+        private_key: Option<&str>,
+        network_timeout: Option<Duration>,
+        progress: Progress,
+        verbosity: Verbosity,
+    ) -> anyhow::Result<Self> {
+        let (client, network) = match mode {
+            ProverMode::Local => (ProverClient::local(), None),
+            ProverMode::Mock => (ProverClient::mock(), None),
+            ProverMode::Network => {
+                let private_key = private_key.context(
+                    "the network prover needs a private key, pass --private-key or set \
+                     SP1_PRIVATE_KEY",
+                )?;
+                (
+                    ProverClient::local(),
+                    Some(NetworkProver::new_from_key(private_key)),
+                )
+            }
+        };
+        Ok(Self {
+            mode,
+            client,
+            network,
+            network_timeout,
+            progress,
+            verbosity,
+        })
+    }
+
+    /// Generate a proof of the program on `inputs`, inserting into the tree given by `witness`,
+    /// with the given proof system.
+    ///
+    /// Chaining onto a `previous` proof of the program, which must be a compressed one, it's
+    /// written to stdin for the program to verify as a deferred proof.
+    ///
+    /// In network mode, the proof request is saved to `request_path` (if given) until the proof is
+    /// downloaded, so that an interrupted run can be picked up again with [`Prover::resume`].
+    ///
+    /// Local proofs are returned with how long each phase of proving took.
+    pub fn prove(
+        &self,
+        pk: &SP1ProvingKey,
+        inputs: &FibonacciInputs,
+        witness: &TreeWitness,
+        previous: Option<&SP1ProofWithPublicValues>,
+        system: ProofSystem,
+        request_path: Option<&Path>,
+    ) -> anyhow::Result<(SP1ProofWithPublicValues, Option<PhaseTimings>)> {
+        let mut stdin = to_stdin(inputs, witness);
+        if let Some(previous) = previous {
+            let SP1Proof::Compressed(proof) = &previous.proof else {
+                anyhow::bail!("only compressed proofs can be chained onto");
+            };
+            stdin.write_proof(proof.clone(), pk.vk.vk.clone());
+        }
+        if let Some(network) = &self.network {
+            let on_request = |request_id: &str| match request_path {
+                Some(path) => PendingRequest {
+                    request_id: request_id.to_string(),
+                    inputs: inputs.clone(),
+                }
+                .save(path),
+                None => Ok(()),
+            };
+            let proof = self.prove_on_network(network, FIBONACCI_ELF, stdin, system, on_request)?;
+            return Ok((proof, None));
+        }
+        self.prove_locally(FIBONACCI_ELF, pk, stdin, system)
+            .map(|(proof, timings)| (proof, Some(timings)))
+    }
+
+    /// Generate a proof of the aggregator program, given the compressed fibonacci proofs to
+    /// aggregate and their public values in `stdin`, with the given proof system.
+    pub fn aggregate(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: SP1Stdin,
+        system: ProofSystem,
+    ) -> anyhow::Result<(SP1ProofWithPublicValues, Option<PhaseTimings>)> {
+        if let Some(network) = &self.network {
+            let proof =
+                self.prove_on_network(network, AGGREGATOR_ELF, stdin, system, |_| Ok(()))?;
+            return Ok((proof, None));
+        }
+        self.prove_locally(AGGREGATOR_ELF, pk, stdin, system)
+            .map(|(proof, timings)| (proof, Some(timings)))
+    }
+
+    /// Generate a proof of `elf` on this machine, returned with how long each phase took.
+    fn prove_locally(
+        &self,
+        elf: &[u8],
+        pk: &SP1ProvingKey,
+        stdin: SP1Stdin,
+        system: ProofSystem,
+    ) -> anyhow::Result<(SP1ProofWithPublicValues, PhaseTimings)> {
+        // Executing first is cheap compared to proving, and tells how many shards to expect.
+        let start_time = Instant::now();
+        let (_, report) = self.client.execute(elf, stdin.clone()).run()?;
+        let execution_time = start_time.elapsed();
+        self.progress
+            .start(estimate_shards(report.total_instruction_count()));
+
+        let builder = self.client.prove(pk, stdin);
+        let result = match system {
+            ProofSystem::Core => builder.core().run(),
+            ProofSystem::Compressed => builder.compressed().run(),
+            ProofSystem::Plonk => builder.plonk().run(),
+            ProofSystem::Groth16 => builder.groth16().run(),
+        };
+        let timings = self.progress.finish();
+        Ok((
+            result?,
+            PhaseTimings {
+                cycles: report.total_instruction_count(),
+                execution_secs: execution_time.as_secs_f64(),
+                ..timings
+            },
+        ))
+    }
+
+    /// Wrap a compressed proof for the EVM with the given proof system, PLONK or Groth16, as the
+    /// last stages of proving with it would, so the expensive wrap can be paid for only once the
+    /// proof is published.
+    ///
+    /// Only the local prover can wrap a proof it didn't generate, as the prover network only takes
+    /// programs and their stdin.
+    pub fn wrap(
+        &self,
+        proof: SP1ProofWithPublicValues,
+        system: ProofSystem,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        anyhow::ensure!(
+            self.mode == ProverMode::Local,
+            "only the local prover can wrap proofs, pass --prover local"
+        );
+        anyhow::ensure!(
+            matches!(system, ProofSystem::Plonk | ProofSystem::Groth16),
+            "compressed proofs can only be wrapped with plonk or groth16"
+        );
+        let SP1Proof::Compressed(compressed) = proof.proof else {
+            anyhow::bail!("only compressed proofs can be wrapped");
+        };
+        let prover = self.client.prover.sp1_prover();
+        let opts = Default::default();
+        let shrunk = prover.shrink(compressed, opts)?;
+        let outer = prover.wrap_bn254(shrunk, opts)?;
+        let artifacts = try_install_circuit_artifacts();
+        let wrapped = match system {
+            ProofSystem::Plonk => SP1Proof::Plonk(prover.wrap_plonk_bn254(outer, &artifacts)),
+            ProofSystem::Groth16 => SP1Proof::Groth16(prover.wrap_groth16_bn254(outer, &artifacts)),
+            ProofSystem::Core | ProofSystem::Compressed => unreachable!("checked above"),
+        };
+        Ok(SP1ProofWithPublicValues {
+            proof: wrapped,
+            ..proof
+        })
+    }
+
+    /// Download the proof for a request previously sent to the prover network, polling until
+    /// it's fulfilled or times out.
+    pub fn resume(&self, request_id: &str) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let network = self
+            .network
+            .as_ref()
+            .context("only prover network requests can be resumed, pass --prover network")?;
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(self.wait_for_network_proof(network, request_id))
+    }
+
+    /// Request a proof of `elf` from the prover network and poll until it's fulfilled or times
+    /// out.
+    ///
+    /// `on_request` is called with the request ID as soon as the request is accepted.
+    fn prove_on_network(
+        &self,
+        network: &NetworkProver,
+        elf: &[u8],
+        stdin: SP1Stdin,
+        system: ProofSystem,
+        on_request: impl FnOnce(&str) -> std::io::Result<()>,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(async {
+            let request_id = network
+                .request_proof(elf, stdin, system.into())
+                .await
+                .context("failed to request proof from the prover network")?;
+            if !self.verbosity.is_quiet() {
+                eprintln!("Proof request ID: {}", request_id);
+            }
+            on_request(&request_id).context("failed to save the proof request")?;
+
+            self.wait_for_network_proof(network, &request_id).await
+        })
+    }
+
+    async fn wait_for_network_proof(
+        &self,
+        network: &NetworkProver,
+        request_id: &str,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        match self.network_timeout {
+            _ if self.verbosity.is_quiet() => {}
+            Some(timeout) => eprintln!("Waiting for the proof (timeout: {:?})...", timeout),
+            None => eprintln!("Waiting for the proof..."),
+        }
+        network
+            .wait_proof(request_id, self.network_timeout)
+            .await
+            .with_context(|| {
+                format!(
+                    "proof request {} did not complete, retry with --resume {}",
+                    request_id, request_id
+                )
+            })
+    }
+}
+
+/// A prover network request whose proof hasn't been downloaded yet.
+///
+/// It's saved next to the proof it's for while waiting, so that a crashed or interrupted run can
+/// download the proof with `prove --resume <request-id>` instead of paying for a new request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingRequest {
+    pub request_id: String,
+    pub inputs: FibonacciInputs,
+}
+
+impl PendingRequest {
+    /// The file the pending request for the proof at `proof_path` is saved to, e.g.
+    /// `proof.request.json` for `proof.json`.
+    pub fn path_for(proof_path: &Path) -> PathBuf {
+        proof_path.with_extension("request.json")
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+impl From<ProofSystem> for ProofMode {
+    fn from(system: ProofSystem) -> Self {
+        match system {
+            ProofSystem::Core => ProofMode::Core,
+            ProofSystem::Compressed => ProofMode::Compressed,
+            ProofSystem::Plonk => ProofMode::Plonk,
+            ProofSystem::Groth16 => ProofMode::Groth16,
+        }
+    }
+}
@@ -0,0 +1,1801 @@
+//! The results of each command, printed either human-readably or as a single JSON document.
+
+use std::{fmt, path::PathBuf};
+
+use fibonacci_lib::{
+    check_version, decode_big_results, fixed::Q64x64, split_public_values, verify_inclusion_proof,
+    LegacyPublicValuesStruct, PublicValuesEncoding, PublicValuesStruct, Sequence, TreeHasher,
+    VersionedPublicValues, PUBLIC_VALUES_VERSION,
+};
+use monotree::hasher::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    fixture::SP1FibonacciProofFixture,
+    manifest::ManifestEntry,
+    proof::{is_stdio, ProofMetadata, ProofSystem},
+    prover::ProverMode,
+    schema::PublicValuesSchema,
+    tree::{SelectedHasher, SnapshotFormat, TreeEntry, TreeSnapshot, TreeStats},
+};
+
+/// The result of a command.
+#[derive(Debug, Serialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Report {
+    Execute(ExecuteReport),
+    Prove(ProveReport),
+    Verify(VerifyReport),
+    Vkey(VkeyReport),
+    Bench(BenchReport),
+    Evm(EvmReport),
+    Estimate(EstimateReport),
+    Decode(DecodeReport),
+    Diff(DiffReport),
+    #[serde(rename = "gen-sol")]
+    GenSol(GenSolReport),
+    Schema(SchemaReport),
+    #[serde(rename = "inclusion-proof")]
+    InclusionProof(InclusionProofReport),
+    #[serde(rename = "verify-inclusion")]
+    VerifyInclusion(VerifyInclusionReport),
+    #[serde(rename = "chain-check")]
+    ChainCheck(ChainCheckReport),
+    Append(AppendReport),
+    Aggregate(AggregateReport),
+    Wrap(WrapReport),
+    #[serde(rename = "tree-export")]
+    TreeExport(TreeExportReport),
+    #[serde(rename = "tree-ls")]
+    TreeLs(TreeLsReport),
+    #[serde(rename = "tree-get")]
+    TreeGet(TreeGetReport),
+}
+
+impl Report {
+    /// The error to exit with if the command's checks failed, e.g. if the proof didn't verify.
+    pub fn failure(&self) -> Option<Error> {
+        match self {
+            Report::Execute(report) if !report.values_correct => Some(Error::ValuesMismatch),
+            Report::Verify(report) if !report.verified => Some(Error::Verification(
+                report.error.clone().unwrap_or_default(),
+            )),
+            Report::Verify(report) if report.offset_matches == Some(false) => {
+                Some(Error::ValuesMismatch)
+            }
+            Report::Diff(report) if !report.identical => Some(Error::ProofsDiffer),
+            Report::ChainCheck(report) => report.failure(),
+            Report::Aggregate(report) if !report.covered => Some(Error::ValuesMismatch),
+            Report::VerifyInclusion(report) => report.failure(),
+            Report::GenSol(report) if report.check && !report.up_to_date => {
+                Some(Error::OutOfDate(report.output.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Print the report to stdout, as JSON if `json` is set, or to stderr if the command wrote its
+    /// proof to stdout, to keep the report out of the proof.
+    pub fn print(&self, json: bool) {
+        let report = if json {
+            let json = serde_json::to_string_pretty(self).expect("failed to serialize report");
+            format!("{}\n", json)
+        } else {
+            self.to_string()
+        };
+        if self.writes_proof_to_stdout() {
+            eprint!("{}", report);
+        } else {
+            print!("{}", report);
+        }
+    }
+
+    /// Whether the command wrote the proof it generated to stdout, see
+    /// [`STDIO_PATH`](crate::proof::STDIO_PATH).
+    fn writes_proof_to_stdout(&self) -> bool {
+        match self {
+            Report::Prove(report) => report
+                .proofs
+                .iter()
+                .any(|proof| is_stdio(&proof.proof_path)),
+            Report::Aggregate(report) => is_stdio(&report.proof_path),
+            Report::Wrap(report) => is_stdio(&report.proof_path),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Report::Execute(report) => report.fmt(f),
+            Report::Prove(report) => report.fmt(f),
+            Report::Verify(report) => report.fmt(f),
+            Report::Vkey(report) => report.fmt(f),
+            Report::Bench(report) => report.fmt(f),
+            Report::Evm(report) => report.fmt(f),
+            Report::Estimate(report) => report.fmt(f),
+            Report::Decode(report) => report.fmt(f),
+            Report::Diff(report) => report.fmt(f),
+            Report::GenSol(report) => report.fmt(f),
+            Report::Schema(report) => report.fmt(f),
+            Report::InclusionProof(report) => report.fmt(f),
+            Report::VerifyInclusion(report) => report.fmt(f),
+            Report::ChainCheck(report) => report.fmt(f),
+            Report::Append(report) => report.fmt(f),
+            Report::Aggregate(report) => report.fmt(f),
+            Report::Wrap(report) => report.fmt(f),
+            Report::TreeExport(report) => report.fmt(f),
+            Report::TreeLs(report) => report.fmt(f),
+            Report::TreeGet(report) => report.fmt(f),
+        }
+    }
+}
+
+/// The public values committed by the program, decoded.
+#[derive(Debug, Serialize)]
+pub struct PublicValues {
+    /// The layout version of the public values, see [`fibonacci_lib::PUBLIC_VALUES_VERSION`].
+    ///
+    /// Zero for the first version of the program, see [`fibonacci_lib::LegacyPublicValuesStruct`].
+    /// Its proofs have no offset, nonce or timestamp, so those are reported as `None` and zero.
+    pub version: u32,
+    /// How the program encoded the public values.
+    pub encoding: PublicValuesEncoding,
+    pub n: u32,
+    /// The offset added to `a` and `b`, or `None` if it was hidden or not committed.
+    pub offset: Option<u32>,
+    /// The hex-encoded hash committed in place of a hidden offset, see
+    /// [`fibonacci_lib::offset_commitment`].
+    pub offset_hash: Option<String>,
+    pub a: u64,
+    pub b: u64,
+    /// The hex-encoded key inserted into the tree, or `None` for the first version.
+    pub key: Option<String>,
+    /// The hex-encoded leaf inserted into the tree at `key`, or `None` for the first version.
+    pub leaf: Option<String>,
+    /// How many times the entry was inserted, 1 or 0 for an offset of zero, or `None` for the
+    /// first version. The entry is only inserted once, as inserting it again leaves the same root.
+    pub insert_count: Option<u32>,
+    /// The hex-encoded root of the tree.
+    pub root: String,
+    pub nonce: u64,
+    /// When the proof was requested, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The hex-encoded digest of the inputs the program read, see [`fibonacci_lib::inputs_digest`],
+    /// or `None` for the first version.
+    pub inputs_digest: Option<String>,
+    /// The hex-encoded verification key hash of the aggregated child proof, e.g. the proof of the
+    /// previous run in chained mode, see [`fibonacci_lib::child_vkey`], or `None` for leaf proofs
+    /// and the first version.
+    pub child_vkey: Option<String>,
+    /// Whether adding the offset made `a` or `b` wrap around, see [`fibonacci_lib::add_offset`], or
+    /// `None` for the first version.
+    pub overflowed: Option<bool>,
+    /// The hex-encoded hash chain of the roots the tree went through, see
+    /// [`fibonacci_lib::roots_digest`], or `None` for the first version.
+    pub roots_digest: Option<String>,
+    /// The hex-encoded root of the tree before the program inserted into it, zero for an empty
+    /// tree, or `None` for the first version.
+    pub prior_root: Option<String>,
+    /// How many entries were inserted into the tree in one batch, or `None` for the first version.
+    pub entry_count: Option<u32>,
+    /// Whether the program checked `inclusion_proof` against `root`, or `None` for the first
+    /// version.
+    pub inclusion_verified: Option<bool>,
+    /// The hex-encoded key the program proved absent from the tree at `prior_root`, zero if it
+    /// wasn't asked to, or `None` for the first version.
+    pub absent_key: Option<String>,
+    /// The hex-encoded digest of the updates applied to the tree, see
+    /// [`fibonacci_lib::updates_digest`], or `None` for the first version.
+    pub updates_digest: Option<String>,
+    /// The hasher the tree is built with, or `None` for the first version.
+    pub hasher: Option<String>,
+    /// How many keys were removed from the tree, or `None` for the first version.
+    pub removal_count: Option<u32>,
+    /// The hex-encoded key whose leaf was changed, zero if none was, or `None` for the first
+    /// version.
+    pub update_key: Option<String>,
+    /// The hex-encoded leaf at `update_key` before the change, or `None` for the first version.
+    pub old_leaf: Option<String>,
+    /// The hex-encoded leaf at `update_key` after the change, or `None` for the first version.
+    pub new_leaf: Option<String>,
+    /// How many trees of namespaces were inserted into, or `None` for the first version.
+    pub namespace_count: Option<u32>,
+    /// The hex-encoded digest of the roots of the trees of namespaces, see
+    /// [`fibonacci_lib::namespaces_digest`], or `None` for the first version.
+    pub namespaces_digest: Option<String>,
+    /// How many blobs of leaf data were inserted, or `None` for the first version.
+    pub blob_count: Option<u32>,
+    /// The hex-encoded digest of the blobs of leaf data, see [`fibonacci_lib::blobs_digest`], or
+    /// `None` for the first version.
+    pub blobs_digest: Option<String>,
+    /// The exact `a`, in decimal, if the program was asked to commit it, see
+    /// [`fibonacci_lib::fibonacci_big`]. Unlike `a`, it doesn't wrap around.
+    pub big_a: Option<String>,
+    /// The exact `b`, in decimal, if the program was asked to commit it.
+    pub big_b: Option<String>,
+    /// The modulus `a_mod` and `b_mod` are reduced by, or `None` if the program wasn't given one.
+    pub modulus: Option<u64>,
+    /// `a` modulo `modulus`, without wrapping around first, see
+    /// [`fibonacci_lib::fibonacci_mod_offset`].
+    pub a_mod: Option<u64>,
+    /// `b` modulo `modulus`, without wrapping around first.
+    pub b_mod: Option<u64>,
+    /// The sequence `a` and `b` are elements of, see [`fibonacci_lib::Sequence`], or `None` for
+    /// the first version.
+    pub sequence: Option<String>,
+    /// Why the program aborted, see [`fibonacci_lib::ERROR_OVERFLOW`], 0 if it didn't, or `None`
+    /// for the first version.
+    pub error_code: Option<u8>,
+    /// `a` with 128 bits, its high half committed as `a_hi`, or `None` if the program wasn't asked
+    /// for it.
+    pub a_wide: Option<u128>,
+    /// `b` with 128 bits, its high half committed as `b_hi`.
+    pub b_wide: Option<u128>,
+    /// The modulus `pisano_period` is the Pisano period of, or `None` if the program wasn't given
+    /// one.
+    pub pisano_modulus: Option<u64>,
+    /// The period of the fibonacci numbers modulo `pisano_modulus`, see
+    /// [`fibonacci_lib::pisano_period`].
+    pub pisano_period: Option<u64>,
+    /// The signed index `a_signed` and `b_signed` are at, or `None` if the program wasn't given
+    /// one.
+    pub signed_n: Option<i64>,
+    /// The fibonacci number at `signed_n`, see [`fibonacci_lib::fibonacci_signed`].
+    pub a_signed: Option<i64>,
+    /// The fibonacci number at `signed_n + 1`.
+    pub b_signed: Option<i64>,
+    /// The k of the table of every k'th fibonacci number up to the n'th, or `None` if the program
+    /// wasn't asked for one.
+    pub table_stride: Option<u32>,
+    /// How many numbers the table has.
+    pub table_len: Option<u32>,
+    /// The root of the Merkle tree over the table, see [`fibonacci_lib::table_root`].
+    pub table_root: Option<String>,
+    /// The number the program was asked whether it's a fibonacci number, or `None` if it wasn't.
+    pub membership_x: Option<u64>,
+    /// Whether `membership_x` is a fibonacci number.
+    pub membership_found: Option<bool>,
+    /// The lowest index of `membership_x` in the fibonacci numbers, if it's one of them.
+    pub membership_index: Option<u32>,
+    /// `F(n+1) / F(n)` in decimal, see [`fibonacci_lib::fibonacci_ratio`], or `None` if the
+    /// program wasn't asked for it.
+    pub ratio: Option<String>,
+    /// The hex-encoded SHA-256 hash of the public values of the previous run the program chained
+    /// onto, see [`fibonacci_lib::PreviousProof`], or `None` if it didn't.
+    pub previous_digest: Option<String>,
+    /// The Merkle proof of `leaf` being at `key` under `root`, or `None` for the first version.
+    pub inclusion_proof: Option<Vec<ProofStep>>,
+    /// The hex-encoded raw public values.
+    pub raw: String,
+}
+
+impl PublicValues {
+    /// Decode the raw public values committed by the program, or by its first version.
+    ///
+    /// Public values of any other version are rejected, rather than decoded with the wrong layout.
+    pub fn decode(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        let (encoding, values) = match Self::decode_versioned(bytes)? {
+            (encoding, VersionedPublicValues::Current(values)) => (encoding, values),
+            (_, VersionedPublicValues::Legacy(values)) => {
+                return Ok(Self::from_legacy(values, bytes))
+            }
+        };
+        let PublicValuesStruct {
+            version,
+            n,
+            offset,
+            offset_hash,
+            a,
+            b,
+            key,
+            leaf,
+            insert_count,
+            root,
+            nonce,
+            timestamp,
+            inputs_digest,
+            child_vkey,
+            overflowed,
+            roots_digest,
+            prior_root,
+            entry_count,
+            inclusion_verified,
+            absent_key,
+            updates_digest,
+            hasher,
+            removal_count,
+            update_key,
+            old_leaf,
+            new_leaf,
+            namespace_count,
+            namespaces_digest,
+            blob_count,
+            blobs_digest,
+            big,
+            modulus,
+            a_mod,
+            b_mod,
+            recurrence_a0,
+            recurrence_a1,
+            recurrence_p,
+            recurrence_q,
+            error_code,
+            wide,
+            a_hi,
+            b_hi,
+            pisano_modulus,
+            pisano_period,
+            signed,
+            signed_n,
+            a_signed,
+            b_signed,
+            table_stride,
+            table_len,
+            table_root,
+            membership,
+            membership_x,
+            membership_found,
+            membership_index,
+            golden_ratio,
+            ratio,
+            previous_digest,
+        } = values;
+        let (_, inclusion_proof, big_results) = split_public_values(bytes, encoding)?;
+        let (big_a, big_b) = decode_big_results(big, big_results)?
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .unzip();
+        let hidden = !offset_hash.is_zero();
+        Ok(Self {
+            version,
+            encoding,
+            n,
+            offset: (!hidden).then_some(offset),
+            offset_hash: hidden.then(|| hex::encode(offset_hash)),
+            a,
+            b,
+            key: Some(hex::encode(key)),
+            leaf: Some(hex::encode(leaf)),
+            insert_count: Some(insert_count),
+            root: hex::encode(root),
+            nonce,
+            timestamp,
+            inputs_digest: Some(hex::encode(inputs_digest)),
+            child_vkey: (!child_vkey.is_zero()).then(|| hex::encode(child_vkey)),
+            overflowed: Some(overflowed),
+            roots_digest: Some(hex::encode(roots_digest)),
+            prior_root: Some(hex::encode(prior_root)),
+            entry_count: Some(entry_count),
+            inclusion_verified: Some(inclusion_verified),
+            absent_key: Some(hex::encode(absent_key)),
+            updates_digest: Some(hex::encode(updates_digest)),
+            hasher: Some(
+                TreeHasher::try_from(hasher)
+                    .map(|hasher| hasher.to_string())
+                    .unwrap_or_else(|_| format!("unknown ({})", hasher)),
+            ),
+            removal_count: Some(removal_count),
+            update_key: Some(hex::encode(update_key)),
+            old_leaf: Some(hex::encode(old_leaf)),
+            new_leaf: Some(hex::encode(new_leaf)),
+            namespace_count: Some(namespace_count),
+            namespaces_digest: Some(hex::encode(namespaces_digest)),
+            blob_count: Some(blob_count),
+            blobs_digest: Some(hex::encode(blobs_digest)),
+            big_a,
+            big_b,
+            modulus: (modulus != 0).then_some(modulus),
+            a_mod: (modulus != 0).then_some(a_mod),
+            b_mod: (modulus != 0).then_some(b_mod),
+            sequence: Some(
+                Sequence::from_params(recurrence_a0, recurrence_a1, recurrence_p, recurrence_q)
+                    .to_string(),
+            ),
+            error_code: Some(error_code),
+            a_wide: wide.then_some((u128::from(a_hi) << 64) | u128::from(a)),
+            b_wide: wide.then_some((u128::from(b_hi) << 64) | u128::from(b)),
+            pisano_modulus: (pisano_modulus != 0).then_some(pisano_modulus),
+            pisano_period: (pisano_modulus != 0).then_some(pisano_period),
+            signed_n: signed.then_some(signed_n),
+            a_signed: signed.then_some(a_signed),
+            b_signed: signed.then_some(b_signed),
+            table_stride: (table_stride != 0).then_some(table_stride),
+            table_len: (table_stride != 0).then_some(table_len),
+            table_root: (table_stride != 0).then(|| hex::encode(table_root)),
+            membership_x: membership.then_some(membership_x),
+            membership_found: membership.then_some(membership_found),
+            membership_index: membership_found.then_some(membership_index),
+            ratio: golden_ratio.then(|| Q64x64(ratio).to_string()),
+            previous_digest: (!previous_digest.is_zero()).then(|| hex::encode(previous_digest)),
+            inclusion_proof: Some(
+                inclusion_proof
+                    .steps()
+                    .into_iter()
+                    .map(|(right, cut)| ProofStep {
+                        right,
+                        cut: hex::encode(cut),
+                    })
+                    .collect(),
+            ),
+            raw: hex::encode(bytes),
+        })
+    }
+
+    /// Decode the raw public values committed by the program or by its first version, in
+    /// whichever encoding they were committed in.
+    ///
+    /// Public values of any other version are rejected, rather than decoded with the wrong layout.
+    pub fn decode_versioned(
+        bytes: &[u8],
+    ) -> Result<(PublicValuesEncoding, VersionedPublicValues), alloy_sol_types::Error> {
+        // The ABI encoding pads the first field to a word, so its first bytes are zero, while
+        // Borsh and SSZ start with the (non-zero) version as a little-endian number. They encode
+        // the fields the same way, so SSZ-encoded public values are reported as Borsh.
+        if bytes.get(..4).map_or(true, |version| version == [0; 4]) {
+            let values = VersionedPublicValues::abi_decode(bytes)?;
+            return Ok((PublicValuesEncoding::Abi, values));
+        }
+
+        check_version(Some(u32::from_le_bytes(bytes[..4].try_into().unwrap())))?;
+        let (bytes, _, _) = split_public_values(bytes, PublicValuesEncoding::Borsh)?;
+        let values = PublicValuesStruct::borsh_decode(bytes)
+            .map_err(|err| alloy_sol_types::Error::custom(err.to_string()))?;
+        Ok((
+            PublicValuesEncoding::Borsh,
+            VersionedPublicValues::Current(values),
+        ))
+    }
+
+    /// Decode the raw public values committed by the current version of the program, in whichever
+    /// encoding it committed them in.
+    pub fn decode_struct(
+        bytes: &[u8],
+    ) -> Result<(PublicValuesEncoding, PublicValuesStruct), alloy_sol_types::Error> {
+        match Self::decode_versioned(bytes)? {
+            (encoding, VersionedPublicValues::Current(values)) => Ok((encoding, values)),
+            (_, VersionedPublicValues::Legacy(_)) => Err(alloy_sol_types::Error::custom(format!(
+                "expected public values of version {}, got ones of the first version",
+                PUBLIC_VALUES_VERSION
+            ))),
+        }
+    }
+
+    fn from_legacy(values: LegacyPublicValuesStruct, bytes: &[u8]) -> Self {
+        let LegacyPublicValuesStruct { n, a, b, root } = values;
+        Self {
+            version: 0,
+            encoding: PublicValuesEncoding::Abi,
+            n,
+            offset: None,
+            offset_hash: None,
+            a: a.into(),
+            b: b.into(),
+            key: None,
+            leaf: None,
+            insert_count: None,
+            root: hex::encode(root),
+            nonce: 0,
+            timestamp: 0,
+            inputs_digest: None,
+            child_vkey: None,
+            overflowed: None,
+            roots_digest: None,
+            prior_root: None,
+            entry_count: None,
+            inclusion_verified: None,
+            absent_key: None,
+            updates_digest: None,
+            hasher: None,
+            removal_count: None,
+            update_key: None,
+            old_leaf: None,
+            new_leaf: None,
+            namespace_count: None,
+            namespaces_digest: None,
+            blob_count: None,
+            blobs_digest: None,
+            big_a: None,
+            big_b: None,
+            modulus: None,
+            a_mod: None,
+            b_mod: None,
+            sequence: None,
+            error_code: None,
+            a_wide: None,
+            b_wide: None,
+            pisano_modulus: None,
+            pisano_period: None,
+            signed_n: None,
+            a_signed: None,
+            b_signed: None,
+            table_stride: None,
+            table_len: None,
+            table_root: None,
+            membership_x: None,
+            membership_found: None,
+            membership_index: None,
+            ratio: None,
+            previous_digest: None,
+            inclusion_proof: None,
+            raw: hex::encode(bytes),
+        }
+    }
+
+    /// The decoded fields by name, formatted for comparing them.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("version", self.version.to_string()),
+            ("encoding", self.encoding.to_string()),
+            ("n", self.n.to_string()),
+            ("offset", self.offset_display()),
+            ("a", self.a.to_string()),
+            ("b", self.b.to_string()),
+            ("key", self.key.clone().unwrap_or_default()),
+            ("leaf", self.leaf.clone().unwrap_or_default()),
+            (
+                "insert_count",
+                self.insert_count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("root", self.root.clone()),
+            ("nonce", self.nonce.to_string()),
+            ("timestamp", self.timestamp.to_string()),
+            (
+                "inputs_digest",
+                self.inputs_digest.clone().unwrap_or_default(),
+            ),
+            ("child_vkey", self.child_vkey.clone().unwrap_or_default()),
+            (
+                "overflowed",
+                self.overflowed
+                    .map(|overflowed| overflowed.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "roots_digest",
+                self.roots_digest.clone().unwrap_or_default(),
+            ),
+            ("prior_root", self.prior_root.clone().unwrap_or_default()),
+            (
+                "entry_count",
+                self.entry_count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "inclusion_verified",
+                self.inclusion_verified
+                    .map(|verified| verified.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("absent_key", self.absent_key.clone().unwrap_or_default()),
+            (
+                "updates_digest",
+                self.updates_digest.clone().unwrap_or_default(),
+            ),
+            ("hasher", self.hasher.clone().unwrap_or_default()),
+            (
+                "removal_count",
+                self.removal_count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("update_key", self.update_key.clone().unwrap_or_default()),
+            ("old_leaf", self.old_leaf.clone().unwrap_or_default()),
+            ("new_leaf", self.new_leaf.clone().unwrap_or_default()),
+            (
+                "namespace_count",
+                self.namespace_count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "namespaces_digest",
+                self.namespaces_digest.clone().unwrap_or_default(),
+            ),
+            (
+                "blob_count",
+                self.blob_count
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "blobs_digest",
+                self.blobs_digest.clone().unwrap_or_default(),
+            ),
+            ("big_a", self.big_a.clone().unwrap_or_default()),
+            ("big_b", self.big_b.clone().unwrap_or_default()),
+            (
+                "modulus",
+                self.modulus
+                    .map(|modulus| modulus.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "a_mod",
+                self.a_mod.map(|a| a.to_string()).unwrap_or_default(),
+            ),
+            (
+                "b_mod",
+                self.b_mod.map(|b| b.to_string()).unwrap_or_default(),
+            ),
+            ("sequence", self.sequence.clone().unwrap_or_default()),
+            (
+                "error_code",
+                self.error_code
+                    .map(|error_code| error_code.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "a_wide",
+                self.a_wide.map(|a| a.to_string()).unwrap_or_default(),
+            ),
+            (
+                "b_wide",
+                self.b_wide.map(|b| b.to_string()).unwrap_or_default(),
+            ),
+            (
+                "pisano_modulus",
+                self.pisano_modulus
+                    .map(|modulus| modulus.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "pisano_period",
+                self.pisano_period
+                    .map(|period| period.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "signed_n",
+                self.signed_n.map(|n| n.to_string()).unwrap_or_default(),
+            ),
+            (
+                "a_signed",
+                self.a_signed.map(|a| a.to_string()).unwrap_or_default(),
+            ),
+            (
+                "b_signed",
+                self.b_signed.map(|b| b.to_string()).unwrap_or_default(),
+            ),
+            (
+                "table_stride",
+                self.table_stride
+                    .map(|stride| stride.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "table_len",
+                self.table_len
+                    .map(|len| len.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("table_root", self.table_root.clone().unwrap_or_default()),
+            (
+                "membership_x",
+                self.membership_x.map(|x| x.to_string()).unwrap_or_default(),
+            ),
+            (
+                "membership_found",
+                self.membership_found
+                    .map(|found| found.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "membership_index",
+                self.membership_index
+                    .map(|index| index.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("ratio", self.ratio.clone().unwrap_or_default()),
+            (
+                "previous_digest",
+                self.previous_digest.clone().unwrap_or_default(),
+            ),
+            (
+                "inclusion_proof",
+                self.inclusion_proof
+                    .iter()
+                    .flatten()
+                    .map(ProofStep::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        ]
+    }
+
+    fn offset_display(&self) -> String {
+        match (&self.offset, &self.offset_hash) {
+            (Some(offset), _) => offset.to_string(),
+            (None, Some(offset_hash)) => format!("hidden (hash {})", offset_hash),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PublicValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "encoding: {}", self.encoding)?;
+        writeln!(f, "n: {}", self.n)?;
+        writeln!(f, "offset: {}", self.offset_display())?;
+        writeln!(f, "a: {}", self.a)?;
+        writeln!(f, "b: {}", self.b)?;
+        if let (Some(key), Some(leaf)) = (&self.key, &self.leaf) {
+            writeln!(f, "key: {}", key)?;
+            writeln!(f, "leaf: {}", leaf)?;
+        }
+        if let Some(insert_count) = self.insert_count {
+            writeln!(f, "insert_count: {}", insert_count)?;
+        }
+        writeln!(f, "root: {}", self.root)?;
+        writeln!(f, "nonce: {}", self.nonce)?;
+        writeln!(f, "timestamp: {}", self.timestamp)?;
+        if let Some(inputs_digest) = &self.inputs_digest {
+            writeln!(f, "inputs_digest: {}", inputs_digest)?;
+        }
+        if let Some(child_vkey) = &self.child_vkey {
+            writeln!(f, "child_vkey: {}", child_vkey)?;
+        }
+        if let Some(overflowed) = self.overflowed {
+            writeln!(f, "overflowed: {}", overflowed)?;
+        }
+        if let Some(roots_digest) = &self.roots_digest {
+            writeln!(f, "roots_digest: {}", roots_digest)?;
+        }
+        if let Some(prior_root) = &self.prior_root {
+            writeln!(f, "prior_root: {}", prior_root)?;
+        }
+        if let Some(entry_count) = self.entry_count {
+            writeln!(f, "entry_count: {}", entry_count)?;
+        }
+        if let Some(inclusion_verified) = self.inclusion_verified {
+            writeln!(f, "inclusion_verified: {}", inclusion_verified)?;
+        }
+        if let Some(absent_key) = &self.absent_key {
+            writeln!(f, "absent_key: {}", absent_key)?;
+        }
+        if let Some(updates_digest) = &self.updates_digest {
+            writeln!(f, "updates_digest: {}", updates_digest)?;
+        }
+        if let Some(hasher) = &self.hasher {
+            writeln!(f, "hasher: {}", hasher)?;
+        }
+        if let Some(removal_count) = self.removal_count {
+            writeln!(f, "removal_count: {}", removal_count)?;
+        }
+        if let Some(update_key) = &self.update_key {
+            writeln!(f, "update_key: {}", update_key)?;
+        }
+        if let Some(old_leaf) = &self.old_leaf {
+            writeln!(f, "old_leaf: {}", old_leaf)?;
+        }
+        if let Some(new_leaf) = &self.new_leaf {
+            writeln!(f, "new_leaf: {}", new_leaf)?;
+        }
+        if let Some(namespace_count) = self.namespace_count {
+            writeln!(f, "namespace_count: {}", namespace_count)?;
+        }
+        if let Some(namespaces_digest) = &self.namespaces_digest {
+            writeln!(f, "namespaces_digest: {}", namespaces_digest)?;
+        }
+        if let Some(blob_count) = self.blob_count {
+            writeln!(f, "blob_count: {}", blob_count)?;
+        }
+        if let Some(blobs_digest) = &self.blobs_digest {
+            writeln!(f, "blobs_digest: {}", blobs_digest)?;
+        }
+        if let (Some(big_a), Some(big_b)) = (&self.big_a, &self.big_b) {
+            writeln!(f, "big_a: {}", big_a)?;
+            writeln!(f, "big_b: {}", big_b)?;
+        }
+        if let (Some(modulus), Some(a_mod), Some(b_mod)) = (self.modulus, self.a_mod, self.b_mod) {
+            writeln!(f, "modulus: {}", modulus)?;
+            writeln!(f, "a_mod: {}", a_mod)?;
+            writeln!(f, "b_mod: {}", b_mod)?;
+        }
+        if let Some(sequence) = &self.sequence {
+            writeln!(f, "sequence: {}", sequence)?;
+        }
+        if let Some(error_code) = self.error_code {
+            writeln!(f, "error_code: {}", error_code)?;
+        }
+        if let (Some(a_wide), Some(b_wide)) = (self.a_wide, self.b_wide) {
+            writeln!(f, "a_wide: {}", a_wide)?;
+            writeln!(f, "b_wide: {}", b_wide)?;
+        }
+        if let (Some(modulus), Some(period)) = (self.pisano_modulus, self.pisano_period) {
+            writeln!(f, "pisano_modulus: {}", modulus)?;
+            writeln!(f, "pisano_period: {}", period)?;
+        }
+        if let (Some(n), Some(a), Some(b)) = (self.signed_n, self.a_signed, self.b_signed) {
+            writeln!(f, "signed_n: {}", n)?;
+            writeln!(f, "a_signed: {}", a)?;
+            writeln!(f, "b_signed: {}", b)?;
+        }
+        if let (Some(stride), Some(len), Some(root)) =
+            (self.table_stride, self.table_len, &self.table_root)
+        {
+            writeln!(f, "table_stride: {}", stride)?;
+            writeln!(f, "table_len: {}", len)?;
+            writeln!(f, "table_root: {}", root)?;
+        }
+        if let (Some(x), Some(found)) = (self.membership_x, self.membership_found) {
+            writeln!(f, "membership_x: {}", x)?;
+            writeln!(f, "membership_found: {}", found)?;
+        }
+        if let Some(index) = self.membership_index {
+            writeln!(f, "membership_index: {}", index)?;
+        }
+        if let Some(ratio) = &self.ratio {
+            writeln!(f, "ratio: {}", ratio)?;
+        }
+        if let Some(previous_digest) = &self.previous_digest {
+            writeln!(f, "previous_digest: {}", previous_digest)?;
+        }
+        if let Some(inclusion_proof) = &self.inclusion_proof {
+            writeln!(f, "inclusion_proof: {} steps", inclusion_proof.len())?;
+            for step in inclusion_proof {
+                writeln!(f, "  {}", step)?;
+            }
+        }
+        writeln!(f, "public_values slice is {}", self.raw)
+    }
+}
+
+/// A step of the Merkle proof committed after the public values, see
+/// [`fibonacci_lib::InclusionProofStruct`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofStep {
+    /// Whether the path goes right at this step, i.e. the cut is the left sibling.
+    pub right: bool,
+    /// The hex-encoded cut of the sibling node.
+    pub cut: String,
+}
+
+impl fmt::Display for ProofStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.right { "right" } else { "left" };
+        write!(f, "{}:{}", direction, self.cut)
+    }
+}
+
+/// The result of the `execute` command.
+#[derive(Debug, Serialize)]
+pub struct ExecuteReport {
+    pub public_values: PublicValues,
+    /// Whether the public values match the ones computed natively.
+    pub values_correct: bool,
+    /// The first field that doesn't match, if any.
+    pub mismatch: Option<&'static str>,
+    pub cycles: u64,
+    /// The cycles inside the guest's `monotree` cycle-tracker region, which updates the tree.
+    pub monotree_cycles: Option<u64>,
+    /// The cycles inside the guest's `inclusion-proof` cycle-tracker region, which checks the
+    /// Merkle proof of the entry.
+    pub inclusion_proof_cycles: Option<u64>,
+    /// The cycles inside the guest's `pisano` cycle-tracker region, which finds the Pisano period.
+    pub pisano_cycles: Option<u64>,
+    /// The tree work done for the inputs, or `None` if it couldn't be counted.
+    pub tree_stats: Option<TreeStats>,
+    pub execution_time_secs: f64,
+}
+
+impl fmt::Display for ExecuteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Program executed successfully.")?;
+        writeln!(f, "Time elapsed: {:.3}s", self.execution_time_secs)?;
+        write!(f, "{}", self.public_values)?;
+        match self.mismatch {
+            None => writeln!(f, "Values are correct!")?,
+            Some(field) => writeln!(f, "Values are incorrect! {} doesn't match.", field)?,
+        }
+        writeln!(f, "Number of cycles: {}", self.cycles)?;
+        if let Some(cycles) = self.monotree_cycles {
+            writeln!(f, "Tree update cycles: {}", cycles)?;
+        }
+        if let Some(cycles) = self.inclusion_proof_cycles {
+            writeln!(f, "Inclusion proof cycles: {}", cycles)?;
+        }
+        if let Some(cycles) = self.pisano_cycles {
+            writeln!(f, "Pisano period cycles: {}", cycles)?;
+        }
+        if let Some(stats) = &self.tree_stats {
+            writeln!(f, "Tree hashes: {}", stats.hashes)?;
+            writeln!(f, "Tree node reads: {}", stats.node_reads)?;
+            writeln!(f, "Tree node writes: {}", stats.node_writes)?;
+            writeln!(f, "Witness nodes: {}", stats.witness_nodes)?;
+        }
+        Ok(())
+    }
+}
+
+/// A proof generated by the `prove` command.
+#[derive(Debug, Serialize)]
+pub struct ProofSummary {
+    pub n: u32,
+    pub offset: u32,
+    pub proof_path: PathBuf,
+    /// The hex-encoded raw public values.
+    pub public_values: String,
+    /// How long generating the proof took, zero if it was cached.
+    pub proving_time_secs: f64,
+    /// Whether the proof was taken from the cache rather than generated.
+    pub cached: bool,
+    /// Where the raw and decoded public values were saved next to the proof with
+    /// `--save-public-values`, if they were.
+    pub public_values_paths: Vec<PathBuf>,
+    /// How long each phase of proving took, or `None` for proofs from the prover network and
+    /// proofs generated concurrently with `--jobs`, whose phases can't be told apart.
+    pub timings: Option<PhaseTimings>,
+}
+
+/// How long each phase of generating a proof locally took.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct PhaseTimings {
+    /// How many cycles executing the program took, which the proving times scale with.
+    pub cycles: u64,
+    /// Executing the program, to learn how many shards to expect.
+    pub execution_secs: f64,
+    /// Proving the shards of the execution.
+    pub core_secs: f64,
+    /// Compressing the shard proofs into a single proof. Zero for core proofs.
+    pub compress_secs: f64,
+    /// Wrapping the compressed proof for the EVM. Zero for core and compressed proofs.
+    pub wrap_secs: f64,
+}
+
+impl fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cycles: {}", self.cycles)?;
+        writeln!(f, "Execution: {:.3}s", self.execution_secs)?;
+        writeln!(f, "Core proving: {:.3}s", self.core_secs)?;
+        writeln!(f, "Compression: {:.3}s", self.compress_secs)?;
+        writeln!(f, "Wrapping: {:.3}s", self.wrap_secs)
+    }
+}
+
+/// The result of the `prove` command.
+#[derive(Debug, Serialize)]
+pub struct ProveReport {
+    pub proof_system: ProofSystem,
+    pub prover: ProverMode,
+    /// How long setting up the program took.
+    pub setup_secs: f64,
+    /// Where the verifying key was saved, if `--save-vk` was passed.
+    pub vk_path: Option<PathBuf>,
+    pub proofs: Vec<ProofSummary>,
+}
+
+impl fmt::Display for ProveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Proof System: {:?}", self.proof_system)?;
+        writeln!(f, "Prover: {:?}", self.prover)?;
+        writeln!(f, "Setup: {:.3}s", self.setup_secs)?;
+        match self.proofs.as_slice() {
+            [proof] => {
+                writeln!(f, "n: {}", proof.n)?;
+                if proof.cached {
+                    writeln!(f, "Reused the cached proof of the same inputs")?;
+                } else {
+                    writeln!(
+                        f,
+                        "Successfully generated proof in {:.3}s!",
+                        proof.proving_time_secs
+                    )?;
+                }
+                if let Some(timings) = &proof.timings {
+                    write!(f, "{}", timings)?;
+                }
+                if is_stdio(&proof.proof_path) {
+                    writeln!(f, "Proof written to stdout")?;
+                } else {
+                    writeln!(f, "Proof saved to {}", proof.proof_path.display())?;
+                }
+                for path in &proof.public_values_paths {
+                    writeln!(f, "Public values saved to {}", path.display())?;
+                }
+            }
+            proofs => {
+                writeln!(f, "Successfully generated {} proofs!", proofs.len())?;
+                writeln!(f, "{:>8} {:>8} {:>10}  proof", "n", "offset", "time (s)")?;
+                for proof in proofs {
+                    writeln!(
+                        f,
+                        "{:>8} {:>8} {:>10.3}  {}{}",
+                        proof.n,
+                        proof.offset,
+                        proof.proving_time_secs,
+                        proof.proof_path.display(),
+                        if proof.cached { " (cached)" } else { "" }
+                    )?;
+                }
+            }
+        }
+        if let Some(vk_path) = &self.vk_path {
+            writeln!(f, "Verifying key saved to {}", vk_path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of the `verify` command.
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub proof_path: PathBuf,
+    /// The verifying key file the proof was checked against, or `None` if the verifying key was
+    /// embedded in the proof file or derived from the embedded ELF.
+    pub vk_path: Option<PathBuf>,
+    /// Whether the proof was checked against the verifying key embedded in the proof file.
+    pub embedded_vk: bool,
+    /// The bytes32 hash of the verification key the proof was checked against.
+    pub vkey: String,
+    pub proof_system: ProofSystem,
+    /// What the proof file records about the proof, or `None` for proofs saved before proof files
+    /// recorded it.
+    pub metadata: Option<ProofMetadata>,
+    pub verified: bool,
+    /// Why verification failed, if it did.
+    pub error: Option<String>,
+    pub public_values: PublicValues,
+    /// Whether the offset claimed with `--offset` is the one the proof was generated with, if an
+    /// offset was claimed.
+    pub offset_matches: Option<bool>,
+    /// How long setting up the program took, or `None` if the verifying key was loaded from disk.
+    pub setup_secs: Option<f64>,
+    pub verification_time_secs: f64,
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "loaded {} from disk", self.proof_path.display())?;
+        if let Some(vk_path) = &self.vk_path {
+            writeln!(f, "loaded verifying key {} from disk", vk_path.display())?;
+        }
+        if self.embedded_vk {
+            writeln!(f, "using the verifying key embedded in the proof file")?;
+        }
+        writeln!(f, "Verification Key: {}", self.vkey)?;
+        writeln!(f, "Proof System: {:?}", self.proof_system)?;
+        if let Some(metadata) = &self.metadata {
+            writeln!(f, "Proof Verification Key: {}", metadata.vkey_hash)?;
+            writeln!(f, "ELF Hash: {}", metadata.elf_hash)?;
+            writeln!(f, "Program Version: {}", metadata.program_version)?;
+            writeln!(f, "Created At: {}", metadata.created_at)?;
+            writeln!(f, "Prover: {:?}", metadata.prover)?;
+            if let Some(cycles) = metadata.cycles {
+                writeln!(f, "Cycles: {}", cycles)?;
+            }
+            if let Some(environment) = &metadata.environment {
+                writeln!(f, "SP1 Circuits: {}", environment.sp1_circuit_version)?;
+                writeln!(f, "Platform: {}-{}", environment.os, environment.arch)?;
+                writeln!(f, "Stdin Hash: {}", environment.stdin_hash)?;
+                for (name, value) in &environment.parameters {
+                    writeln!(f, "{}: {}", name, value)?;
+                }
+            }
+        }
+        if let Some(setup_secs) = self.setup_secs {
+            writeln!(f, "Setup: {:.3}s", setup_secs)?;
+        }
+        writeln!(f, "Verification: {:.3}s", self.verification_time_secs)?;
+        match &self.error {
+            None => writeln!(f, "Successfully verified proof!")?,
+            Some(error) => writeln!(f, "Failed to verify proof: {}", error)?,
+        }
+        match self.offset_matches {
+            Some(true) => writeln!(f, "The claimed offset matches the proof.")?,
+            Some(false) => writeln!(f, "The claimed offset does NOT match the proof!")?,
+            None => {}
+        }
+        write!(f, "{}", self.public_values)
+    }
+}
+
+/// The result of the `vkey` command.
+#[derive(Debug, Serialize)]
+pub struct VkeyReport {
+    /// The verification key hash as a hex-encoded bytes32, as expected by the on-chain verifiers.
+    pub bytes32: String,
+    /// The verification key hash as the u32 words used when verifying proofs inside the zkVM.
+    pub words: [u32; 8],
+    /// The file the bytes32 hash was written to, if any.
+    pub output: Option<PathBuf>,
+}
+
+impl fmt::Display for VkeyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Verification Key Hash (bytes32): {}", self.bytes32)?;
+        writeln!(f, "Verification Key Hash (u32 words): {:?}", self.words)?;
+        if let Some(output) = &self.output {
+            writeln!(f, "Verification key hash saved to {}", output.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// The cycles spent executing the program with one set of inputs.
+#[derive(Debug, Serialize)]
+pub struct BenchRow {
+    pub n: u32,
+    pub offset: u32,
+    pub cycles: u64,
+    pub syscalls: u64,
+    /// The cycles inside the guest's `fibonacci` cycle-tracker region.
+    pub fibonacci_cycles: u64,
+    /// The cycles inside the `fibonacci` cycle-tracker region when computing with fast doubling.
+    pub fast_fibonacci_cycles: u64,
+    /// The cycles inside the guest's `monotree` cycle-tracker region.
+    pub monotree_cycles: u64,
+    /// The cycles spent by the program hashing its tree with SHA-256 instead of Blake3.
+    pub sha2_cycles: u64,
+    /// The cycles inside the `monotree` cycle-tracker region when hashing with SHA-256.
+    pub sha2_monotree_cycles: u64,
+}
+
+impl BenchRow {
+    /// How many more cycles hashing the tree with SHA-256 takes than with Blake3, negative when it
+    /// takes fewer.
+    pub fn sha2_cycles_diff(&self) -> i64 {
+        self.sha2_cycles as i64 - self.cycles as i64
+    }
+}
+
+/// The result of the `bench` command.
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub rows: Vec<BenchRow>,
+    /// Render as CSV rather than as an aligned table.
+    #[serde(skip)]
+    pub csv: bool,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.csv {
+            writeln!(
+                f,
+                "n,offset,cycles,syscalls,fibonacci_cycles,fast_fibonacci_cycles,monotree_cycles,\
+                 sha2_cycles,sha2_monotree_cycles"
+            )?;
+            for row in &self.rows {
+                writeln!(
+                    f,
+                    "{},{},{},{},{},{},{},{},{}",
+                    row.n,
+                    row.offset,
+                    row.cycles,
+                    row.syscalls,
+                    row.fibonacci_cycles,
+                    row.fast_fibonacci_cycles,
+                    row.monotree_cycles,
+                    row.sha2_cycles,
+                    row.sha2_monotree_cycles
+                )?;
+            }
+            return Ok(());
+        }
+
+        writeln!(
+            f,
+            "{:>8} {:>8} {:>12} {:>10} {:>12} {:>14} {:>12} {:>14} {:>12}",
+            "n",
+            "offset",
+            "cycles",
+            "syscalls",
+            "fibonacci",
+            "fibonacci_fast",
+            "monotree",
+            "monotree_sha2",
+            "sha2_diff"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:>8} {:>8} {:>12} {:>10} {:>12} {:>14} {:>12} {:>14} {:>+12}",
+                row.n,
+                row.offset,
+                row.cycles,
+                row.syscalls,
+                row.fibonacci_cycles,
+                row.fast_fibonacci_cycles,
+                row.monotree_cycles,
+                row.sha2_monotree_cycles,
+                row.sha2_cycles_diff()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of the `evm` command.
+#[derive(Debug, Serialize)]
+pub struct EvmReport {
+    pub proof_system: ProofSystem,
+    pub fixture_path: PathBuf,
+    pub fixture: SP1FibonacciProofFixture,
+    /// The calldata of a call to `verifyFibonacciProof` with the proof, e.g. to send with `cast`.
+    pub calldata: String,
+}
+
+impl fmt::Display for EvmReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Proof System: {:?}", self.proof_system)?;
+
+        // The verification key is used to verify that the proof corresponds to the execution of
+        // the program on the given input.
+        //
+        // Note that the verification key stays the same regardless of the input.
+        writeln!(f, "Verification Key: {}", self.fixture.vkey)?;
+
+        // The public values are the values which are publicly committed to by the zkVM.
+        writeln!(f, "Public Values: {}", self.fixture.public_values)?;
+
+        // The proof proves to the verifier that the program was executed with some inputs that
+        // led to the given public values.
+        writeln!(f, "Proof Bytes: {}", self.fixture.proof)?;
+
+        // The two of them as the calldata of the verifier contract's entrypoint.
+        writeln!(f, "Calldata: {}", self.calldata)?;
+        writeln!(f, "Fixture saved to {}", self.fixture_path.display())
+    }
+}
+
+/// The result of the `estimate` command.
+#[derive(Debug, Serialize)]
+pub struct EstimateReport {
+    pub cycles: u64,
+    pub proof_system: ProofSystem,
+    /// The estimated time to prove the execution on this machine.
+    pub local_proving_secs: f64,
+    /// The approximate cost of proving the execution on the prover network.
+    pub network_cost_usd: f64,
+}
+
+impl fmt::Display for EstimateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Number of cycles: {}", self.cycles)?;
+        writeln!(f, "Proof System: {:?}", self.proof_system)?;
+        writeln!(
+            f,
+            "Estimated local proving time: {:.0}s",
+            self.local_proving_secs
+        )?;
+        writeln!(
+            f,
+            "Estimated network proving cost: ${:.4}",
+            self.network_cost_usd
+        )?;
+        writeln!(
+            f,
+            "These are rough estimates; actual times depend on your hardware."
+        )
+    }
+}
+
+/// The result of the `decode` command.
+#[derive(Debug, Serialize)]
+pub struct DecodeReport {
+    /// The proof the public values were read from, if they weren't given directly.
+    pub proof_path: Option<PathBuf>,
+    pub public_values: PublicValues,
+}
+
+impl fmt::Display for DecodeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(proof_path) = &self.proof_path {
+            writeln!(f, "loaded {} from disk", proof_path.display())?;
+        }
+        write!(f, "{}", self.public_values)
+    }
+}
+
+/// The result of the `diff` command.
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+    pub a: DiffedProof,
+    pub b: DiffedProof,
+    /// The public values fields of both proofs.
+    pub fields: Vec<FieldDiff>,
+    /// Whether both proofs are for the same verifying key, or `None` if that can't be told from
+    /// the proofs alone.
+    pub vkeys_match: Option<bool>,
+    /// Whether the proof systems, the verifying keys and all public values fields match.
+    pub identical: bool,
+}
+
+/// One of the proofs compared by the `diff` command.
+#[derive(Debug, Serialize)]
+pub struct DiffedProof {
+    pub proof_path: PathBuf,
+    pub proof_system: ProofSystem,
+    /// The bytes32 hash of the verifying key the proof is for, if the proof commits to it.
+    pub vkey: Option<String>,
+}
+
+/// A public values field of the proofs compared by the `diff` command.
+#[derive(Debug, Serialize)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub a: String,
+    pub b: String,
+    pub equal: bool,
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "a: {}", self.a.proof_path.display())?;
+        writeln!(f, "b: {}", self.b.proof_path.display())?;
+        let unknown = || "unknown".to_string();
+        let rows = [
+            (
+                "proof system",
+                format!("{:?}", self.a.proof_system),
+                format!("{:?}", self.b.proof_system),
+                self.a.proof_system == self.b.proof_system,
+            ),
+            (
+                "vkey",
+                self.a.vkey.clone().unwrap_or_else(unknown),
+                self.b.vkey.clone().unwrap_or_else(unknown),
+                self.vkeys_match.unwrap_or(true),
+            ),
+        ];
+        let fields = self
+            .fields
+            .iter()
+            .map(|diff| (diff.field, diff.a.clone(), diff.b.clone(), diff.equal));
+        for (field, a, b, equal) in rows.into_iter().chain(fields) {
+            let marker = if equal { " " } else { "!" };
+            writeln!(f, "{} {:<12} {:<66} {}", marker, field, a, b)?;
+        }
+        match (self.identical, self.vkeys_match) {
+            (false, _) => writeln!(f, "The proofs differ."),
+            (true, None) => writeln!(
+                f,
+                "The public values match, but the verifying keys can't be compared."
+            ),
+            (true, Some(_)) => writeln!(f, "The proofs match."),
+        }
+    }
+}
+
+/// The result of the `gen-sol` command.
+#[derive(Debug, Serialize)]
+pub struct GenSolReport {
+    pub output: PathBuf,
+    /// Whether the file was only checked, and not written.
+    pub check: bool,
+    /// Whether the file already matched the Rust definition.
+    pub up_to_date: bool,
+    pub written: bool,
+}
+
+impl fmt::Display for GenSolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.up_to_date, self.written) {
+            (true, _) => writeln!(f, "{} is up to date", self.output.display()),
+            (false, true) => writeln!(f, "wrote {}", self.output.display()),
+            (false, false) => writeln!(
+                f,
+                "{} is out of date, run `gen-sol` to regenerate it",
+                self.output.display()
+            ),
+        }
+    }
+}
+
+/// The result of the `schema` command.
+#[derive(Debug, Serialize)]
+pub struct SchemaReport {
+    #[serde(flatten)]
+    pub schema: PublicValuesSchema,
+    /// The file the schema was written to, if any.
+    pub output: Option<PathBuf>,
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version: {}", self.schema.version)?;
+        writeln!(f, "vkey: {}", self.schema.vkey)?;
+        for encoding in &self.schema.encodings {
+            writeln!(f)?;
+            writeln!(f, "{} ({} bytes):", encoding.encoding, encoding.size)?;
+            writeln!(f, "{:>6} {:>6}  {:<10} name", "offset", "size", "type")?;
+            for field in &encoding.fields {
+                writeln!(
+                    f,
+                    "{:>6} {:>6}  {:<10} {}",
+                    field.offset, field.size, field.ty, field.name
+                )?;
+            }
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "followed by the ABI-encoded {}",
+            self.schema.inclusion_proof
+        )?;
+        writeln!(
+            f,
+            "followed, when big is set, by the exact a and b: {}",
+            self.schema.big_results
+        )?;
+        if let Some(output) = &self.output {
+            writeln!(f)?;
+            writeln!(f, "Schema saved to {}", output.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// The Merkle proof committed by the program, along with the key, the leaf and the root it's for,
+/// for checking it outside of the program.
+///
+/// Its JSON is portable: checking it takes only the hasher and the hashing of
+/// [`fibonacci_lib::proof_path`], not monotree itself.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedInclusionProof {
+    /// The hex-encoded root of the tree.
+    pub root: String,
+    /// The hasher the tree is built with. Proofs exported before it was recorded are Blake3 ones.
+    #[serde(default)]
+    pub hasher: TreeHasher,
+    /// The hex-encoded key the leaf is at.
+    pub key: String,
+    /// The hex-encoded leaf.
+    pub leaf: String,
+    /// Whether the program checked the proof against the root itself.
+    pub verified_in_guest: bool,
+    /// The steps of the proof, from the root down.
+    pub steps: Vec<ProofStep>,
+}
+
+impl ExportedInclusionProof {
+    /// Whether the steps lead from the leaf to `root`, see
+    /// [`fibonacci_lib::verify_inclusion_proof`].
+    pub fn verify(&self, root: &str) -> anyhow::Result<bool> {
+        let decode32 = |value: &str| -> anyhow::Result<[u8; 32]> {
+            let bytes = hex::decode(value.trim_start_matches("0x"))?;
+            bytes
+                .try_into()
+                .map_err(|bytes: Vec<u8>| anyhow::anyhow!("expected 32 bytes, got {}", bytes.len()))
+        };
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| Ok((step.right, hex::decode(&step.cut)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let hasher = SelectedHasher::with(self.hasher);
+        Ok(verify_inclusion_proof(
+            |bytes| hasher.digest(bytes),
+            &decode32(root)?,
+            &decode32(&self.leaf)?,
+            &steps,
+        ))
+    }
+}
+
+/// The result of the `inclusion-proof` command.
+#[derive(Debug, Serialize)]
+pub struct InclusionProofReport {
+    pub proof_path: PathBuf,
+    pub inclusion_proof: ExportedInclusionProof,
+    /// The file the inclusion proof was written to, if any.
+    pub output: Option<PathBuf>,
+}
+
+impl fmt::Display for InclusionProofReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proof = &self.inclusion_proof;
+        writeln!(f, "loaded {} from disk", self.proof_path.display())?;
+        writeln!(f, "root: {}", proof.root)?;
+        writeln!(f, "key: {}", proof.key)?;
+        writeln!(f, "leaf: {}", proof.leaf)?;
+        writeln!(f, "verified in the program: {}", proof.verified_in_guest)?;
+        writeln!(f, "{} steps:", proof.steps.len())?;
+        for step in &proof.steps {
+            writeln!(f, "  {}", step)?;
+        }
+        if let Some(output) = &self.output {
+            writeln!(f, "Inclusion proof saved to {}", output.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of the `verify-inclusion` command.
+#[derive(Debug, Serialize)]
+pub struct VerifyInclusionReport {
+    pub input: PathBuf,
+    pub inclusion_proof: ExportedInclusionProof,
+    /// The hex-encoded root the proof was checked against.
+    pub root: String,
+    pub verified: bool,
+}
+
+impl VerifyInclusionReport {
+    /// The error to exit with if the proof doesn't lead to the root.
+    pub fn failure(&self) -> Option<Error> {
+        (!self.verified).then(|| {
+            Error::Verification(format!(
+                "the inclusion proof of {} doesn't lead to the root {}",
+                self.inclusion_proof.leaf, self.root
+            ))
+        })
+    }
+}
+
+impl fmt::Display for VerifyInclusionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proof = &self.inclusion_proof;
+        writeln!(f, "loaded {} from disk", self.input.display())?;
+        writeln!(f, "root: {}", self.root)?;
+        writeln!(f, "key: {}", proof.key)?;
+        writeln!(f, "leaf: {}", proof.leaf)?;
+        writeln!(f, "hasher: {}", proof.hasher)?;
+        if self.verified {
+            writeln!(f, "The inclusion proof leads to the root.")
+        } else {
+            writeln!(f, "The inclusion proof doesn't lead to the root!")
+        }
+    }
+}
+
+/// A proof checked by the `chain-check` command.
+#[derive(Debug, Serialize)]
+pub struct ChainedProof {
+    pub proof_path: PathBuf,
+    /// The hex-encoded root of the tree before the proof's updates, or `None` for the first
+    /// version, whose proofs can't be chained.
+    pub prior_root: Option<String>,
+    /// The hex-encoded root of the tree after the proof's updates.
+    pub root: String,
+    /// Whether the proof picks up the tree where the previous one left it. Always true for the
+    /// first proof.
+    pub linked: bool,
+    /// Whether the proof verified, or `None` if it wasn't verified.
+    pub verified: Option<bool>,
+}
+
+/// The result of the `chain-check` command.
+#[derive(Debug, Serialize)]
+pub struct ChainCheckReport {
+    pub proofs: Vec<ChainedProof>,
+}
+
+impl ChainCheckReport {
+    /// The error to exit with if a proof didn't verify, or didn't link up with the previous one.
+    pub fn failure(&self) -> Option<Error> {
+        if let Some(proof) = self
+            .proofs
+            .iter()
+            .find(|proof| proof.verified == Some(false))
+        {
+            return Some(Error::Verification(format!(
+                "{} didn't verify",
+                proof.proof_path.display()
+            )));
+        }
+        (!self.proofs.iter().all(|proof| proof.linked)).then_some(Error::BrokenChain)
+    }
+}
+
+impl fmt::Display for ChainCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, proof) in self.proofs.iter().enumerate() {
+            let marker = if proof.linked { " " } else { "!" };
+            writeln!(f, "{} {}: {}", marker, index, proof.proof_path.display())?;
+            writeln!(
+                f,
+                "    prior_root: {}",
+                proof.prior_root.as_deref().unwrap_or("none")
+            )?;
+            writeln!(f, "    root:       {}", proof.root)?;
+            if let Some(verified) = proof.verified {
+                writeln!(f, "    verified:   {}", verified)?;
+            }
+        }
+        match self.failure() {
+            None => writeln!(f, "The {} proofs form a chain.", self.proofs.len()),
+            Some(Error::BrokenChain) => writeln!(
+                f,
+                "The proofs marked with ! don't start from the root the previous one ended at."
+            ),
+            Some(_) => writeln!(f, "Not every proof verified."),
+        }
+    }
+}
+
+/// The result of the `tree export` command.
+#[derive(Debug, Serialize)]
+pub struct TreeExportReport {
+    pub tree_db: PathBuf,
+    pub snapshot: TreeSnapshot,
+    /// Whether the snapshot has all of the tree's entries, see
+    /// [`HostTree::snapshot`](crate::tree::HostTree::snapshot).
+    pub complete: bool,
+    pub format: SnapshotFormat,
+    /// The snapshot encoded in `format`, printed when it isn't written to a file.
+    #[serde(skip)]
+    pub encoded: Option<String>,
+    /// The file the snapshot was written to, if any.
+    pub output: Option<PathBuf>,
+}
+
+impl fmt::Display for TreeExportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(encoded) = &self.encoded {
+            f.write_str(encoded)?;
+        }
+        if let Some(output) = &self.output {
+            writeln!(
+                f,
+                "Exported {} entries of {} to {}",
+                self.snapshot.entries.len(),
+                self.tree_db.display(),
+                output.display()
+            )?;
+        }
+        if !self.complete {
+            writeln!(
+                f,
+                "Warning: the entries don't add up to the root, some were inserted before the \
+                 tree indexed them"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of the `append` command.
+#[derive(Debug, Serialize)]
+pub struct AppendReport {
+    pub manifest: PathBuf,
+    /// The position of the proof in the chain, from 1.
+    pub position: usize,
+    /// The proof as recorded in the manifest.
+    pub entry: ManifestEntry,
+    pub prove: ProveReport,
+}
+
+impl fmt::Display for AppendReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.prove)?;
+        writeln!(
+            f,
+            "Appended the proof to {} as proof {} of the chain.",
+            self.manifest.display(),
+            self.position
+        )?;
+        writeln!(f, "prior_root: {}", self.entry.prior_root)?;
+        writeln!(f, "root: {}", self.entry.root)
+    }
+}
+
+/// The result of the `aggregate` command.
+#[derive(Debug, Serialize)]
+pub struct AggregateReport {
+    pub proof_path: PathBuf,
+    pub proof_system: ProofSystem,
+    /// The aggregated proofs, in the order the aggregator verified them.
+    pub proofs: Vec<PathBuf>,
+    /// The verification key hash of the fibonacci program the aggregated proofs are of.
+    pub vkey: String,
+    /// The verification key hash of the aggregator program, to verify the aggregated proof with.
+    pub aggregator_vkey: String,
+    /// The committed digest of the public values of the aggregated proofs, see
+    /// [`fibonacci_lib::aggregation_digest`].
+    pub public_values_digest: String,
+    /// The hex-encoded public values of the aggregated proof.
+    pub public_values: String,
+    /// Whether the aggregated proof commits to exactly the given proofs, in order.
+    pub covered: bool,
+    pub timings: Option<PhaseTimings>,
+}
+
+impl fmt::Display for AggregateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, proof) in self.proofs.iter().enumerate() {
+            writeln!(f, "{}: {}", index, proof.display())?;
+        }
+        writeln!(f, "Proof System: {:?}", self.proof_system)?;
+        writeln!(f, "Program Verification Key: {}", self.vkey)?;
+        writeln!(f, "Aggregator Verification Key: {}", self.aggregator_vkey)?;
+        writeln!(f, "Public Values Digest: {}", self.public_values_digest)?;
+        writeln!(f, "Public Values: {}", self.public_values)?;
+        if let Some(timings) = &self.timings {
+            write!(f, "{}", timings)?;
+        }
+        match self.covered {
+            true => writeln!(
+                f,
+                "Aggregated {} proofs into {}.",
+                self.proofs.len(),
+                self.proof_path.display()
+            ),
+            false => writeln!(
+                f,
+                "The proof saved to {} doesn't commit to the given proofs.",
+                self.proof_path.display()
+            ),
+        }
+    }
+}
+
+/// The result of the `wrap` command.
+#[derive(Debug, Serialize)]
+pub struct WrapReport {
+    /// The compressed proof that was wrapped.
+    pub compressed_path: PathBuf,
+    pub proof_path: PathBuf,
+    pub proof_system: ProofSystem,
+    /// The hex-encoded public values, the same as the compressed proof's.
+    pub public_values: String,
+    pub wrapping_secs: f64,
+}
+
+impl fmt::Display for WrapReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Compressed Proof: {}", self.compressed_path.display())?;
+        writeln!(f, "Proof System: {:?}", self.proof_system)?;
+        writeln!(f, "Public Values: {}", self.public_values)?;
+        writeln!(f, "Wrapping: {:.3}s", self.wrapping_secs)?;
+        writeln!(f, "Wrapped proof saved to {}.", self.proof_path.display())
+    }
+}
+
+/// The result of the `tree ls` command.
+#[derive(Debug, Serialize)]
+pub struct TreeLsReport {
+    pub tree_db: PathBuf,
+    /// The hex-encoded root the entries are under, or `None` for an empty tree.
+    pub root: Option<String>,
+    pub entries: Vec<TreeEntry>,
+    /// Whether the entries are all of the tree's, see
+    /// [`HostTree::entries`](crate::tree::HostTree::entries).
+    pub complete: bool,
+}
+
+impl fmt::Display for TreeLsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "root: {}", self.root.as_deref().unwrap_or("empty"))?;
+        for entry in &self.entries {
+            writeln!(f, "{} {}", entry.key, entry.leaf)?;
+        }
+        writeln!(f, "{} entries", self.entries.len())?;
+        if !self.complete {
+            writeln!(
+                f,
+                "Warning: the entries don't add up to the root, some were inserted before the \
+                 tree indexed them"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of the `tree get` command.
+#[derive(Debug, Serialize)]
+pub struct TreeGetReport {
+    pub tree_db: PathBuf,
+    /// The hex-encoded root the key was looked up under, or `None` for an empty tree.
+    pub root: Option<String>,
+    /// The hex-encoded key.
+    pub key: String,
+    /// The hex-encoded leaf at the key, or `None` if it isn't in the tree.
+    pub leaf: Option<String>,
+}
+
+impl fmt::Display for TreeGetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "root: {}", self.root.as_deref().unwrap_or("empty"))?;
+        match &self.leaf {
+            Some(leaf) => writeln!(f, "{}: {}", self.key, leaf),
+            None => writeln!(f, "{} isn't in the tree", self.key),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! A machine-readable description of the public values the program commits, so external tools can
+//! decode proofs without hardcoding the layout.
+
+use alloy_sol_types::SolStruct;
+use fibonacci_lib::{
+    InclusionProofStruct, PackedPublicValues, PublicValuesEncoding, PublicValuesStruct,
+    PUBLIC_VALUES_VERSION,
+};
+use serde::Serialize;
+
+use crate::solidity::public_values_fields;
+
+/// The layout of the public values, in every encoding the program can commit them in.
+#[derive(Debug, Serialize)]
+pub struct PublicValuesSchema {
+    /// The layout version committed as the `version` field.
+    pub version: u32,
+    /// The bytes32 verification key hash of the program the schema is for.
+    pub vkey: String,
+    pub encodings: Vec<EncodingSchema>,
+    /// The type of the ABI-encoded Merkle proof following the public values in every encoding,
+    /// e.g. `InclusionProofStruct(bool[] rights,bytes[] cuts)`.
+    pub inclusion_proof: String,
+    /// The layout of the exact fibonacci numbers following the Merkle proof when `big` is set, see
+    /// [`fibonacci_lib::encode_big_results`].
+    pub big_results: &'static str,
+}
+
+/// The layout of the public values in one encoding.
+#[derive(Debug, Serialize)]
+pub struct EncodingSchema {
+    pub encoding: PublicValuesEncoding,
+    /// The total size of the encoded public values, in bytes, without the Merkle proof after them.
+    pub size: usize,
+    pub fields: Vec<FieldSchema>,
+}
+
+/// Where a field is in the encoded public values.
+#[derive(Debug, Serialize)]
+pub struct FieldSchema {
+    pub name: String,
+    /// The Solidity type of the field, e.g. `uint32` or `uint8[32]`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The byte offset of the field from the start of the public values.
+    pub offset: usize,
+    /// The size of the encoded field, in bytes.
+    pub size: usize,
+}
+
+impl PublicValuesSchema {
+    /// The schema of the public values committed by the program with the verification key `vkey`.
+    pub fn new(vkey: String) -> Self {
+        let encodings = vec![
+            EncodingSchema::new(PublicValuesEncoding::Abi, abi_size),
+            EncodingSchema::new(PublicValuesEncoding::Borsh, packed_size),
+            EncodingSchema::new(PublicValuesEncoding::Ssz, packed_size),
+        ];
+        debug_assert_eq!(
+            Some(encodings[0].size),
+            <PublicValuesStruct as alloy_sol_types::SolType>::ENCODED_SIZE
+        );
+        debug_assert_eq!(encodings[1].size, PackedPublicValues::ENCODED_SIZE);
+
+        Self {
+            version: PUBLIC_VALUES_VERSION,
+            vkey,
+            encodings,
+            inclusion_proof: InclusionProofStruct::eip712_root_type().into_owned(),
+            big_results: "uint32 length of a, a, uint32 length of b, b, all big-endian",
+        }
+    }
+}
+
+impl EncodingSchema {
+    fn new(encoding: PublicValuesEncoding, field_size: fn(&str) -> usize) -> Self {
+        let mut offset = 0;
+        let fields = public_values_fields()
+            .into_iter()
+            .map(|(ty, name)| {
+                let size = field_size(&ty);
+                let field = FieldSchema {
+                    name,
+                    ty,
+                    offset,
+                    size,
+                };
+                offset += size;
+                field
+            })
+            .collect();
+        Self {
+            encoding,
+            size: offset,
+            fields,
+        }
+    }
+}
+
+/// The length and element type of a fixed-size array type like `uint8[32]`.
+fn array_type(ty: &str) -> Option<(&str, usize)> {
+    let (element, len) = ty.strip_suffix(']')?.rsplit_once('[')?;
+    let len = len
+        .parse()
+        .expect("arrays in the public values have a size");
+    Some((element, len))
+}
+
+/// The ABI-encoded size of a field, which pads every value to a 32-byte word.
+fn abi_size(ty: &str) -> usize {
+    match array_type(ty) {
+        Some((element, len)) => len * abi_size(element),
+        None => 32,
+    }
+}
+
+/// The Borsh- or SSZ-encoded size of a field, which both pack values without padding.
+fn packed_size(ty: &str) -> usize {
+    if let Some((element, len)) = array_type(ty) {
+        return len * packed_size(element);
+    }
+    if ty == "bool" {
+        return 1;
+    }
+    if let Some(bits) = ty.strip_prefix("uint").or_else(|| ty.strip_prefix("int")) {
+        return bits.parse::<usize>().expect("uint types have a bit size") / 8;
+    }
+    if let Some(bytes) = ty.strip_prefix("bytes") {
+        return bytes
+            .parse()
+            .expect("bytes types in the public values have a fixed size");
+    }
+    panic!("unsupported public values field type {}", ty)
+}
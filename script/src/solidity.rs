@@ -0,0 +1,99 @@
+//! Generating the Solidity definition of the public values from the Rust one, so the contracts
+//! can't drift from the layout the program commits.
+
+use std::path::PathBuf;
+
+use alloy_sol_types::SolStruct;
+use fibonacci_lib::{
+    InclusionProofStruct, PublicValuesEncoding, PublicValuesStruct, PUBLIC_VALUES_VERSION,
+};
+
+/// The file the contracts in `contracts/` import the public values definition from.
+pub fn default_solidity_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../contracts/src/PublicValues.sol")
+}
+
+/// The fields of [`PublicValuesStruct`] as `(type, name)` pairs, in the order they're encoded.
+pub fn public_values_fields() -> Vec<(String, String)> {
+    struct_fields::<PublicValuesStruct>()
+}
+
+/// The fields of a `sol!` struct as `(type, name)` pairs, in the order they're encoded.
+fn struct_fields<T: SolStruct>() -> Vec<(String, String)> {
+    // The EIP-712 type is e.g. `PublicValuesStruct(uint32 version,uint32 n,...)`, listing every
+    // field exactly as declared in the `sol!` macro.
+    let root_type = T::eip712_root_type();
+    let fields = root_type
+        .strip_prefix(T::NAME)
+        .and_then(|fields| fields.strip_prefix('('))
+        .and_then(|fields| fields.strip_suffix(')'))
+        .expect("the EIP-712 type lists the fields in parentheses");
+    fields
+        .split(',')
+        .map(|field| {
+            let (ty, name) = field
+                .split_once(' ')
+                .expect("every field has a type and a name");
+            (ty.to_string(), name.to_string())
+        })
+        .collect()
+}
+
+/// The fields of a `sol!` struct as the body of a Solidity struct definition.
+fn struct_body<T: SolStruct>() -> String {
+    struct_fields::<T>()
+        .iter()
+        .map(|(ty, name)| format!("    {} {};\n", ty, name))
+        .collect()
+}
+
+/// A Solidity file defining [`PublicValuesStruct`] and [`InclusionProofStruct`], and a library
+/// decoding them.
+pub fn public_values_solidity() -> String {
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+// Generated by `cargo run --release -- gen-sol` from `PublicValuesStruct` in `lib/`. Do not edit.
+pragma solidity ^0.8.20;
+
+/// @notice The public values committed by the fibonacci program.
+struct {name} {{
+{fields}}}
+
+/// @notice The monotree Merkle proof of the committed leaf, committed right after the public
+///         values. Each step is a cut of a sibling node and whether the path goes right at it,
+///         from the root down. See `MonotreeProof` for checking it.
+struct {proof_name} {{
+{proof_fields}}}
+
+/// @title PublicValues.
+/// @notice Decoding the public values committed by the fibonacci program.
+library PublicValues {{
+    /// @notice The layout version of the public values this library decodes.
+    uint32 internal constant VERSION = {version};
+
+    /// @notice The size of the ABI-encoded public values, i.e. where the inclusion proof starts.
+    uint256 internal constant STRUCT_SIZE = {struct_size};
+
+    /// @notice Decode the ABI-encoded public values of a proof, rejecting other layout versions.
+    /// @param _publicValues The encoded public values.
+    function decode(bytes calldata _publicValues) internal pure returns ({name} memory) {{
+        {name} memory publicValues = abi.decode(_publicValues, ({name}));
+        require(publicValues.version == VERSION, "unsupported public values version");
+        return publicValues;
+    }}
+
+    /// @notice Decode the inclusion proof committed after the ABI-encoded public values.
+    /// @param _publicValues The encoded public values.
+    function decodeInclusionProof(bytes calldata _publicValues) internal pure returns ({proof_name} memory) {{
+        return abi.decode(_publicValues[STRUCT_SIZE:], ({proof_name}));
+    }}
+}}
+"#,
+        name = PublicValuesStruct::NAME,
+        fields = struct_body::<PublicValuesStruct>(),
+        proof_name = InclusionProofStruct::NAME,
+        proof_fields = struct_body::<InclusionProofStruct>(),
+        version = PUBLIC_VALUES_VERSION,
+        struct_size = PublicValuesEncoding::Abi.struct_size(),
+    )
+}
@@ -0,0 +1,118 @@
+//! The content-addressed layout of `prove --proof-dir`, which saves each proof under the
+//! verification key of the program and a hash of its inputs, so proofs of different inputs, or of
+//! different builds of the program, never overwrite each other.
+
+use std::path::{Path, PathBuf};
+
+use fibonacci_lib::FibonacciInputs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::proof::ProofSystem;
+
+/// A directory of proofs saved as `<vkey_hash>/<input_hash>.proof`, with an index of them.
+pub struct ProofStore {
+    dir: PathBuf,
+}
+
+/// The proofs saved in a [`ProofStore`], in the order they were first saved.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoreIndex {
+    pub proofs: Vec<StoreEntry>,
+}
+
+/// A proof recorded in a [`StoreIndex`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoreEntry {
+    /// The bytes32 hash of the verification key of the program the proof is of.
+    pub vkey_hash: String,
+    /// The hex-encoded hash of the inputs, see [`input_hash`].
+    pub input_hash: String,
+    pub proof_system: ProofSystem,
+    pub inputs: FibonacciInputs,
+    pub proof_path: PathBuf,
+    /// The raw and decoded public values saved next to the proof with `--save-public-values`.
+    #[serde(default)]
+    pub public_values_paths: Vec<PathBuf>,
+}
+
+/// The hex-encoded SHA-256 hash of the JSON encoding of `inputs` without their timestamp, which
+/// tells any two sets of inputs apart but for when they were proven, so proving the same inputs
+/// again saves to the same file rather than piling up copies.
+pub fn input_hash(inputs: &FibonacciInputs) -> String {
+    let inputs = FibonacciInputs {
+        timestamp: 0,
+        ..inputs.clone()
+    };
+    let json = serde_json::to_vec(&inputs).expect("inputs serialize to JSON");
+    hex::encode(Sha256::digest(json))
+}
+
+impl ProofStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// The file the proof of `inputs` with `proof_system` by the program with `vkey_hash` is saved
+    /// to. Core and compressed proofs get their own extension, like with
+    /// [`ProofSystem::default_proof_path`].
+    pub fn proof_path(
+        &self,
+        vkey_hash: &str,
+        inputs: &FibonacciInputs,
+        proof_system: ProofSystem,
+    ) -> PathBuf {
+        let extension = match proof_system {
+            ProofSystem::Core => "core.proof",
+            ProofSystem::Compressed => "compressed.proof",
+            ProofSystem::Plonk | ProofSystem::Groth16 => "proof",
+        };
+        self.dir
+            .join(vkey_hash)
+            .join(format!("{}.{}", input_hash(inputs), extension))
+    }
+
+    /// The file the verifying key of the program with `vkey_hash` is saved to with `--save-vk`.
+    pub fn vk_path(&self, vkey_hash: &str) -> PathBuf {
+        self.dir.join(vkey_hash).join("vk.json")
+    }
+
+    /// The index of the proofs in the store.
+    pub fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+}
+
+impl StoreIndex {
+    /// Load the index at `path`, or start an empty one if there is none yet.
+    pub fn load_or_default(path: &Path) -> std::io::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Record `entry`, replacing the entry of the proof saved to the same file, if any.
+    pub fn insert(&mut self, entry: StoreEntry) {
+        match self
+            .proofs
+            .iter_mut()
+            .find(|existing| existing.proof_path == entry.proof_path)
+        {
+            Some(existing) => *existing = entry,
+            None => self.proofs.push(entry),
+        }
+    }
+}
@@ -0,0 +1,652 @@
+//! The persistent tree on the host, whose state the program's proofs carry forward.
+//!
+//! The tree is stored in a sled database. Rather than the whole tree, the program is given the
+//! nodes it reads when inserting into it, recorded while making the same inserts on the host.
+//! Monotree can't list the entries of a tree, so the database also indexes the current leaf of
+//! every key, for exporting snapshots of the tree, and every key ever inserted, for listing the
+//! entries under older roots.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::Path,
+    rc::Rc,
+};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use fibonacci_lib::{
+    add_offset, namespaces_digest, proof_path, FibonacciInputs, Namespace, TreeHasher, TreeWitness,
+};
+use monotree::{
+    database::{Database, MemoryDB},
+    hasher::*,
+    Errors, Hash, Monotree,
+};
+use serde::Serialize;
+
+/// The key the current root is stored under, which can't collide with the 32-byte node hashes.
+const ROOT_KEY: &[u8] = b"root";
+
+/// The prefix of the keys the roots of the trees of namespaces are stored under, followed by the
+/// name of the namespace.
+const NAMESPACE_ROOT_PREFIX: &[u8] = b"root:";
+
+/// The key the hasher the tree is built with is stored under.
+const HASHER_KEY: &[u8] = b"hasher";
+
+/// The name of the sled tree indexing the current leaf of every key.
+const ENTRIES_TREE: &str = "entries";
+
+/// The name of the sled tree indexing every key ever inserted into the tree or the trees of its
+/// namespaces, with empty values.
+const KEYS_TREE: &str = "keys";
+
+thread_local! {
+    /// The database and recording the next [`RecordingDB`] is created with. Monotree creates its
+    /// database itself, given only a path, so they're handed over through here.
+    static NEXT_DB: RefCell<Option<(sled::Db, Rc<RefCell<Recording>>)>> =
+        const { RefCell::new(None) };
+
+    /// The hasher the next [`SelectedHasher`] hashes with, handed over like [`NEXT_DB`].
+    static NEXT_HASHER: Cell<TreeHasher> = const { Cell::new(TreeHasher::Blake3) };
+
+    /// The number of digests taken by every [`SelectedHasher`] so far.
+    static HASH_COUNT: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A monotree hasher dispatching to the [`TreeHasher`] the tree is opened with, like the program's.
+pub struct SelectedHasher(TreeHasher);
+
+impl SelectedHasher {
+    /// A hasher hashing with `hasher`, for hashing outside of a tree.
+    pub fn with(hasher: TreeHasher) -> Self {
+        Self(hasher)
+    }
+}
+
+impl Hasher for SelectedHasher {
+    fn new() -> Self {
+        Self(NEXT_HASHER.with(Cell::get))
+    }
+
+    fn digest(&self, bytes: &[u8]) -> Hash {
+        HASH_COUNT.with(|count| count.set(count.get() + 1));
+        match self.0 {
+            TreeHasher::Blake3 => Blake3::new().digest(bytes),
+            TreeHasher::Blake2b => Blake2b::new().digest(bytes),
+            TreeHasher::Sha2 => Sha2::new().digest(bytes),
+            #[cfg(feature = "poseidon")]
+            TreeHasher::Poseidon => fibonacci_lib::poseidon_digest(bytes),
+        }
+    }
+}
+
+/// The nodes read from the tree while recording.
+#[derive(Debug, Default)]
+struct Recording {
+    active: bool,
+    /// The nodes read that were already in the tree, by hash.
+    read: BTreeMap<Hash, Vec<u8>>,
+    /// The nodes written since recording started, which the program writes itself.
+    written: HashSet<Hash>,
+    /// The number of nodes read and written since recording started, repeats included.
+    reads: u64,
+    writes: u64,
+}
+
+/// A monotree database over a sled database, recording the nodes read from it.
+pub struct RecordingDB {
+    db: sled::Db,
+    recording: Rc<RefCell<Recording>>,
+}
+
+impl Database for RecordingDB {
+    fn new(_dbpath: &str) -> Self {
+        let (db, recording) = NEXT_DB
+            .with(|next| next.borrow_mut().take())
+            .expect("open the tree with HostTree::open");
+        Self { db, recording }
+    }
+
+    fn get(&mut self, key: &[u8]) -> monotree::Result<Option<Vec<u8>>> {
+        let value = self
+            .db
+            .get(key)
+            .map_err(|err| Errors::new(&err.to_string()))?
+            .map(|value| value.to_vec());
+        let mut recording = self.recording.borrow_mut();
+        if recording.active && value.is_some() {
+            recording.reads += 1;
+        }
+        if let (true, Some(value), Ok(hash)) = (recording.active, &value, Hash::try_from(key)) {
+            if !recording.written.contains(&hash) {
+                recording.read.insert(hash, value.clone());
+            }
+        }
+        Ok(value)
+    }
+
+    fn put(&mut self, key: &[u8], value: Vec<u8>) -> monotree::Result<()> {
+        let mut recording = self.recording.borrow_mut();
+        if let (true, Ok(hash)) = (recording.active, Hash::try_from(key)) {
+            recording.writes += 1;
+            recording.written.insert(hash);
+        }
+        self.db
+            .insert(key, value)
+            .map_err(|err| Errors::new(&err.to_string()))?;
+        Ok(())
+    }
+
+    fn delete(&mut self, _key: &[u8]) -> monotree::Result<()> {
+        // Nodes are addressed by their hash, and older roots may still point to them, so they're
+        // kept around.
+        Ok(())
+    }
+
+    fn init_batch(&mut self) -> monotree::Result<()> {
+        Ok(())
+    }
+
+    fn finish_batch(&mut self) -> monotree::Result<()> {
+        Ok(())
+    }
+}
+
+/// The tree persisted on the host, along with its current root.
+pub struct HostTree {
+    db: sled::Db,
+    hasher: TreeHasher,
+    tree: Monotree<RecordingDB, SelectedHasher>,
+    recording: Rc<RefCell<Recording>>,
+    /// The index of the current leaf of every key.
+    entries: sled::Tree,
+    /// The index of every key ever inserted.
+    keys: sled::Tree,
+    /// The changes to the index made by the last [`HostTree::prepare`], as `(key, leaf)` pairs with
+    /// no leaf for removed keys, applied once they're committed.
+    pending: Vec<(Hash, Option<Hash>)>,
+    /// The namespaces updated by the last [`HostTree::prepare`], with their prior and new roots,
+    /// which are stored once they're committed.
+    pending_namespaces: Vec<(Namespace, Option<Hash>, Option<Hash>)>,
+    /// The tree work of the last [`HostTree::prepare`].
+    stats: Option<TreeStats>,
+}
+
+/// How much tree work the program does for a set of inputs, counted by making the same updates on
+/// the host, so it only approximates the program's own.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct TreeStats {
+    /// The digests taken while updating the tree, looking up the entry and proving it.
+    pub hashes: u64,
+    /// The nodes read from the tree, repeats included.
+    pub node_reads: u64,
+    /// The nodes written to the tree, repeats included.
+    pub node_writes: u64,
+    /// The nodes in the witness, each of which the program hashes once more to check it.
+    pub witness_nodes: u64,
+}
+
+/// A snapshot of a host tree: its root and all its entries.
+#[derive(Debug, Serialize)]
+pub struct TreeSnapshot {
+    pub hasher: TreeHasher,
+    /// The hex-encoded root of the tree, or `None` if it's empty.
+    pub root: Option<String>,
+    pub entries: Vec<TreeEntry>,
+}
+
+/// An entry of a host tree.
+#[derive(Debug, Serialize)]
+pub struct TreeEntry {
+    /// The hex-encoded key.
+    pub key: String,
+    /// The hex-encoded leaf at the key.
+    pub leaf: String,
+}
+
+/// The formats a [`TreeSnapshot`] can be exported in.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotFormat {
+    #[default]
+    Json,
+    /// One `root,key,leaf` row per entry, after a header row.
+    Csv,
+}
+
+impl TreeSnapshot {
+    /// Encode the snapshot in `format`.
+    pub fn encode(&self, format: SnapshotFormat) -> String {
+        match format {
+            SnapshotFormat::Json => {
+                let json =
+                    serde_json::to_string_pretty(self).expect("failed to serialize the snapshot");
+                format!("{}\n", json)
+            }
+            SnapshotFormat::Csv => {
+                let root = self.root.as_deref().unwrap_or_default();
+                let rows = self
+                    .entries
+                    .iter()
+                    .map(|entry| format!("{},{},{}\n", root, entry.key, entry.leaf));
+                std::iter::once("root,key,leaf\n".to_string())
+                    .chain(rows)
+                    .collect()
+            }
+        }
+    }
+}
+
+impl HostTree {
+    /// Open the tree stored at `path`, creating an empty one built with `hasher` if there is none.
+    ///
+    /// Fails if the tree is built with another hasher, whose nodes the program couldn't read.
+    pub fn open(path: &Path, hasher: TreeHasher) -> anyhow::Result<Self> {
+        let db = sled::open(path)
+            .with_context(|| format!("failed to open the tree database {}", path.display()))?;
+        Self::from_db(db, path, hasher)
+    }
+
+    /// An empty tree built with `hasher`, removed once it's dropped.
+    pub fn temporary(hasher: TreeHasher) -> anyhow::Result<Self> {
+        let db = sled::Config::new()
+            .temporary(true)
+            .open()
+            .context("failed to open a temporary tree database")?;
+        Self::from_db(db, Path::new(""), hasher)
+    }
+
+    fn from_db(db: sled::Db, path: &Path, hasher: TreeHasher) -> anyhow::Result<Self> {
+        match db.get(HASHER_KEY)? {
+            Some(stored) => {
+                let stored: TreeHasher = String::from_utf8_lossy(&stored)
+                    .parse()
+                    .map_err(anyhow::Error::msg)?;
+                anyhow::ensure!(
+                    stored == hasher,
+                    "the tree {} is built with {}, not {}",
+                    path.display(),
+                    stored,
+                    hasher
+                );
+            }
+            None => {
+                db.insert(HASHER_KEY, hasher.to_string().as_bytes())?;
+            }
+        }
+        NEXT_HASHER.with(|next| next.set(hasher));
+        let recording = Rc::new(RefCell::new(Recording::default()));
+        NEXT_DB.with(|next| *next.borrow_mut() = Some((db.clone(), recording.clone())));
+        let tree = Monotree::new(&path.to_string_lossy());
+        let entries = db.open_tree(ENTRIES_TREE)?;
+        let keys = db.open_tree(KEYS_TREE)?;
+        Ok(Self {
+            db,
+            hasher,
+            tree,
+            recording,
+            entries,
+            keys,
+            pending: Vec::new(),
+            pending_namespaces: Vec::new(),
+            stats: None,
+        })
+    }
+
+    /// Open the tree stored at `path` with the hasher it's built with, failing if there is none.
+    pub fn open_existing(path: &Path) -> anyhow::Result<Self> {
+        anyhow::ensure!(path.exists(), "there is no tree at {}", path.display());
+        let hasher = sled::open(path)
+            .with_context(|| format!("failed to open the tree database {}", path.display()))?
+            .get(HASHER_KEY)?
+            .map(|stored| String::from_utf8_lossy(&stored).parse::<TreeHasher>())
+            .transpose()
+            .map_err(anyhow::Error::msg)?
+            .unwrap_or_default();
+        Self::open(path, hasher)
+    }
+
+    /// The hasher the tree is built with.
+    pub fn hasher(&self) -> TreeHasher {
+        self.hasher
+    }
+
+    /// The tree work of the last [`HostTree::prepare`], if any.
+    pub fn stats(&self) -> Option<TreeStats> {
+        self.stats
+    }
+
+    /// The current root of the tree, or `None` if it's empty.
+    pub fn root(&self) -> anyhow::Result<Option<Hash>> {
+        let root = self.db.get(ROOT_KEY)?;
+        root.map(|root| Hash::try_from(root.as_ref()).context("the stored root isn't 32 bytes"))
+            .transpose()
+    }
+
+    /// The current root of the tree of the namespace `name`, or `None` if it's empty.
+    ///
+    /// The trees of all namespaces share the nodes of the main one, but each has its own root.
+    pub fn namespace_root(&self, name: &str) -> anyhow::Result<Option<Hash>> {
+        let root = self
+            .db
+            .get([NAMESPACE_ROOT_PREFIX, name.as_bytes()].concat())?;
+        root.map(|root| Hash::try_from(root.as_ref()).context("the stored root isn't 32 bytes"))
+            .transpose()
+    }
+
+    /// The leaf at `key` in the tree with `root`, which may be any root the tree or the trees of
+    /// its namespaces ever had, or `None` if the key isn't in it.
+    pub fn get(&mut self, root: Option<&Hash>, key: &Hash) -> anyhow::Result<Option<Hash>> {
+        self.check_root(root)?;
+        self.tree
+            .get(root, key)
+            .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))
+    }
+
+    /// The entries of the tree with `root`, which may be any root the tree or the trees of its
+    /// namespaces ever had, with whether they're all of its entries, i.e. whether a tree of only
+    /// them has the same root.
+    ///
+    /// Monotree can't list the entries under a root, so every key the tree indexed is looked up
+    /// under it instead. Keys inserted before the tree indexed them are missing, in which case the
+    /// entries aren't all of them.
+    pub fn entries(&mut self, root: Option<&Hash>) -> anyhow::Result<(Vec<TreeEntry>, bool)> {
+        self.check_root(root)?;
+        let mut known = BTreeSet::new();
+        for key in self.keys.iter().keys().chain(self.entries.iter().keys()) {
+            known.insert(Hash::try_from(key?.as_ref()).context("an indexed key isn't 32 bytes")?);
+        }
+        let mut keys = Vec::new();
+        let mut leaves = Vec::new();
+        for key in known {
+            let leaf = self
+                .tree
+                .get(root, &key)
+                .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?;
+            if let Some(leaf) = leaf {
+                keys.push(key);
+                leaves.push(leaf);
+            }
+        }
+        let complete = rebuilt_root(&keys, &leaves)?.as_ref() == root;
+        Ok((tree_entries(&keys, &leaves), complete))
+    }
+
+    /// Fail if there is no node for `root`, which monotree would fail to read it without.
+    fn check_root(&self, root: Option<&Hash>) -> anyhow::Result<()> {
+        if let Some(root) = root {
+            anyhow::ensure!(
+                self.db.contains_key(root)?,
+                "the tree never had the root {}",
+                hex::encode(root)
+            );
+        }
+        Ok(())
+    }
+
+    /// The `namespaces_digest` the program commits for the inputs of the last
+    /// [`HostTree::prepare`].
+    pub fn namespaces_digest(&self) -> Hash {
+        let namespaces = self
+            .pending_namespaces
+            .iter()
+            .map(|(namespace, prior_root, root)| (namespace, *prior_root, *root));
+        namespaces_digest(namespaces).0
+    }
+
+    /// Make the inserts the program makes for `inputs` from the current root, returning the witness
+    /// the program needs to make them and the root it ends up with.
+    ///
+    /// With `path_witness`, the witness has the Merkle proof of the key of the leaf update instead
+    /// of the nodes on its path, which the program derives from the proof.
+    ///
+    /// The new root isn't stored until it's [committed](HostTree::commit), e.g. once the proof of
+    /// the inserts is saved.
+    pub fn prepare(
+        &mut self,
+        inputs: &FibonacciInputs,
+        path_witness: bool,
+    ) -> anyhow::Result<(TreeWitness, Hash)> {
+        anyhow::ensure!(
+            !inputs.checked || inputs.checked_results().is_ok(),
+            "the fibonacci numbers overflow, so the program would abort without updating the tree"
+        );
+        let prior_root = self.root()?;
+        let mut names = HashSet::new();
+        let mut namespace_roots = Vec::with_capacity(inputs.namespaces.len());
+        for namespace in &inputs.namespaces {
+            anyhow::ensure!(
+                names.insert(&namespace.name),
+                "the namespace {:?} is given more than once",
+                namespace.name
+            );
+            namespace_roots.push(self.namespace_root(&namespace.name)?);
+        }
+        let leaf_update_proof = match (path_witness, &inputs.leaf_update) {
+            (false, _) => None,
+            (true, None) => anyhow::bail!("a path witness needs a leaf update"),
+            (true, Some(update)) => Some(
+                self.tree
+                    .get_merkle_proof(prior_root.as_ref(), &update.key)
+                    .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?
+                    .context("the key to update isn't in the tree")?,
+            ),
+        };
+        *self.recording.borrow_mut() = Recording {
+            active: true,
+            ..Recording::default()
+        };
+        HASH_COUNT.with(|count| count.set(0));
+
+        // Mirror the program: look up the absent key, apply the updates, changing the updated leaf,
+        // inserting the batch of entries in one go, then removing keys one by one and inserting
+        // the entry, then insert into the tree of every namespace, then look up the entry and prove
+        // it, if it was inserted.
+        let mut root = prior_root;
+        if let Some(absent_key) = &inputs.absent_key {
+            let found = self
+                .tree
+                .get(root.as_ref(), absent_key)
+                .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?;
+            anyhow::ensure!(
+                found.is_none(),
+                "the key {} is in the tree",
+                hex::encode(absent_key)
+            );
+        }
+        let (a, b) = inputs.results();
+        let (_, b, _) = add_offset(a, b, inputs.offset);
+        let updates = inputs.updates(b);
+        let (_, rest) = updates.split_at(inputs.leaf_update.is_some() as usize);
+        let (batch, rest) = rest.split_at(inputs.batch_len());
+        let (removals, entry) = rest.split_at(inputs.removals.len());
+        if let Some(update) = &inputs.leaf_update {
+            let found = self
+                .tree
+                .get(root.as_ref(), &update.key)
+                .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?;
+            anyhow::ensure!(
+                found == Some(update.old_leaf),
+                "the leaf at {} isn't {}",
+                hex::encode(update.key),
+                hex::encode(update.old_leaf)
+            );
+            root = self
+                .tree
+                .insert(root.as_ref(), &update.key, &update.new_leaf)
+                .map_err(|err| anyhow::anyhow!("failed to update the tree: {:?}", err))?;
+        }
+        if !batch.is_empty() {
+            let (keys, leaves): (Vec<_>, Vec<_>) = batch.iter().copied().unzip();
+            root = self
+                .tree
+                .inserts(root.as_ref(), &keys, &leaves)
+                .map_err(|err| anyhow::anyhow!("failed to insert into the tree: {:?}", err))?;
+        }
+        for (key, _) in removals {
+            let found = self
+                .tree
+                .get(root.as_ref(), key)
+                .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?;
+            anyhow::ensure!(
+                found.is_some(),
+                "the key {} to remove isn't in the tree",
+                hex::encode(key)
+            );
+            root = self
+                .tree
+                .remove(root.as_ref(), key)
+                .map_err(|err| anyhow::anyhow!("failed to remove from the tree: {:?}", err))?;
+        }
+        if let Some((key, leaf)) = entry.first() {
+            root = self
+                .tree
+                .insert(root.as_ref(), key, leaf)
+                .map_err(|err| anyhow::anyhow!("failed to insert into the tree: {:?}", err))?;
+        }
+        let mut pending_namespaces = Vec::with_capacity(inputs.namespaces.len());
+        for (namespace, prior_root) in inputs.namespaces.iter().zip(&namespace_roots) {
+            let root = if namespace.entries.is_empty() {
+                *prior_root
+            } else {
+                let (keys, leaves): (Vec<_>, Vec<_>) = namespace.entries.iter().copied().unzip();
+                self.tree
+                    .inserts(prior_root.as_ref(), &keys, &leaves)
+                    .map_err(|err| anyhow::anyhow!("failed to insert into the tree: {:?}", err))?
+            };
+            pending_namespaces.push((namespace.clone(), *prior_root, root));
+        }
+        if let Some((key, leaf)) = entry.first() {
+            let found = self
+                .tree
+                .get(root.as_ref(), key)
+                .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?;
+            anyhow::ensure!(
+                found == Some(*leaf),
+                "the leaf at {} isn't {} after inserting it",
+                hex::encode(key),
+                hex::encode(leaf)
+            );
+            self.tree
+                .get_merkle_proof(root.as_ref(), key)
+                .map_err(|err| anyhow::anyhow!("failed to read the tree: {:?}", err))?;
+        }
+
+        // Leave out the nodes the program derives from the proof of the key to update.
+        self.pending_namespaces = pending_namespaces;
+        let mut recording = std::mem::take(&mut *self.recording.borrow_mut());
+        let hashes = HASH_COUNT.with(Cell::get);
+        if let (Some(update), Some(proof)) = (&inputs.leaf_update, &leaf_update_proof) {
+            let hasher = SelectedHasher::new();
+            let (_, path) = proof_path(|bytes| hasher.digest(bytes), &update.old_leaf, proof)
+                .context("monotree made a malformed Merkle proof of the key to update")?;
+            for (hash, _) in path {
+                recording.read.remove(&hash);
+            }
+        }
+        let witness = TreeWitness {
+            root: prior_root,
+            nodes: recording.read.into_iter().collect(),
+            leaf_update_proof,
+            namespace_roots,
+        };
+        self.stats = Some(TreeStats {
+            hashes,
+            node_reads: recording.reads,
+            node_writes: recording.writes,
+            witness_nodes: witness.nodes.len() as u64,
+        });
+
+        // Index the entries the updates leave behind once they're committed.
+        let leaf_update = inputs
+            .leaf_update
+            .map(|update| (update.key, Some(update.new_leaf)));
+        self.pending = leaf_update
+            .into_iter()
+            .chain(batch.iter().map(|(key, leaf)| (*key, Some(*leaf))))
+            .chain(removals.iter().map(|(key, _)| (*key, None)))
+            .chain(entry.iter().map(|(key, leaf)| (*key, Some(*leaf))))
+            .collect();
+        let root = root.context("the tree is still empty, pass a non-zero offset")?;
+        Ok((witness, root))
+    }
+
+    /// Make `root` the current root of the tree, indexing the entries the last
+    /// [`HostTree::prepare`] left behind and storing the new roots of its namespaces.
+    ///
+    /// Only the entries of the main tree are indexed.
+    pub fn commit(&mut self, root: &Hash) -> anyhow::Result<()> {
+        let mut batch = sled::Batch::default();
+        for (key, leaf) in std::mem::take(&mut self.pending) {
+            match leaf {
+                Some(leaf) => {
+                    batch.insert(key.as_slice(), leaf.as_slice());
+                    self.keys.insert(key, &[] as &[u8])?;
+                }
+                None => batch.remove(key.as_slice()),
+            }
+        }
+        self.entries.apply_batch(batch)?;
+        for (namespace, _, root) in std::mem::take(&mut self.pending_namespaces) {
+            for (key, _) in &namespace.entries {
+                self.keys.insert(key, &[] as &[u8])?;
+            }
+            if let Some(root) = root {
+                let key = [NAMESPACE_ROOT_PREFIX, namespace.name.as_bytes()].concat();
+                self.db.insert(key, root.as_slice())?;
+            }
+        }
+        self.db.insert(ROOT_KEY, root.as_slice())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// A snapshot of the tree at its current root, with whether its entries are all of the
+    /// tree's, i.e. whether a tree of only them has the same root.
+    ///
+    /// Entries inserted before the tree indexed them are missing, in which case they aren't.
+    pub fn snapshot(&self) -> anyhow::Result<(TreeSnapshot, bool)> {
+        let root = self.root()?;
+        let mut keys = Vec::new();
+        let mut leaves = Vec::new();
+        for entry in self.entries.iter() {
+            let (key, leaf) = entry?;
+            keys.push(Hash::try_from(key.as_ref()).context("an indexed key isn't 32 bytes")?);
+            leaves.push(Hash::try_from(leaf.as_ref()).context("an indexed leaf isn't 32 bytes")?);
+        }
+
+        let snapshot = TreeSnapshot {
+            hasher: self.hasher,
+            root: root.map(hex::encode),
+            entries: tree_entries(&keys, &leaves),
+        };
+        Ok((snapshot, rebuilt_root(&keys, &leaves)? == root))
+    }
+}
+
+/// The root of a tree of only the entries with `keys` and `leaves`, built with the hasher of the
+/// host tree last opened.
+///
+/// Monotree's roots don't depend on the order of the inserts, so rebuilding a tree from some of its
+/// entries yields the same root if and only if they're all of them.
+fn rebuilt_root(keys: &[Hash], leaves: &[Hash]) -> anyhow::Result<Option<Hash>> {
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    Monotree::<MemoryDB, SelectedHasher>::new("")
+        .inserts(None, keys, leaves)
+        .map_err(|err| anyhow::anyhow!("failed to rebuild the tree: {:?}", err))
+}
+
+/// The hex-encoded entries with `keys` and `leaves`.
+fn tree_entries(keys: &[Hash], leaves: &[Hash]) -> Vec<TreeEntry> {
+    keys.iter()
+        .zip(leaves)
+        .map(|(key, leaf)| TreeEntry {
+            key: hex::encode(key),
+            leaf: hex::encode(leaf),
+        })
+        .collect()
+}
@@ -0,0 +1,30 @@
+//! Checks what the proof cache of `prove` is keyed by, which needs neither the program nor a
+//! prover.
+
+use fibonacci_lib::{FibonacciInputs, TreeWitness};
+use fibonacci_script::{cache::cache_key, proof::ProofSystem, FIBONACCI_ELF};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn runs_without_a_timestamp_hit_the_cache(n: u32, offset: u32, timestamps: (u64, u64)) {
+        let key = |timestamp, default_timestamp| {
+            let inputs = FibonacciInputs {
+                n,
+                offset,
+                timestamp,
+                ..FibonacciInputs::default()
+            };
+            let witness = TreeWitness::default();
+            cache_key(FIBONACCI_ELF, &inputs, &witness, ProofSystem::Groth16, default_timestamp)
+        };
+        // Two runs defaulting to the time they're proven at share the proof...
+        prop_assert_eq!(key(timestamps.0, true), key(timestamps.1, true));
+        // ...which isn't the proof of inputs given a timestamp, not even of the epoch.
+        prop_assert_ne!(key(timestamps.0, true), key(timestamps.0, false));
+        prop_assert_ne!(key(0, true), key(0, false));
+        if timestamps.0 != timestamps.1 {
+            prop_assert_ne!(key(timestamps.0, false), key(timestamps.1, false));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Executes the program for random inputs and checks the public values it commits against the ones
+//! the lib computes for the same inputs, so the program and the lib can't drift apart unnoticed.
+
+use fibonacci_lib::{
+    verify_public_values, FibonacciInputs, FibonacciMethod, LeafUpdate, PublicValuesEncoding,
+    TreeHasher, TreeWitness,
+};
+use fibonacci_script::{inputs::to_stdin, tree::HostTree, FIBONACCI_ELF};
+use proptest::prelude::*;
+use sp1_sdk::ProverClient;
+use std::process::Command;
+
+fn encoding() -> impl Strategy<Value = PublicValuesEncoding> {
+    prop_oneof![
+        Just(PublicValuesEncoding::Abi),
+        Just(PublicValuesEncoding::Borsh),
+        Just(PublicValuesEncoding::Ssz),
+    ]
+}
+
+fn method() -> impl Strategy<Value = FibonacciMethod> {
+    prop_oneof![
+        Just(FibonacciMethod::Iterative),
+        Just(FibonacciMethod::FastDoubling),
+    ]
+}
+
+/// Inputs for the program starting from an empty tree, which takes a non-zero offset to insert
+/// anything into it.
+fn inputs() -> impl Strategy<Value = FibonacciInputs> {
+    (
+        0u32..200,
+        1u32..,
+        any::<Option<[u8; 32]>>(),
+        any::<u64>(),
+        any::<u64>(),
+        encoding(),
+        method(),
+        any::<bool>(),
+        prop::option::of(1u64..),
+        any::<bool>(),
+        any::<bool>(),
+        prop::option::of(1u64..1_000),
+    )
+        .prop_map(
+            |(
+                n,
+                offset,
+                salt,
+                nonce,
+                timestamp,
+                encoding,
+                method,
+                big,
+                modulus,
+                checked,
+                wide,
+                pisano,
+            )| FibonacciInputs {
+                n,
+                offset,
+                salt,
+                nonce,
+                timestamp,
+                encoding,
+                method,
+                big,
+                modulus,
+                checked,
+                wide,
+                pisano,
+                ..FibonacciInputs::default()
+            },
+        )
+}
+
+proptest! {
+    // Every case executes the program, so run only a few.
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn program_commits_what_the_lib_computes(inputs in inputs()) {
+        let client = ProverClient::local();
+        let stdin = to_stdin(&inputs, &TreeWitness::default());
+        let (output, _) = client.execute(FIBONACCI_ELF, stdin).run().unwrap();
+        if let Err(err) = verify_public_values(output.as_slice(), &inputs) {
+            prop_assert!(false, "{} for {:?}", err, inputs);
+        }
+    }
+}
+
+/// Sets up a tree holding the same leaf at two keys, neither of them the key the program inserts
+/// its own entry at, then executes the program changing the leaf at the first one along its Merkle
+/// proof, passed off as the proof of the second one if `forge`, panicking if the program does.
+fn execute_leaf_update(forge: bool) {
+    let (key, other_key, leaf) = ([5; 32], [6; 32], [3; 32]);
+    let mut tree = HostTree::temporary(TreeHasher::Blake3).unwrap();
+    let setup = FibonacciInputs {
+        entries: vec![(key, leaf), (other_key, leaf)],
+        ..FibonacciInputs::default()
+    };
+    let (_, root) = tree.prepare(&setup, false).unwrap();
+    tree.commit(&root).unwrap();
+
+    let update = LeafUpdate {
+        key,
+        old_leaf: leaf,
+        new_leaf: [4; 32],
+    };
+    let inputs = FibonacciInputs {
+        offset: 1,
+        leaf_update: Some(update),
+        ..FibonacciInputs::default()
+    };
+    let (witness, _) = tree.prepare(&inputs, true).unwrap();
+    let inputs = match forge {
+        true => FibonacciInputs {
+            leaf_update: Some(LeafUpdate {
+                key: other_key,
+                ..update
+            }),
+            ..inputs
+        },
+        false => inputs,
+    };
+    let stdin = to_stdin(&inputs, &witness);
+    ProverClient::local()
+        .execute(FIBONACCI_ELF, stdin)
+        .run()
+        .unwrap();
+}
+
+/// The program changes the leaf at the key to update along the Merkle proof the host sends, which
+/// must be the proof of that key, even though the proof of another key holding the same leaf leads
+/// to the same prior root.
+#[test]
+fn leaf_update_proof_is_bound_to_the_key() {
+    execute_leaf_update(false);
+
+    // The executor only prints what the program panics with, so look for it in the output of the
+    // test executing the forged proof.
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "forged_leaf_update_proof",
+            "--exact",
+            "--ignored",
+            "--nocapture",
+        ])
+        .output()
+        .unwrap();
+    let printed = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.status.success(), "{}", printed);
+    assert!(
+        printed.contains("the Merkle proof isn't of the key to update"),
+        "{}",
+        printed
+    );
+}
+
+/// Passes the Merkle proof of a key off as the proof of another one holding the same leaf, run by
+/// [`leaf_update_proof_is_bound_to_the_key`] to check what the program panics with.
+#[test]
+#[ignore]
+#[should_panic]
+fn forged_leaf_update_proof() {
+    execute_leaf_update(true);
+}
@@ -0,0 +1,43 @@
+//! Checks where `prove --proof-dir` saves proofs, which needs neither the program nor a prover.
+
+use fibonacci_lib::FibonacciInputs;
+use fibonacci_script::{proof::ProofSystem, store::ProofStore};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn same_inputs_are_stored_at_the_same_path(
+        n: u32,
+        offset: u32,
+        nonce: u64,
+        timestamps: (u64, u64),
+    ) {
+        let store = ProofStore::new("proofs".as_ref());
+        let inputs = FibonacciInputs {
+            n,
+            offset,
+            nonce,
+            ..FibonacciInputs::default()
+        };
+        let path = |inputs: &FibonacciInputs| {
+            store.proof_path("0x00", inputs, ProofSystem::Groth16)
+        };
+        // Proving the same inputs at another time lands on the same file...
+        let earlier = FibonacciInputs {
+            timestamp: timestamps.0,
+            ..inputs.clone()
+        };
+        let later = FibonacciInputs {
+            timestamp: timestamps.1,
+            ..inputs.clone()
+        };
+        prop_assert_eq!(path(&earlier), path(&later));
+
+        // ...but proving other inputs doesn't.
+        let other = FibonacciInputs {
+            nonce: nonce.wrapping_add(1),
+            ..inputs
+        };
+        prop_assert_ne!(path(&earlier), path(&other));
+    }
+}